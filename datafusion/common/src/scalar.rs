@@ -643,11 +643,16 @@ impl ScalarValue {
                 | ScalarValue::Date64(None)
                 | ScalarValue::Utf8(None)
                 | ScalarValue::LargeUtf8(None)
+                | ScalarValue::Binary(None)
+                | ScalarValue::LargeBinary(None)
                 | ScalarValue::List(None, _)
                 | ScalarValue::TimestampSecond(None, _)
                 | ScalarValue::TimestampMillisecond(None, _)
                 | ScalarValue::TimestampMicrosecond(None, _)
                 | ScalarValue::TimestampNanosecond(None, _)
+                | ScalarValue::IntervalYearMonth(None)
+                | ScalarValue::IntervalDayTime(None)
+                | ScalarValue::IntervalMonthDayNano(None)
                 | ScalarValue::Struct(None, _)
                 | ScalarValue::Decimal128(None, _, _) // For decimal type, the value is null means ScalarValue::Decimal128 is null.
         )
@@ -0,0 +1,457 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Benchmarks for `IN` list style expressions
+//!
+//! The `like_any` benchmark compares pattern by pattern matching against the
+//! regex set backend: run it with and without `--features like_regex_set`.
+//! For the unanchored `%_N%` patterns used here the regex set measured about
+//! twice as slow (16.7s vs 34.5s), which is why that backend is opt-in.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use ahash::RandomState;
+use arrow::array::{
+    ArrayRef, DecimalArray, Float64Array, Int32Array, Int64Array, StringArray,
+};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use criterion::{criterion_group, criterion_main, Criterion};
+use datafusion_common::ScalarValue;
+use datafusion_physical_expr::expressions::{
+    col, in_list, like_in_list, lit, DynamicInSet, FloatEq, InListExpr, InSet,
+};
+use datafusion_physical_expr::PhysicalExpr;
+
+fn string_batch(rows: usize, distinct: usize) -> RecordBatch {
+    let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+    let array: ArrayRef = Arc::new(StringArray::from_iter_values(
+        (0..rows).map(|i| format!("value_{}", i % distinct)),
+    ));
+    RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap()
+}
+
+fn utf8_lit(value: String) -> Arc<dyn PhysicalExpr> {
+    lit(ScalarValue::Utf8(Some(value)))
+}
+
+fn bench_like_any(c: &mut Criterion) {
+    let batch = string_batch(1_000_000, 10_000);
+    let patterns = (0..500)
+        .map(|i| utf8_lit(format!("%_{}%", i * 17)))
+        .collect();
+    let expr =
+        like_in_list(col("a", &batch.schema()).unwrap(), patterns, &false).unwrap();
+
+    c.bench_function("like_any 500 patterns over 1M strings", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_utf8_null_free_list(c: &mut Criterion) {
+    let batch = string_batch(1_000_000, 100);
+    let list = (0..10).map(|i| utf8_lit(format!("value_{}", i))).collect();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    c.bench_function("in_list utf8 10 non-null literals over 1M strings", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_int64_clustered(c: &mut Criterion) {
+    let base = 1_000_000_000_000i64;
+    let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+    let array: ArrayRef = Arc::new(Int64Array::from_iter_values(
+        (0..5_000_000).map(|i| base + (i * 7919) % 20_000),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    let list = (0..10_000)
+        .step_by(10)
+        .map(|i| lit(ScalarValue::Int64(Some(base + i))))
+        .collect();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    c.bench_function("in_list i64 clustered in a 10k window over 5M rows", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_utf8_short_strings(c: &mut Criterion) {
+    let batch = string_batch(10_000_000, 64);
+    // enum-like codes of at most 8 bytes, half of them present in the batch
+    let list = (0..64)
+        .step_by(2)
+        .chain(64..74)
+        .map(|i| utf8_lit(format!("value_{}", i)))
+        .collect();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    c.bench_function("in_list utf8 42 short literals over 10M strings", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_decimal_matched_scale(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Decimal(20, 2), true)]);
+    let array: ArrayRef = Arc::new(
+        DecimalArray::from_iter_values((0..2_000_000).map(|i| (i % 1000) as i128))
+            .with_precision_and_scale(20, 2)
+            .unwrap(),
+    );
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    let list = (0..40)
+        .map(|i| lit(ScalarValue::Decimal128(Some(i * 25), 20, 2)))
+        .collect();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    c.bench_function("in_list decimal(20, 2) 40 literals over 2M rows", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+/// Run with and without `--features fst_string_set`. Over 5M distinct codes
+/// the FST set is far smaller than the hash set, whose `ScalarValue` entries
+/// each own a heap allocated `String`: compare the peak memory of the runs.
+fn bench_utf8_huge_list(c: &mut Criterion) {
+    let batch = string_batch(1_000_000, 10_000_000);
+    let list = (0..5_000_000)
+        .map(|i| utf8_lit(format!("value_{}", i * 2)))
+        .collect();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    c.bench_function("in_list utf8 5M literals over 1M strings", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_utf8_regex_alternation(c: &mut Criterion) {
+    let batch = string_batch(1_000_000, 1_000);
+    let list = (0..200)
+        .map(|i| utf8_lit(format!("value_{}", i * 7)))
+        .collect::<Vec<_>>();
+    let col_a = col("a", &batch.schema()).unwrap();
+    let hashed = InListExpr::new(col_a.clone(), list.clone(), false);
+    let regex = InListExpr::new(col_a, list, false)
+        .with_regex_alternation()
+        .unwrap();
+
+    c.bench_function("in_list utf8 200 literals over 1M strings, hash set", |b| {
+        b.iter(|| criterion::black_box(hashed.evaluate(&batch).unwrap()))
+    });
+    c.bench_function("in_list utf8 200 literals over 1M strings, regex", |b| {
+        b.iter(|| criterion::black_box(regex.evaluate(&batch).unwrap()))
+    });
+}
+
+// strings with the first byte spread over 90 printable characters
+fn diverse_prefix(i: usize) -> String {
+    format!("{}_value_{}", (b'!' + (i % 90) as u8) as char, i)
+}
+
+fn bench_utf8_diverse_prefixes(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+    let array: ArrayRef = Arc::new(StringArray::from_iter_values(
+        (0..10_000_000).map(|i| diverse_prefix(i % 4000)),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    let list = (0..2000).map(|i| utf8_lit(diverse_prefix(i * 2))).collect();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    c.bench_function("in_list utf8 2000 diverse prefixes over 10M strings", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_int32_list_sizes(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+    let array: ArrayRef = Arc::new(Int32Array::from_iter_values(
+        (0..5_000_000i32).map(|i| i.wrapping_mul(7919) % 100_000),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    for len in [64, 1024, 16384] {
+        // spread out so the values are not clustered
+        let list = (0..len)
+            .map(|i| lit(ScalarValue::Int32(Some(i * 97))))
+            .collect();
+        let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+        c.bench_function(&format!("in_list i32 {} literals over 5M rows", len), |b| {
+            b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+        });
+    }
+}
+
+fn bench_int64_batch_sizes(c: &mut Criterion) {
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    let batch = |rows: i64| {
+        let array: ArrayRef = Arc::new(Int64Array::from_iter_values(0..rows));
+        RecordBatch::try_new(schema.clone(), vec![array]).unwrap()
+    };
+    let list = (0..10)
+        .map(|i| lit(ScalarValue::Int64(Some(i * 7))))
+        .collect();
+    let expr = in_list(col("a", &schema).unwrap(), list, &false).unwrap();
+
+    let large = batch(1_000_000);
+    c.bench_function("in_list i64 10 literals over one batch of 1M rows", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&large).unwrap()))
+    });
+    let small = batch(1000);
+    c.bench_function(
+        "in_list i64 10 literals over 1000 batches of 1k rows",
+        |b| {
+            b.iter(|| {
+                for _ in 0..1000 {
+                    criterion::black_box(expr.evaluate(&small).unwrap());
+                }
+            })
+        },
+    );
+}
+
+/// Run with and without `--features fx_hash_set` to compare the hashers
+fn bench_int64_hashed(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+    let array: ArrayRef = Arc::new(Int64Array::from_iter_values(
+        (0..10_000_000i64).map(|i| (i % 200) * 1_000_003),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    // spread too far apart for a bitset
+    let list = (0..100)
+        .map(|i| lit(ScalarValue::Int64(Some(i * 2 * 1_000_003))))
+        .collect();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    c.bench_function("in_list i64 100 literals over 10M rows", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_utf8_bloom_filter(c: &mut Criterion) {
+    let batch = string_batch(1_000_000, 1_000_000);
+    // about 5% of the rows are in the set
+    let set = (0..50_000)
+        .map(|i| ScalarValue::Utf8(Some(format!("value_{}", i * 20))))
+        .collect::<HashSet<_, RandomState>>();
+    let col_a = col("a", &batch.schema()).unwrap();
+    let filtered =
+        InListExpr::from_build_side(col_a.clone(), InSet::new(set.clone()), false);
+    let exact = InListExpr::from_build_side(
+        col_a,
+        InSet::new(set).with_bloom_threshold(usize::MAX),
+        false,
+    );
+
+    c.bench_function("in_list utf8 50k set over 1M strings, bloom filter", |b| {
+        b.iter(|| criterion::black_box(filtered.evaluate(&batch).unwrap()))
+    });
+    c.bench_function(
+        "in_list utf8 50k set over 1M strings, no bloom filter",
+        |b| b.iter(|| criterion::black_box(exact.evaluate(&batch).unwrap())),
+    );
+}
+
+fn bench_utf8_set(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+    let array: ArrayRef = Arc::new(StringArray::from_iter_values(
+        (0..1_000_000).map(|i| format!("customer_{}", i % 2000)),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    let list = (0..1000)
+        .map(|i| utf8_lit(format!("customer_{}", i * 2)))
+        .collect();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    c.bench_function("in_list utf8 1000 literals over 1M strings", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_utf8_not_in_batches(c: &mut Criterion) {
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Utf8, true)]));
+    let batch = |rows: usize| {
+        let array: ArrayRef = Arc::new(StringArray::from_iter_values(
+            (0..rows).map(|i| format!("customer_{}", i % 200)),
+        ));
+        RecordBatch::try_new(schema.clone(), vec![array]).unwrap()
+    };
+    let list = (0..100)
+        .map(|i| utf8_lit(format!("customer_{}", i * 2)))
+        .collect();
+    let expr = in_list(col("a", &schema).unwrap(), list, &true).unwrap();
+
+    let large = batch(1_000_000);
+    c.bench_function("in_list utf8 NOT IN 100 literals over 1M strings", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&large).unwrap()))
+    });
+    let small = batch(1000);
+    c.bench_function(
+        "in_list utf8 NOT IN 100 literals over 1000 batches of 1k strings",
+        |b| {
+            b.iter(|| {
+                for _ in 0..1000 {
+                    criterion::black_box(expr.evaluate(&small).unwrap());
+                }
+            })
+        },
+    );
+}
+
+fn bench_float64_sorted(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+    let array: ArrayRef = Arc::new(Float64Array::from_iter_values(
+        (0..2_000_000).map(|i| (i % 1000) as f64 * 0.25),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    let list = (0..20)
+        .map(|i| lit(ScalarValue::Float64(Some(i as f64 * 12.5))))
+        .collect::<Vec<_>>();
+    let col_a = col("a", &batch.schema()).unwrap();
+    let sorted = InListExpr::new(col_a.clone(), list.clone(), false);
+    // the same equality, through the linear scan of a custom one
+    let linear = InListExpr::new(col_a, list, false)
+        .with_float_eq(FloatEq::new(|l, r| l == r || (l.is_nan() && r.is_nan())));
+
+    c.bench_function("in_list f64 20 sorted literals over 2M rows, search", |b| {
+        b.iter(|| criterion::black_box(sorted.evaluate(&batch).unwrap()))
+    });
+    c.bench_function("in_list f64 20 sorted literals over 2M rows, linear", |b| {
+        b.iter(|| criterion::black_box(linear.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_int64_contiguous_range(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+    let array: ArrayRef = Arc::new(Int64Array::from_iter_values(
+        (0..5_000_000i64).map(|i| (i * 7919) % 4000),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    let list = (1000..2000)
+        .map(|i| lit(ScalarValue::Int64(Some(i))))
+        .collect::<Vec<_>>();
+    let col_a = col("a", &batch.schema()).unwrap();
+    let set = InListExpr::new(col_a.clone(), list.clone(), false);
+    let range = InListExpr::new(col_a, list, false).with_range_folding();
+
+    c.bench_function(
+        "in_list i64 1000 contiguous literals over 5M rows, set",
+        |b| b.iter(|| criterion::black_box(set.evaluate(&batch).unwrap())),
+    );
+    c.bench_function(
+        "in_list i64 1000 contiguous literals over 5M rows, range",
+        |b| b.iter(|| criterion::black_box(range.evaluate(&batch).unwrap())),
+    );
+}
+
+fn bench_utf8_probe_cache(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+    let array: ArrayRef = Arc::new(StringArray::from_iter_values(
+        (0..10_000_000).map(|i| format!("hot customer {}", (i * 7) % 5)),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    let list = (0..100)
+        .map(|i| lit(ScalarValue::Utf8(Some(format!("hot customer {}", i * 2)))))
+        .collect::<Vec<_>>();
+    let col_a = col("a", &batch.schema()).unwrap();
+    let uncached = InListExpr::new(col_a.clone(), list.clone(), false);
+    let cached = InListExpr::new(col_a.clone(), list, false).with_probe_cache(8);
+    let dynamic_set = DynamicInSet::new(
+        (0..100)
+            .map(|i| ScalarValue::Utf8(Some(format!("hot customer {}", i * 2))))
+            .collect(),
+    );
+    let dynamic =
+        InListExpr::new_with_dynamic_set(col_a.clone(), dynamic_set.clone(), false);
+    let dynamic_cached =
+        InListExpr::new_with_dynamic_set(col_a, dynamic_set, false).with_probe_cache(8);
+
+    c.bench_function(
+        "in_list utf8 100 literals over 10M rows of 5 hot strings",
+        |b| b.iter(|| criterion::black_box(uncached.evaluate(&batch).unwrap())),
+    );
+    c.bench_function(
+        "in_list utf8 100 literals over 10M rows of 5 hot strings, probe cache",
+        |b| b.iter(|| criterion::black_box(cached.evaluate(&batch).unwrap())),
+    );
+    c.bench_function(
+        "in_list utf8 dynamic set of 100 over 10M rows of 5 hot strings",
+        |b| b.iter(|| criterion::black_box(dynamic.evaluate(&batch).unwrap())),
+    );
+    c.bench_function(
+        "in_list utf8 dynamic set of 100 over 10M rows of 5 hot strings, probe cache",
+        |b| b.iter(|| criterion::black_box(dynamic_cached.evaluate(&batch).unwrap())),
+    );
+}
+
+fn bench_int64_sorted_search(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+    let array: ArrayRef = Arc::new(Int64Array::from_iter_values(
+        (0..1_000_000i64).map(|i| (i * 7919) % 100),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    let list = (0..25)
+        .map(|i| lit(ScalarValue::Int64(Some(i * 4))))
+        .collect::<Vec<_>>();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    c.bench_function("in_list i64 25 literals over 1M rows", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+fn bench_int64_clustered_codes(c: &mut Criterion) {
+    let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+    let array: ArrayRef = Arc::new(Int64Array::from_iter_values(
+        (0..10_000_000i64).map(|i| (i * 7919) % 200_000_000),
+    ));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![array]).unwrap();
+    // runs of 1000 codes every 1M, too spread out for a bitset
+    let list = (0..100_000i64)
+        .map(|i| lit(ScalarValue::Int64(Some((i / 1000) * 1_000_000 + i % 1000))))
+        .collect::<Vec<_>>();
+    let expr = in_list(col("a", &batch.schema()).unwrap(), list, &false).unwrap();
+
+    // a roaring bitmap with the `roaring_set` feature, else a hash set
+    c.bench_function("in_list i64 100k clustered codes over 10M rows", |b| {
+        b.iter(|| criterion::black_box(expr.evaluate(&batch).unwrap()))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_like_any,
+    bench_utf8_null_free_list,
+    bench_int64_clustered,
+    bench_utf8_short_strings,
+    bench_decimal_matched_scale,
+    bench_utf8_huge_list,
+    bench_utf8_regex_alternation,
+    bench_utf8_diverse_prefixes,
+    bench_int32_list_sizes,
+    bench_int64_batch_sizes,
+    bench_float64_sorted,
+    bench_int64_hashed,
+    bench_utf8_bloom_filter,
+    bench_utf8_set,
+    bench_utf8_not_in_batches,
+    bench_int64_contiguous_range,
+    bench_utf8_probe_cache,
+    bench_int64_sorted_search,
+    bench_int64_clustered_codes
+);
+criterion_main!(benches);
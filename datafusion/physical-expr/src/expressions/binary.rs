@@ -16,6 +16,7 @@
 // under the License.
 
 use std::convert::TryInto;
+use std::hash::{Hash, Hasher};
 use std::{any::Any, sync::Arc};
 
 use arrow::array::TimestampMillisecondArray;
@@ -1041,6 +1042,24 @@ impl PhysicalExpr for BinaryExpr {
         self.evaluate_with_resolved_args(left, &left_data_type, right, &right_data_type)
             .map(|a| ColumnarValue::Array(a))
     }
+
+    fn is_constant(&self) -> bool {
+        self.left.is_constant() && self.right.is_constant()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.left.clone(), self.right.clone()]
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        other
+            .downcast_ref::<Self>()
+            .map_or(false, |o| self.op == o.op)
+    }
+
+    fn node_hash(&self, mut state: &mut dyn Hasher) {
+        self.op.hash(&mut state)
+    }
 }
 
 /// The binary_array_op_dyn_scalar macro includes types that extend beyond the primitive,
@@ -15,6 +15,7 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::hash::{Hash, Hasher};
 use std::{any::Any, sync::Arc};
 
 use crate::expressions::try_cast;
@@ -258,6 +259,46 @@ impl PhysicalExpr for CaseExpr {
             self.case_when_no_expr(batch)
         }
     }
+
+    fn is_constant(&self) -> bool {
+        self.expr.iter().all(|e| e.is_constant())
+            && self
+                .when_then_expr
+                .iter()
+                .all(|(w, t)| w.is_constant() && t.is_constant())
+            && self.else_expr.iter().all(|e| e.is_constant())
+    }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        self.expr
+            .iter()
+            .cloned()
+            .chain(
+                self.when_then_expr
+                    .iter()
+                    .flat_map(|(w, t)| [w.clone(), t.clone()]),
+            )
+            .chain(self.else_expr.iter().cloned())
+            .collect()
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        // the children line up when both have the same shape
+        other.downcast_ref::<Self>().map_or(false, |o| {
+            self.expr.is_some() == o.expr.is_some()
+                && self.when_then_expr.len() == o.when_then_expr.len()
+                && self.else_expr.is_some() == o.else_expr.is_some()
+        })
+    }
+
+    fn node_hash(&self, mut state: &mut dyn Hasher) {
+        (
+            self.expr.is_some(),
+            self.when_then_expr.len(),
+            self.else_expr.is_some(),
+        )
+            .hash(&mut state)
+    }
 }
 
 /// Create a CASE expression
@@ -17,6 +17,7 @@
 
 use std::any::Any;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use crate::PhysicalExpr;
@@ -93,6 +94,24 @@ impl PhysicalExpr for CastExpr {
         let value = self.expr.evaluate(batch)?;
         cast_column(&value, &self.cast_type, &self.cast_options)
     }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<Self>().map_or(false, |o| {
+            self.cast_type == o.cast_type && self.cast_options.safe == o.cast_options.safe
+        })
+    }
+
+    fn node_hash(&self, mut state: &mut dyn Hasher) {
+        (&self.cast_type, self.cast_options.safe).hash(&mut state)
+    }
 }
 
 /// Internal cast function for casting ColumnarValue -> ColumnarValue for cast_type
@@ -17,6 +17,7 @@
 
 //! Column expression
 
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use arrow::{
@@ -86,6 +87,14 @@ impl PhysicalExpr for Column {
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
         Ok(ColumnarValue::Array(batch.column(self.index).clone()))
     }
+
+    fn node_eq(&self, other: &dyn std::any::Any) -> bool {
+        other.downcast_ref::<Self>() == Some(self)
+    }
+
+    fn node_hash(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state)
+    }
 }
 
 /// Create a column expression
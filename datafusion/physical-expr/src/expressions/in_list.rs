@@ -18,7 +18,12 @@
 //! InList expression
 
 use std::any::Any;
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use std::sync::Arc;
 
 use arrow::array::GenericStringArray;
@@ -27,18 +32,31 @@ use arrow::array::{
     Int64Array, Int8Array, OffsetSizeTrait, UInt16Array, UInt32Array, UInt64Array,
     UInt8Array,
 };
-use arrow::datatypes::ArrowPrimitiveType;
+use arrow::datatypes::{
+    ArrowNativeType, ArrowPrimitiveType, Float32Type, Float64Type, Int16Type, Int32Type,
+    Int64Type, Int8Type, IntervalUnit, TimeUnit, UInt16Type, UInt32Type, UInt64Type,
+    UInt8Type,
+};
 use arrow::{
-    datatypes::{DataType, Schema},
+    datatypes::{DataType, Field, Schema},
     record_batch::RecordBatch,
 };
 
-use crate::{expressions, PhysicalExpr};
+use crate::datetime_expressions::date_trunc_single;
+use crate::expressions::struct_in_list::compare_row_values;
+use crate::expressions::StructInListExpr;
+use crate::{expressions, physical_expr_eq, physical_expr_hash, PhysicalExpr};
+use ahash::RandomState;
 use arrow::array::*;
 use arrow::buffer::{Buffer, MutableBuffer};
+use arrow::compute::kernels;
+use arrow::util::bit_util;
+use chrono::Timelike;
 use datafusion_common::ScalarValue;
 use datafusion_common::{DataFusionError, Result};
-use datafusion_expr::ColumnarValue;
+use datafusion_expr::{ColumnarValue, Operator};
+use once_cell::sync::OnceCell;
+use parking_lot::{MappedRwLockReadGuard, Mutex, RwLock, RwLockReadGuard};
 
 /// Size at which to use a Set rather than Vec for `IN` / `NOT IN`
 /// Value chosen by the benchmark at
@@ -46,10 +64,15 @@ use datafusion_expr::ColumnarValue;
 /// TODO: add switch codeGen in In_List
 static OPTIMIZER_INSET_THRESHOLD: usize = 30;
 
+// Null slots are compared too, then masked by the null buffer. Reading them is
+// sound as arrays uphold the invariants for every slot, null or not: any bit
+// pattern is a valid primitive, and string offsets are in bounds with valid
+// UTF-8 between them, as checked by `ArrayData::validate_full` for all slots.
 macro_rules! compare_op_scalar {
     ($left: expr, $right:expr, $op:expr) => {{
         let null_bit_buffer = $left.data().null_buffer().cloned();
 
+        // SAFETY: `i` is in bounds, and null slots hold valid values as above
         let comparison =
             (0..$left.len()).map(|i| unsafe { $op($left.value_unchecked(i), $right) });
         // same as $left.len()
@@ -71,682 +94,10027 @@ macro_rules! compare_op_scalar {
 }
 
 /// InList
-#[derive(Debug)]
+///
+/// Float values compare with IEEE 754 equality, consistent with
+/// [`ScalarValue`]: infinities equal themselves, subnormals compare exactly and
+/// `-0.0` equals `0.0`. NaN is the only value with special equality, as
+/// [`ScalarValue`] considers it equal to itself.
+///
+/// Intervals compare by exact component equality, also like [`ScalarValue`],
+/// not by normalized duration: a `DayTime` interval of 1 day does not equal
+/// one of 86400000 milliseconds, nor does a `MonthDayNano` interval of 1 day
+/// equal one of 24 hours, so inputs must be normalized upstream if they
+/// should. `YearMonth` intervals count months, so 1 year is 12 months.
+///
+/// Binary values compare as raw bytes with no interpretation: an integer
+/// stored little-endian does not equal the same integer stored big-endian.
+#[derive(Debug, Clone)]
 pub struct InListExpr {
     expr: Arc<dyn PhysicalExpr>,
     list: Vec<Arc<dyn PhysicalExpr>>,
     negated: bool,
     set: Option<InSet>,
+    dynamic_set: Option<DynamicInSet>,
+    float_eq: Option<FloatEq>,
+    lazy_set: Option<OnceCell<InSet>>,
+    list_sorted: OnceCell<Option<bool>>,
+    /// Literals dropped by [`InListExpr::coerce_literals`] and why, if
+    /// collected
+    dropped_literals: Option<Vec<(ScalarValue, String)>>,
+    trim_trailing_whitespace: bool,
+    /// Boolean expression marking rows whose value is treated as NULL
+    null_indicator: Option<Arc<dyn PhysicalExpr>>,
+    sorted_merge: Option<SortedMergeInSet>,
+    /// Whether the caller guarantees input arrays are sorted ascending
+    input_sorted: bool,
+    null_handling: NullHandling,
+    /// Input values found in the list so far, if tracked
+    matched: Option<Arc<Mutex<MatchedKeys>>>,
+    /// Input values found in the list by the most recent `evaluate` call, if
+    /// tracked
+    matched_last: Option<Arc<Mutex<MatchedKeys>>>,
+    /// The list compiled to a single anchored regex alternation, if enabled,
+    /// and whether the list has a NULL entry
+    #[cfg(feature = "regex_expressions")]
+    regex: Option<(regex::Regex, bool)>,
+    /// `date_trunc` granularity applied to timestamp inputs, if any
+    date_trunc: Option<String>,
+    /// Observations choosing the representation of a list, if adaptive
+    adaptive: Option<Arc<AdaptiveState>>,
+    ignore_diacritics: bool,
+    /// List length above which a list of literals is probed with a set
+    set_threshold: usize,
+    /// Largest string or binary literal accepted in the list, if limited
+    max_literal_bytes: Option<usize>,
+    /// The values of the list evaluated once by the first scan of it, if it
+    /// is constant
+    list_values: OnceCell<Option<Vec<ScalarValue>>>,
+    /// `list_values` cast once to the numeric type of the first input of
+    /// another type
+    coerced_list_values: OnceCell<(DataType, Vec<ScalarValue>)>,
+    /// Smallest and largest non-null list values, computed on first use
+    value_bounds: OnceCell<Option<(ScalarValue, ScalarValue)>>,
+    /// Whether a contiguous run of integers is evaluated as a range
+    range_folding: bool,
+    /// The list folded to a range, if enabled and it is a contiguous run
+    range: Option<IntegerRange>,
+    /// Membership of recently probed strings, if enabled
+    probe_cache: Option<Arc<ProbeCaches>>,
 }
 
-/// InSet
+/// Probe caches of an [`InListExpr`], see [`InListExpr::with_probe_cache`].
+/// Shared by copies of the expression, each evaluation takes a cache of its
+/// own for the whole batch, so partitions evaluating concurrently never share
+/// one and only lock before and after a batch rather than for every value.
 #[derive(Debug)]
-pub struct InSet {
-    set: HashSet<ScalarValue>,
+struct ProbeCaches {
+    capacity: usize,
+    /// Caches not taken by an evaluation
+    idle: Mutex<Vec<ProbeCache>>,
 }
 
-impl InSet {
-    pub fn new(set: HashSet<ScalarValue>) -> Self {
-        Self { set }
+impl ProbeCaches {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            idle: Mutex::new(vec![]),
+        }
     }
 
-    pub fn get_set(&self) -> &HashSet<ScalarValue> {
-        &self.set
+    /// Evaluate string `array` with a cache taken for the duration, see
+    /// [`ProbeCache::evaluate`], or return `None` for other types
+    fn evaluate(
+        &self,
+        array: &ArrayRef,
+        generation: u64,
+        negated: bool,
+        contains: impl Fn(&str) -> bool,
+    ) -> Option<ColumnarValue> {
+        if !matches!(array.data_type(), DataType::Utf8 | DataType::LargeUtf8) {
+            return None;
+        }
+        let mut cache = self
+            .idle
+            .lock()
+            .pop()
+            .unwrap_or_else(|| ProbeCache::new(self.capacity));
+        let result = cache.evaluate(array, generation, negated, contains);
+        self.idle.lock().push(cache);
+        result
     }
 }
 
-macro_rules! make_contains {
-    ($ARRAY:expr, $LIST_VALUES:expr, $NEGATED:expr, $SCALAR_VALUE:ident, $ARRAY_TYPE:ident) => {{
-        let array = $ARRAY.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
-
-        let contains_null = $LIST_VALUES
-            .iter()
-            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
-        let values = $LIST_VALUES
-            .iter()
-            .flat_map(|expr| match expr {
-                ColumnarValue::Scalar(s) => match s {
-                    ScalarValue::$SCALAR_VALUE(Some(v)) => Some(*v),
-                    ScalarValue::$SCALAR_VALUE(None) => None,
-                    ScalarValue::Utf8(None) => None,
-                    datatype => unimplemented!("Unexpected type {} for InList", datatype),
-                },
-                ColumnarValue::Array(_) => {
-                    unimplemented!("InList does not yet support nested columns.")
-                }
-            })
-            .collect::<Vec<_>>();
-
-        Ok(ColumnarValue::Array(Arc::new(
-            array
-                .iter()
-                .map(|x| {
-                    let contains = x.map(|x| values.contains(&x));
-                    match contains {
-                        Some(true) => {
-                            if $NEGATED {
-                                Some(false)
-                            } else {
-                                Some(true)
-                            }
-                        }
-                        Some(false) => {
-                            if contains_null {
-                                None
-                            } else if $NEGATED {
-                                Some(true)
-                            } else {
-                                Some(false)
-                            }
-                        }
-                        None => None,
-                    }
-                })
-                .collect::<BooleanArray>(),
-        )))
-    }};
+/// Membership of the most recently probed strings in a set, used by one
+/// evaluation at a time
+#[derive(Debug)]
+struct ProbeCache {
+    capacity: usize,
+    /// Generation of the set the entries were probed in
+    generation: u64,
+    /// Tags of the cached values, see [`probe_cache_tag`]
+    tags: Vec<u64>,
+    /// Cached values, whether each is in the set, and when it was last used
+    entries: Vec<(Box<str>, bool, u64)>,
+    /// Number of lookups so far
+    clock: u64,
 }
 
-macro_rules! make_contains_primitive {
-    ($ARRAY:expr, $LIST_VALUES:expr, $NEGATED:expr, $SCALAR_VALUE:ident, $ARRAY_TYPE:ident) => {{
-        let array = $ARRAY.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+/// The length of `v` and its last bytes, which tell most strings apart without
+/// hashing them, as values often differ at their end more than at their start
+fn probe_cache_tag(v: &str) -> u64 {
+    let bytes = v.as_bytes();
+    let tail = &bytes[bytes.len().saturating_sub(6)..];
+    tail.iter()
+        .fold(bytes.len() as u64 & 0xFFFF, |tag, b| (tag << 8) | *b as u64)
+}
 
-        let contains_null = $LIST_VALUES
-            .iter()
-            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
-        let values = $LIST_VALUES
-            .iter()
-            .flat_map(|expr| match expr {
-                ColumnarValue::Scalar(s) => match s {
-                    ScalarValue::$SCALAR_VALUE(Some(v)) => Some(*v),
-                    ScalarValue::$SCALAR_VALUE(None) => None,
-                    ScalarValue::Utf8(None) => None,
-                    datatype => unimplemented!("Unexpected type {} for InList", datatype),
-                },
-                ColumnarValue::Array(_) => {
-                    unimplemented!("InList does not yet support nested columns.")
-                }
-            })
-            .collect::<Vec<_>>();
+impl ProbeCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            generation: 0,
+            tags: Vec::with_capacity(capacity),
+            entries: Vec::with_capacity(capacity),
+            clock: 0,
+        }
+    }
 
-        if $NEGATED {
-            if contains_null {
-                Ok(ColumnarValue::Array(Arc::new(
-                    array
-                        .iter()
-                        .map(|x| match x.map(|v| !values.contains(&v)) {
-                            Some(true) => None,
-                            x => x,
-                        })
-                        .collect::<BooleanArray>(),
-                )))
-            } else {
-                Ok(ColumnarValue::Array(Arc::new(
-                    not_in_list_primitive(array, &values)?,
-                )))
-            }
-        } else {
-            if contains_null {
-                Ok(ColumnarValue::Array(Arc::new(
-                    array
-                        .iter()
-                        .map(|x| match x.map(|v| values.contains(&v)) {
-                            Some(false) => None,
-                            x => x,
-                        })
-                        .collect::<BooleanArray>(),
-                )))
-            } else {
-                Ok(ColumnarValue::Array(Arc::new(in_list_primitive(
-                    array, &values,
-                )?)))
+    // whether `v` is in the set, probing it with `contains` unless cached. A
+    // value probed replaces the least recently used entry of a full cache.
+    fn contains(&mut self, v: &str, contains: &impl Fn(&str) -> bool) -> bool {
+        self.clock += 1;
+        let tag = probe_cache_tag(v);
+        for (i, _) in self.tags.iter().enumerate().filter(|(_, t)| **t == tag) {
+            let entry = &mut self.entries[i];
+            if entry.0.as_ref() == v {
+                entry.2 = self.clock;
+                return entry.1;
             }
         }
-    }};
-}
+        let found = contains(v);
+        let entry = (Box::from(v), found, self.clock);
+        if self.entries.len() < self.capacity {
+            self.tags.push(tag);
+            self.entries.push(entry);
+        } else if let Some(lru) =
+            (0..self.entries.len()).min_by_key(|i| self.entries[*i].2)
+        {
+            self.tags[lru] = tag;
+            self.entries[lru] = entry;
+        }
+        found
+    }
 
-macro_rules! set_contains_with_negated {
-    ($ARRAY:expr, $LIST_VALUES:expr, $NEGATED:expr) => {{
-        if $NEGATED {
-            return Ok(ColumnarValue::Array(Arc::new(
-                $ARRAY
-                    .iter()
-                    .map(|x| x.map(|v| !$LIST_VALUES.contains(&v.try_into().unwrap())))
-                    .collect::<BooleanArray>(),
-            )));
-        } else {
-            return Ok(ColumnarValue::Array(Arc::new(
-                $ARRAY
+    /// Evaluate string `array` against the set of `generation`, probed with
+    /// `contains` for values not cached, or return `None` for other types.
+    /// Entries of another generation are dropped first.
+    fn evaluate(
+        &mut self,
+        array: &ArrayRef,
+        generation: u64,
+        negated: bool,
+        contains: impl Fn(&str) -> bool,
+    ) -> Option<ColumnarValue> {
+        fn probe<T: OffsetSizeTrait>(
+            cache: &mut ProbeCache,
+            array: &ArrayRef,
+            negated: bool,
+            contains: impl Fn(&str) -> bool,
+        ) -> ColumnarValue {
+            let array = array
+                .as_any()
+                .downcast_ref::<GenericStringArray<T>>()
+                .unwrap();
+            ColumnarValue::Array(Arc::new(
+                array
                     .iter()
-                    .map(|x| x.map(|v| $LIST_VALUES.contains(&v.try_into().unwrap())))
+                    .map(|x| x.map(|v| cache.contains(v, &contains) != negated))
                     .collect::<BooleanArray>(),
-            )));
+            ))
         }
-    }};
+        if generation != self.generation {
+            self.tags.clear();
+            self.entries.clear();
+            self.generation = generation;
+        }
+        match array.data_type() {
+            DataType::Utf8 => Some(probe::<i32>(self, array, negated, contains)),
+            DataType::LargeUtf8 => Some(probe::<i64>(self, array, negated, contains)),
+            _ => None,
+        }
+    }
 }
 
-// whether each value on the left (can be null) is contained in the non-null list
-fn in_list_primitive<T: ArrowPrimitiveType>(
-    array: &PrimitiveArray<T>,
-    values: &[<T as ArrowPrimitiveType>::Native],
-) -> Result<BooleanArray> {
-    compare_op_scalar!(
-        array,
-        values,
-        |x, v: &[<T as ArrowPrimitiveType>::Native]| v.contains(&x)
-    )
+/// Constant integer list values forming a single contiguous run once
+/// deduplicated, such as `(3, 1, 2, 2)`, evaluated as `low <= x AND x <= high`.
+/// See [`InListExpr::with_range_folding`].
+#[derive(Debug, Clone)]
+struct IntegerRange {
+    low: ScalarValue,
+    high: ScalarValue,
+    /// Whether the list also has NULL entries
+    contains_null: bool,
 }
 
-// whether each value on the left (can be null) is contained in the non-null list
-fn not_in_list_primitive<T: ArrowPrimitiveType>(
-    array: &PrimitiveArray<T>,
-    values: &[<T as ArrowPrimitiveType>::Native],
-) -> Result<BooleanArray> {
-    compare_op_scalar!(
-        array,
-        values,
-        |x, v: &[<T as ArrowPrimitiveType>::Native]| !v.contains(&x)
-    )
+impl IntegerRange {
+    fn try_new(list: &[Arc<dyn PhysicalExpr>]) -> Option<Self> {
+        let values = static_filter_values(list)?;
+        let data_type = values.first()?.get_datatype();
+        let mut contains_null = false;
+        let mut keys = Vec::with_capacity(values.len());
+        for value in values {
+            if value.get_datatype() != data_type {
+                return None;
+            }
+            if value.is_null() {
+                contains_null = true;
+                continue;
+            }
+            let key = match &value {
+                ScalarValue::Int8(Some(v)) => *v as i128,
+                ScalarValue::Int16(Some(v)) => *v as i128,
+                ScalarValue::Int32(Some(v)) => *v as i128,
+                ScalarValue::Int64(Some(v)) => *v as i128,
+                ScalarValue::UInt8(Some(v)) => *v as i128,
+                ScalarValue::UInt16(Some(v)) => *v as i128,
+                ScalarValue::UInt32(Some(v)) => *v as i128,
+                ScalarValue::UInt64(Some(v)) => *v as i128,
+                _ => return None,
+            };
+            keys.push((key, value));
+        }
+        keys.sort_unstable_by_key(|(key, _)| *key);
+        keys.dedup_by_key(|(key, _)| *key);
+        let len = keys.len() as i128;
+        let (high_key, high) = keys.pop()?;
+        let (low_key, low) = match keys.first() {
+            Some((key, value)) => (*key, value.clone()),
+            None => (high_key, high.clone()),
+        };
+        (high_key - low_key + 1 == len).then(|| Self {
+            low,
+            high,
+            contains_null,
+        })
+    }
 }
 
-// whether each value on the left (can be null) is contained in the non-null list
-fn in_list_utf8<OffsetSize: OffsetSizeTrait>(
-    array: &GenericStringArray<OffsetSize>,
-    values: &[&str],
-) -> Result<BooleanArray> {
-    compare_op_scalar!(array, values, |x, v: &[&str]| v.contains(&x))
+/// Number of batches a list is scanned for before an adaptive [`InListExpr`]
+/// chooses its representation
+const ADAPTIVE_WARMUP_BATCHES: usize = 8;
+
+/// Hit rate of a linearly scanned list observed during warm-up, and the set
+/// chosen to replace the scan after it, if any. Shared by copies of an
+/// [`InListExpr`].
+#[derive(Debug, Default)]
+struct AdaptiveState {
+    /// Batches, non-null rows and rows found in the list so far
+    observed: Mutex<(usize, usize, usize)>,
+    choice: OnceCell<Option<InSet>>,
 }
 
-fn not_in_list_utf8<OffsetSize: OffsetSizeTrait>(
-    array: &GenericStringArray<OffsetSize>,
-    values: &[&str],
-) -> Result<BooleanArray> {
-    compare_op_scalar!(array, values, |x, v: &[&str]| !v.contains(&x))
+/// How NULL input values and NULL list entries compare in an [`InListExpr`].
+/// Every representation of the list honors the mode identically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullHandling {
+    /// Three-valued logic: a NULL input gives NULL, and so does a value not
+    /// in a list that has a NULL entry
+    SqlStandard,
+    /// NULL equals NULL: a NULL input matches a NULL list entry, and a value
+    /// not in the list never gives NULL
+    NullEqualsNull,
+    /// NULL equals nothing, not even NULL: the result is never NULL, with a
+    /// NULL input not matching
+    NullEqualsNothing,
 }
 
-//check all filter values of In clause are static.
-//include `CastExpr + Literal` or `Literal`
-fn check_all_static_filter_expr(list: &[Arc<dyn PhysicalExpr>]) -> bool {
-    list.iter().all(|v| {
-        let cast = v.as_any().downcast_ref::<expressions::CastExpr>();
-        if let Some(c) = cast {
-            c.expr()
-                .as_any()
-                .downcast_ref::<expressions::Literal>()
-                .is_some()
-        } else {
-            let cast = v.as_any().downcast_ref::<expressions::Literal>();
-            cast.is_some()
-        }
-    })
+impl Default for NullHandling {
+    fn default() -> Self {
+        Self::SqlStandard
+    }
 }
 
-fn cast_static_filter_to_set(list: &[Arc<dyn PhysicalExpr>]) -> HashSet<ScalarValue> {
-    HashSet::from_iter(list.iter().map(|expr| {
-        if let Some(cast) = expr.as_any().downcast_ref::<expressions::CastExpr>() {
-            cast.expr()
-                .as_any()
-                .downcast_ref::<expressions::Literal>()
-                .unwrap()
-                .value()
-                .clone()
-        } else {
-            expr.as_any()
-                .downcast_ref::<expressions::Literal>()
-                .unwrap()
-                .value()
-                .clone()
-        }
-    }))
+/// Three-valued membership of a single value, see
+/// [`InListExpr::as_scalar_predicate`]
+pub type ScalarPredicate = Box<dyn Fn(&ScalarValue) -> Option<bool> + Send + Sync>;
+
+/// Equality function used in place of exact equality when comparing float
+/// values, e.g. a ULP-based comparison. `Float32` values are widened to `f64`.
+#[derive(Clone)]
+pub struct FloatEq(Arc<dyn Fn(f64, f64) -> bool + Send + Sync>);
+
+impl FloatEq {
+    /// Create a new float equality function
+    pub fn new(eq: impl Fn(f64, f64) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(eq))
+    }
+
+    fn eq(&self, left: f64, right: f64) -> bool {
+        (self.0)(left, right)
+    }
 }
 
-impl InListExpr {
-    /// Create a new InList expression
-    pub fn new(
-        expr: Arc<dyn PhysicalExpr>,
-        list: Vec<Arc<dyn PhysicalExpr>>,
-        negated: bool,
-    ) -> Self {
-        if list.len() > OPTIMIZER_INSET_THRESHOLD && check_all_static_filter_expr(&list) {
-            Self {
-                expr,
-                set: Some(InSet::new(cast_static_filter_to_set(&list))),
-                list,
-                negated,
-            }
-        } else {
-            Self {
-                expr,
-                list,
-                negated,
-                set: None,
-            }
+impl std::fmt::Debug for FloatEq {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "FloatEq")
+    }
+}
+
+/// Comparison keys of the matched input values, see
+/// `InListExpr::comparison_key`, and the type of the input they came from
+#[derive(Debug, Default)]
+struct MatchedKeys {
+    data_type: Option<DataType>,
+    keys: HashSet<ScalarValue>,
+}
+
+/// InSet
+#[derive(Debug, Clone)]
+pub struct InSet {
+    set: HashSet<ScalarValue, RandomState>,
+    /// Specialized representation of `set` probed instead of it, if any
+    native: Option<NativeSet>,
+    /// Whether the list the set was built from had NULL entries, making
+    /// values not in the set NULL rather than false
+    contains_null: bool,
+    /// The set cast to the type of the first numeric input of another type
+    coerced: OnceCell<(DataType, Box<InSet>)>,
+    /// Prefilter of the string values tested before the set, if large
+    bloom: Option<BloomFilter>,
+}
+
+impl InSet {
+    pub fn new(set: HashSet<ScalarValue, RandomState>) -> Self {
+        let native = NativeSet::try_new(&set);
+        let contains_null = set.iter().any(|v| v.is_null());
+        let bloom = BloomFilter::try_new(&set, native.as_ref(), BLOOM_FILTER_THRESHOLD);
+        Self {
+            set,
+            native,
+            contains_null,
+            coerced: OnceCell::new(),
+            bloom,
         }
     }
 
-    /// Input expression
-    pub fn expr(&self) -> &Arc<dyn PhysicalExpr> {
-        &self.expr
+    /// Prefilter lookups with a bloom filter if the set has at least
+    /// `threshold` string values, rather than the default of 10000
+    pub fn with_bloom_threshold(mut self, threshold: usize) -> Self {
+        self.bloom = BloomFilter::try_new(&self.set, self.native.as_ref(), threshold);
+        self
     }
 
-    /// List to search in
-    pub fn list(&self) -> &[Arc<dyn PhysicalExpr>] {
-        &self.list
+    /// The values of the set, including a NULL if the list had one
+    pub fn get_set(&self) -> &HashSet<ScalarValue, RandomState> {
+        &self.set
     }
 
-    /// Is this negated e.g. NOT IN LIST
-    pub fn negated(&self) -> bool {
-        self.negated
+    // this set with its values cast to the numeric `data_type`, if any differ
+    fn coerced_to(&self, data_type: &DataType) -> Result<Cow<'_, InSet>> {
+        if !self.set.iter().any(|v| needs_coercion(v, data_type)) {
+            return Ok(Cow::Borrowed(self));
+        }
+        if let Some((coerced_type, coerced)) = self.coerced.get() {
+            if coerced_type == data_type {
+                return Ok(Cow::Borrowed(coerced));
+            }
+        }
+        let mut coerced = InSet::new(
+            coerce_numeric_values(self.set.iter().cloned(), data_type)?
+                .into_iter()
+                .collect(),
+        );
+        coerced.contains_null = self.contains_null;
+        // an input of another type again is coerced for every batch
+        match self
+            .coerced
+            .try_insert((data_type.clone(), Box::new(coerced)))
+        {
+            Ok((_, coerced)) => Ok(Cow::Borrowed(coerced)),
+            Err((_, (_, coerced))) => Ok(Cow::Owned(*coerced)),
+        }
     }
 
-    /// Compare for specific utf8 types
-    #[allow(clippy::unnecessary_wraps)]
-    fn compare_utf8<T: OffsetSizeTrait>(
+    // whether the set has the Utf8 or LargeUtf8 value `v`
+    fn contains_str(&self, v: &str) -> bool {
+        // a set with string values always has them in a native set
+        self.native
+            .as_ref()
+            .map_or(false, |native| native.contains_str(v))
+    }
+
+    // probe string `array` with `bloom` first and with the set on a positive
+    fn evaluate_bloom<T: OffsetSizeTrait>(
         &self,
-        array: ArrayRef,
-        list_values: Vec<ColumnarValue>,
+        array: &ArrayRef,
+        bloom: &BloomFilter,
         negated: bool,
-    ) -> Result<ColumnarValue> {
+    ) -> ColumnarValue {
         let array = array
             .as_any()
             .downcast_ref::<GenericStringArray<T>>()
             .unwrap();
+        ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| {
+                    x.map(|v| {
+                        (bloom.may_contain(v.as_bytes()) && self.contains_str(v))
+                            != negated
+                    })
+                })
+                .collect::<BooleanArray>(),
+        ))
+    }
+}
 
-        let contains_null = list_values
-            .iter()
-            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
-        let values = list_values
+/// Number of string values from which an [`InSet`] tests a bloom filter
+/// before the set, see [`InSet::with_bloom_threshold`]
+const BLOOM_FILTER_THRESHOLD: usize = 10_000;
+
+// seeds of the bloom filter hash, which need not be random
+const BLOOM_FILTER_SEED: ahash::RandomState =
+    ahash::RandomState::with_seeds(0x51a3, 0x2e7b, 0x9c41, 0x6d05);
+
+/// Bloom filter of strings with 16 bits per value and 3 hashes derived from
+/// one ahash of the bytes, for a false positive rate of about 0.5%. Most
+/// values not in a large set are rejected by it without the far more costly
+/// hashing of the exact lookup.
+#[derive(Debug, Clone)]
+struct BloomFilter {
+    /// Number of bits minus one, a power of two
+    mask: u64,
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    /// Returns `None` if `set` has fewer than `threshold` Utf8 or LargeUtf8
+    /// values, or they are already packed by a [`ShortStringSet`]
+    fn try_new<S: BuildHasher>(
+        set: &HashSet<ScalarValue, S>,
+        native: Option<&NativeSet>,
+        threshold: usize,
+    ) -> Option<Self> {
+        if matches!(
+            native,
+            Some(NativeSet::ShortUtf8(_) | NativeSet::ShortLargeUtf8(_))
+        ) {
+            return None;
+        }
+        let values = set
             .iter()
-            .flat_map(|expr| match expr {
-                ColumnarValue::Scalar(s) => match s {
-                    ScalarValue::Utf8(Some(v)) => Some(v.as_str()),
-                    ScalarValue::Utf8(None) => None,
-                    ScalarValue::LargeUtf8(Some(v)) => Some(v.as_str()),
-                    ScalarValue::LargeUtf8(None) => None,
-                    datatype => unimplemented!("Unexpected type {} for InList", datatype),
-                },
-                ColumnarValue::Array(_) => {
-                    unimplemented!("InList does not yet support nested columns.")
+            .filter_map(|v| match v {
+                ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => {
+                    Some(v.as_bytes())
                 }
+                _ => None,
             })
-            .collect::<Vec<&str>>();
-
-        if negated {
-            if contains_null {
-                Ok(ColumnarValue::Array(Arc::new(
-                    array
-                        .iter()
-                        .map(|x| match x.map(|v| !values.contains(&v)) {
-                            Some(true) => None,
-                            x => x,
-                        })
-                        .collect::<BooleanArray>(),
-                )))
-            } else {
-                Ok(ColumnarValue::Array(Arc::new(not_in_list_utf8(
-                    array, &values,
-                )?)))
+            .collect::<Vec<_>>();
+        if values.is_empty() || values.len() < threshold {
+            return None;
+        }
+        let num_bits = (values.len() * 16).next_power_of_two().max(64);
+        let mut filter = Self {
+            mask: num_bits as u64 - 1,
+            bits: vec![0; num_bits / 64],
+        };
+        for v in values {
+            for bit in filter.bits_of(v) {
+                filter.bits[(bit / 64) as usize] |= 1 << (bit % 64);
             }
-        } else if contains_null {
-            Ok(ColumnarValue::Array(Arc::new(
-                array
-                    .iter()
-                    .map(|x| match x.map(|v| values.contains(&v)) {
-                        Some(false) => None,
-                        x => x,
-                    })
-                    .collect::<BooleanArray>(),
-            )))
-        } else {
-            Ok(ColumnarValue::Array(Arc::new(in_list_utf8(
-                array, &values,
-            )?)))
         }
+        Some(filter)
+    }
+
+    #[inline]
+    fn bits_of(&self, v: &[u8]) -> [u64; 3] {
+        let mut hasher = BLOOM_FILTER_SEED.build_hasher();
+        hasher.write(v);
+        let hash = hasher.finish();
+        // double hashing, with an odd step to reach every bit
+        let step = (hash >> 32) | 1;
+        [
+            hash & self.mask,
+            hash.wrapping_add(step) & self.mask,
+            hash.wrapping_add(step.wrapping_mul(2)) & self.mask,
+        ]
+    }
+
+    #[inline]
+    fn may_contain(&self, v: &[u8]) -> bool {
+        self.bits_of(v)
+            .iter()
+            .all(|bit| self.bits[(*bit / 64) as usize] & (1 << (bit % 64)) != 0)
     }
 }
 
-impl std::fmt::Display for InListExpr {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        if self.negated {
-            if self.set.is_some() {
-                write!(f, "{} NOT IN (SET) ({:?})", self.expr, self.list)
-            } else {
-                write!(f, "{} NOT IN ({:?})", self.expr, self.list)
+/// Maximum `max - min` of an integer set for it to be stored as a bitset
+const OFFSET_BITSET_MAX_SPAN: u64 = 1 << 16;
+
+/// Hasher of the native hash sets of primitive values: FxHash with the
+/// `fx_hash_set` feature, which is faster for integers but not DoS resistant.
+#[cfg(feature = "fx_hash_set")]
+type NativeHasher = fxhash::FxBuildHasher;
+#[cfg(not(feature = "fx_hash_set"))]
+type NativeHasher = std::collections::hash_map::RandomState;
+
+type NativeHashSet<T> = HashSet<T, NativeHasher>;
+
+/// Specialized set representations chosen from the contents of an [`InSet`]
+#[derive(Debug, Clone)]
+enum NativeSet {
+    /// `Int64` values within a bounded span, see [`OffsetBitSet`]
+    Int64Bitset(OffsetBitSet),
+    /// Other `Int64` values
+    Int64Hash(NativeHashSet<i64>),
+    /// `Int64` codes too spread out for a bitset, see [`RoaringCodeSet`]
+    #[cfg(feature = "roaring_set")]
+    Int64Roaring(RoaringCodeSet),
+    /// `Int32` codes too many for a perfect hash, see [`RoaringCodeSet`]
+    #[cfg(feature = "roaring_set")]
+    Int32Roaring(RoaringCodeSet),
+    /// Up to [`INT32_PERFECT_HASH_MAX_LEN`] `Int32` values, see
+    /// [`Int32PerfectHash`]
+    Int32PerfectHash(Int32PerfectHash),
+    /// `Utf8` values of at most 8 bytes, see [`ShortStringSet`]
+    ShortUtf8(ShortStringSet),
+    /// `LargeUtf8` values of at most 8 bytes, see [`ShortStringSet`]
+    ShortLargeUtf8(ShortStringSet),
+    /// From [`FIRST_BYTE_SET_THRESHOLD`] `Utf8` values, see
+    /// [`FirstByteStringSet`]
+    FirstByteUtf8(FirstByteStringSet),
+    /// From [`FIRST_BYTE_SET_THRESHOLD`] `LargeUtf8` values, see
+    /// [`FirstByteStringSet`]
+    FirstByteLargeUtf8(FirstByteStringSet),
+    /// Over [`FST_SET_THRESHOLD`] `Utf8` values, see [`FstStringSet`]
+    #[cfg(feature = "fst_string_set")]
+    FstUtf8(FstStringSet),
+    /// Over [`FST_SET_THRESHOLD`] `LargeUtf8` values, see [`FstStringSet`]
+    #[cfg(feature = "fst_string_set")]
+    FstLargeUtf8(FstStringSet),
+    /// `Decimal128` values sharing a scale, compared as their unscaled
+    /// integers against columns of that same scale
+    Decimal128 {
+        scale: usize,
+        values: NativeHashSet<i128>,
+    },
+    /// Values of another primitive type, see [`PrimitiveSet`]
+    Primitive(PrimitiveSet),
+    /// Other `Utf8` or `LargeUtf8` values, probed by either type
+    Strings(HashSet<Box<str>, RandomState>),
+    /// `Binary` or `LargeBinary` values, probed by either type
+    Binaries(HashSet<Box<[u8]>, RandomState>),
+}
+
+impl NativeSet {
+    fn try_new<S: BuildHasher>(set: &HashSet<ScalarValue, S>) -> Option<Self> {
+        let mut values = set.iter().filter(|v| !v.is_null()).peekable();
+        match values.peek()? {
+            ScalarValue::Int64(_) => {
+                let values = values
+                    .map(|v| match v {
+                        ScalarValue::Int64(Some(v)) => Some(*v),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<i64>>>()?;
+                if let Some(bitset) = OffsetBitSet::try_new(&values) {
+                    return Some(Self::Int64Bitset(bitset));
+                }
+                #[cfg(feature = "roaring_set")]
+                if let Some(codes) = RoaringCodeSet::try_new(&values) {
+                    return Some(Self::Int64Roaring(codes));
+                }
+                Some(Self::Int64Hash(values.into_iter().collect()))
             }
-        } else if self.set.is_some() {
-            write!(f, "Use {} IN (SET) ({:?})", self.expr, self.list)
-        } else {
-            write!(f, "{} IN ({:?})", self.expr, self.list)
+            ScalarValue::Int32(_) => {
+                let values = values
+                    .map(|v| match v {
+                        ScalarValue::Int32(Some(v)) => Some(*v),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<i32>>>()?;
+                let table = (values.len() <= INT32_PERFECT_HASH_MAX_LEN)
+                    .then(|| Int32PerfectHash::try_new(&values))
+                    .flatten();
+                if let Some(table) = table {
+                    return Some(Self::Int32PerfectHash(table));
+                }
+                #[cfg(feature = "roaring_set")]
+                if let Some(codes) = RoaringCodeSet::try_new(&values) {
+                    return Some(Self::Int32Roaring(codes));
+                }
+                Some(Self::Primitive(PrimitiveSet::Int32(
+                    values.into_iter().collect(),
+                )))
+            }
+            // either offset size is probed by the set of both
+            ScalarValue::Utf8(_) | ScalarValue::LargeUtf8(_) => {
+                let large = matches!(values.peek(), Some(ScalarValue::LargeUtf8(_)));
+                let values = values
+                    .map(|v| match v {
+                        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => {
+                            Some(v.as_str())
+                        }
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                #[cfg(feature = "fst_string_set")]
+                if values.len() > FST_SET_THRESHOLD {
+                    let strings = FstStringSet::new(values);
+                    return Some(if large {
+                        Self::FstLargeUtf8(strings)
+                    } else {
+                        Self::FstUtf8(strings)
+                    });
+                }
+                if let Some(strings) =
+                    ShortStringSet::try_new(values.iter().copied().map(Some))
+                {
+                    return Some(if large {
+                        Self::ShortLargeUtf8(strings)
+                    } else {
+                        Self::ShortUtf8(strings)
+                    });
+                }
+                if values.len() >= FIRST_BYTE_SET_THRESHOLD {
+                    let strings = FirstByteStringSet::new(&values);
+                    return Some(if large {
+                        Self::FirstByteLargeUtf8(strings)
+                    } else {
+                        Self::FirstByteUtf8(strings)
+                    });
+                }
+                Some(Self::Strings(values.into_iter().map(Box::from).collect()))
+            }
+            ScalarValue::Binary(_) | ScalarValue::LargeBinary(_) => values
+                .map(|v| match v {
+                    ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)) => {
+                        Some(Box::from(v.as_slice()))
+                    }
+                    _ => None,
+                })
+                .collect::<Option<_>>()
+                .map(Self::Binaries),
+            ScalarValue::Decimal128(_, _, scale) => {
+                let scale = *scale;
+                let values = values
+                    .map(|v| match v {
+                        ScalarValue::Decimal128(Some(v), _, s) if *s == scale => Some(*v),
+                        _ => None,
+                    })
+                    .collect::<Option<NativeHashSet<i128>>>()?;
+                Some(Self::Decimal128 { scale, values })
+            }
+            _ => PrimitiveSet::try_new(values).map(Self::Primitive),
+        }
+    }
+
+    /// Evaluate string `array` of offset size `T`, or return `None` if this
+    /// is not a set of strings
+    fn evaluate_strings<T: OffsetSizeTrait>(
+        &self,
+        array: &ArrayRef,
+        negated: bool,
+    ) -> Option<ColumnarValue> {
+        Some(match self {
+            Self::ShortUtf8(strings) | Self::ShortLargeUtf8(strings) => {
+                strings.evaluate::<T>(array, negated)
+            }
+            Self::FirstByteUtf8(strings) | Self::FirstByteLargeUtf8(strings) => {
+                strings.evaluate::<T>(array, negated)
+            }
+            #[cfg(feature = "fst_string_set")]
+            Self::FstUtf8(strings) | Self::FstLargeUtf8(strings) => {
+                strings.evaluate::<T>(array, negated)
+            }
+            Self::Strings(strings) => evaluate_strings::<T>(array, strings, negated),
+            _ => return None,
+        })
+    }
+
+    /// Whether this is a set of strings that has `v`
+    fn contains_str(&self, v: &str) -> bool {
+        match self {
+            Self::ShortUtf8(strings) | Self::ShortLargeUtf8(strings) => {
+                strings.contains(v)
+            }
+            Self::FirstByteUtf8(strings) | Self::FirstByteLargeUtf8(strings) => {
+                strings.contains(v)
+            }
+            #[cfg(feature = "fst_string_set")]
+            Self::FstUtf8(strings) | Self::FstLargeUtf8(strings) => {
+                strings.set.contains(v)
+            }
+            Self::Strings(strings) => strings.contains(v),
+            _ => false,
         }
     }
 }
 
-impl PhysicalExpr for InListExpr {
-    /// Return a reference to Any that can be used for downcasting
-    fn as_any(&self) -> &dyn Any {
-        self
+/// Hash sets of the native values of the primitive types with no more
+/// specialized [`NativeSet`], so that probing them builds no [`ScalarValue`]
+/// per row. Floats are keyed by [`total_order_key`], as values equal under the
+/// IN semantics have equal keys.
+#[derive(Debug, Clone)]
+enum PrimitiveSet {
+    Boolean(NativeHashSet<bool>),
+    Int8(NativeHashSet<i8>),
+    Int16(NativeHashSet<i16>),
+    Int32(NativeHashSet<i32>),
+    UInt8(NativeHashSet<u8>),
+    UInt16(NativeHashSet<u16>),
+    UInt32(NativeHashSet<u32>),
+    UInt64(NativeHashSet<u64>),
+    Float32(NativeHashSet<i64>),
+    Float64(NativeHashSet<i64>),
+}
+
+impl PrimitiveSet {
+    /// Returns `None` unless the non-null `values` are of one supported type
+    fn try_new<'a>(
+        mut values: std::iter::Peekable<impl Iterator<Item = &'a ScalarValue>>,
+    ) -> Option<Self> {
+        macro_rules! collect {
+            ($VARIANT:ident, $KEY:expr) => {
+                Some(Self::$VARIANT(
+                    values
+                        .map(|v| match v {
+                            ScalarValue::$VARIANT(Some(v)) => Some($KEY(*v)),
+                            _ => None,
+                        })
+                        .collect::<Option<_>>()?,
+                ))
+            };
+        }
+        match values.peek()? {
+            ScalarValue::Boolean(_) => collect!(Boolean, |v| v),
+            ScalarValue::Int8(_) => collect!(Int8, |v| v),
+            ScalarValue::Int16(_) => collect!(Int16, |v| v),
+            ScalarValue::UInt8(_) => collect!(UInt8, |v| v),
+            ScalarValue::UInt16(_) => collect!(UInt16, |v| v),
+            ScalarValue::UInt32(_) => collect!(UInt32, |v| v),
+            ScalarValue::UInt64(_) => collect!(UInt64, |v| v),
+            ScalarValue::Float32(_) => collect!(Float32, |v| total_order_key(v as f64)),
+            ScalarValue::Float64(_) => collect!(Float64, total_order_key),
+            _ => None,
+        }
     }
 
-    fn data_type(&self, _input_schema: &Schema) -> Result<DataType> {
-        Ok(DataType::Boolean)
+    /// Evaluate `array`, or return `None` if it is not of the type of the set
+    fn evaluate(&self, array: &ArrayRef, negated: bool) -> Option<ColumnarValue> {
+        fn probe<T: ArrowPrimitiveType, K: Hash + Eq>(
+            array: &ArrayRef,
+            values: &NativeHashSet<K>,
+            key: impl Fn(T::Native) -> K,
+            negated: bool,
+        ) -> ColumnarValue {
+            let array = array.as_any().downcast_ref::<PrimitiveArray<T>>().unwrap();
+            ColumnarValue::Array(Arc::new(
+                array
+                    .iter()
+                    .map(|x| x.map(|v| values.contains(&key(v)) != negated))
+                    .collect::<BooleanArray>(),
+            ))
+        }
+        Some(match (self, array.data_type()) {
+            (Self::Boolean(values), DataType::Boolean) => {
+                let array = array.as_any().downcast_ref::<BooleanArray>().unwrap();
+                ColumnarValue::Array(Arc::new(
+                    array
+                        .iter()
+                        .map(|x| x.map(|v| values.contains(&v) != negated))
+                        .collect::<BooleanArray>(),
+                ))
+            }
+            (Self::Int8(v), DataType::Int8) => {
+                probe::<Int8Type, _>(array, v, |v| v, negated)
+            }
+            (Self::Int16(v), DataType::Int16) => {
+                probe::<Int16Type, _>(array, v, |v| v, negated)
+            }
+            (Self::Int32(v), DataType::Int32) => {
+                probe::<Int32Type, _>(array, v, |v| v, negated)
+            }
+            (Self::UInt8(v), DataType::UInt8) => {
+                probe::<UInt8Type, _>(array, v, |v| v, negated)
+            }
+            (Self::UInt16(v), DataType::UInt16) => {
+                probe::<UInt16Type, _>(array, v, |v| v, negated)
+            }
+            (Self::UInt32(v), DataType::UInt32) => {
+                probe::<UInt32Type, _>(array, v, |v| v, negated)
+            }
+            (Self::UInt64(v), DataType::UInt64) => {
+                probe::<UInt64Type, _>(array, v, |v| v, negated)
+            }
+            (Self::Float32(v), DataType::Float32) => {
+                probe::<Float32Type, _>(array, v, |v| total_order_key(v as f64), negated)
+            }
+            (Self::Float64(v), DataType::Float64) => {
+                probe::<Float64Type, _>(array, v, total_order_key, negated)
+            }
+            _ => return None,
+        })
     }
+}
 
-    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
-        self.expr.nullable(input_schema)
+/// Cost of hashing a [`ScalarValue`] built from an input row, relative to a
+/// single comparison
+const SCALAR_HASH_COST: f64 = 4.0;
+
+// estimated cost of probing `in_set` for one row, see `per_row_cost`
+fn in_set_cost(in_set: &InSet) -> f64 {
+    match &in_set.native {
+        Some(NativeSet::Int64Bitset(_)) => 0.5,
+        Some(NativeSet::Int64Hash(_)) => 2.0,
+        #[cfg(feature = "roaring_set")]
+        Some(NativeSet::Int64Roaring(_) | NativeSet::Int32Roaring(_)) => 1.5,
+        Some(NativeSet::Int32PerfectHash(_)) => 1.0,
+        Some(NativeSet::ShortUtf8(_) | NativeSet::ShortLargeUtf8(_)) => 1.5,
+        Some(NativeSet::FirstByteUtf8(_) | NativeSet::FirstByteLargeUtf8(_)) => 2.0,
+        #[cfg(feature = "fst_string_set")]
+        Some(NativeSet::FstUtf8(_) | NativeSet::FstLargeUtf8(_)) => 3.0,
+        Some(NativeSet::Decimal128 { .. }) => 2.0,
+        Some(NativeSet::Primitive(_)) => 2.0,
+        Some(NativeSet::Strings(_) | NativeSet::Binaries(_)) => 2.5,
+        None => SCALAR_HASH_COST,
     }
+}
 
-    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
-        let value = self.expr.evaluate(batch)?;
-        let value_data_type = value.data_type();
+/// Number of `Int32` values up to which a set of them is a perfect hash,
+/// bounding its construction time
+const INT32_PERFECT_HASH_MAX_LEN: usize = 1 << 16;
+
+/// Perfect hash of distinct `Int32` values by hash and displace: a value
+/// hashes to a bucket whose displacement places it in a slot no other value
+/// uses, so a lookup is two table reads and one comparison, without branches.
+/// Empty slots hold a value stored elsewhere, which can never be found there.
+#[derive(Debug, Clone)]
+struct Int32PerfectHash {
+    bucket_multiplier: u32,
+    bucket_shift: u32,
+    slot_multiplier: u32,
+    slot_shift: u32,
+    displacements: Vec<u32>,
+    slots: Vec<i32>,
+}
 
-        if let Some(in_set) = &self.set {
-            let array = match value {
-                ColumnarValue::Array(array) => array,
-                ColumnarValue::Scalar(scalar) => scalar.to_array(),
+impl Int32PerfectHash {
+    /// Returns `None` if `values` is empty or no perfect hash was found
+    fn try_new(values: &[i32]) -> Option<Self> {
+        let first = *values.first()?;
+        // about 4 values per bucket, with slots at most half full, and at
+        // least two buckets so the bucket shift stays below 32
+        let bucket_bits = (values.len() / 4)
+            .max(2)
+            .next_power_of_two()
+            .trailing_zeros();
+        let slot_bits = (values.len() * 2)
+            .next_power_of_two()
+            .trailing_zeros()
+            .max(1);
+        let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..8 {
+            let mut next_multiplier = || {
+                seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                ((seed ^ (seed >> 31)).wrapping_mul(0xBF58_476D_1CE4_E5B9) >> 32) as u32
+                    | 1
             };
-            let set = in_set.get_set();
-            match value_data_type {
-                DataType::Boolean => {
-                    let array = array.as_any().downcast_ref::<BooleanArray>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::Int8 => {
-                    let array = array.as_any().downcast_ref::<Int8Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::Int16 => {
-                    let array = array.as_any().downcast_ref::<Int16Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::Int32 => {
-                    let array = array.as_any().downcast_ref::<Int32Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::Int64 => {
-                    let array = array.as_any().downcast_ref::<Int64Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::UInt8 => {
-                    let array = array.as_any().downcast_ref::<UInt8Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::UInt16 => {
-                    let array = array.as_any().downcast_ref::<UInt16Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::UInt32 => {
-                    let array = array.as_any().downcast_ref::<UInt32Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::UInt64 => {
-                    let array = array.as_any().downcast_ref::<UInt64Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::Float32 => {
-                    let array = array.as_any().downcast_ref::<Float32Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::Float64 => {
-                    let array = array.as_any().downcast_ref::<Float64Array>().unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::Utf8 => {
-                    let array = array
-                        .as_any()
-                        .downcast_ref::<GenericStringArray<i32>>()
-                        .unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                DataType::LargeUtf8 => {
-                    let array = array
-                        .as_any()
-                        .downcast_ref::<GenericStringArray<i64>>()
-                        .unwrap();
-                    set_contains_with_negated!(array, set, self.negated)
-                }
-                datatype => Result::Err(DataFusionError::NotImplemented(format!(
-                    "InSet does not support datatype {:?}.",
-                    datatype
-                ))),
+            let mut table = Self {
+                bucket_multiplier: next_multiplier(),
+                bucket_shift: 32 - bucket_bits,
+                slot_multiplier: next_multiplier(),
+                slot_shift: 32 - slot_bits,
+                displacements: vec![0; 1 << bucket_bits],
+                slots: vec![first; 1 << slot_bits],
+            };
+            if table.place(values) {
+                return Some(table);
             }
-        } else {
-            let list_values = self
-                .list
-                .iter()
-                .map(|expr| expr.evaluate(batch))
-                .collect::<Result<Vec<_>>>()?;
+        }
+        None
+    }
 
-            let array = match value {
-                ColumnarValue::Array(array) => array,
-                ColumnarValue::Scalar(scalar) => scalar.to_array(),
+    // find a displacement for each bucket, largest first, that places its
+    // values in free slots
+    fn place(&mut self, values: &[i32]) -> bool {
+        let mut buckets = vec![vec![]; self.displacements.len()];
+        for v in values {
+            buckets[self.bucket(*v)].push(*v);
+        }
+        let mut order = (0..buckets.len()).collect::<Vec<_>>();
+        order.sort_by_key(|b| std::cmp::Reverse(buckets[*b].len()));
+        let mut used = vec![false; self.slots.len()];
+        let mut placed = Vec::new();
+        for b in order {
+            let found = (0..self.slots.len() as u32).find(|d| {
+                placed.clear();
+                for v in &buckets[b] {
+                    let slot = self.slot(*v, *d);
+                    if used[slot] || placed.contains(&slot) {
+                        return false;
+                    }
+                    placed.push(slot);
+                }
+                true
+            });
+            let d = match found {
+                Some(d) => d,
+                None => return false,
             };
+            self.displacements[b] = d;
+            for (v, slot) in buckets[b].iter().zip(&placed) {
+                used[*slot] = true;
+                self.slots[*slot] = *v;
+            }
+        }
+        true
+    }
 
-            match value_data_type {
-                DataType::Float32 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        Float32,
-                        Float32Array
-                    )
-                }
-                DataType::Float64 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        Float64,
-                        Float64Array
-                    )
-                }
-                DataType::Int16 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        Int16,
-                        Int16Array
-                    )
-                }
-                DataType::Int32 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        Int32,
-                        Int32Array
-                    )
-                }
-                DataType::Int64 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        Int64,
-                        Int64Array
-                    )
-                }
-                DataType::Int8 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        Int8,
-                        Int8Array
-                    )
-                }
-                DataType::UInt16 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        UInt16,
-                        UInt16Array
-                    )
-                }
-                DataType::UInt32 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        UInt32,
-                        UInt32Array
-                    )
-                }
-                DataType::UInt64 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        UInt64,
-                        UInt64Array
-                    )
-                }
-                DataType::UInt8 => {
-                    make_contains_primitive!(
-                        array,
-                        list_values,
-                        self.negated,
-                        UInt8,
-                        UInt8Array
-                    )
-                }
-                DataType::Boolean => {
-                    make_contains!(
-                        array,
-                        list_values,
-                        self.negated,
-                        Boolean,
-                        BooleanArray
-                    )
-                }
-                DataType::Utf8 => {
-                    self.compare_utf8::<i32>(array, list_values, self.negated)
-                }
-                DataType::LargeUtf8 => {
-                    self.compare_utf8::<i64>(array, list_values, self.negated)
-                }
-                DataType::Null => {
-                    let null_array = new_null_array(&DataType::Boolean, array.len());
-                    Ok(ColumnarValue::Array(Arc::new(null_array)))
-                }
-                datatype => Result::Err(DataFusionError::NotImplemented(format!(
-                    "InList does not support datatype {:?}.",
-                    datatype
-                ))),
+    #[inline]
+    fn bucket(&self, v: i32) -> usize {
+        ((v as u32).wrapping_mul(self.bucket_multiplier) >> self.bucket_shift) as usize
+    }
+
+    #[inline]
+    fn slot(&self, v: i32, displacement: u32) -> usize {
+        // xor permutes the slots, keeping the values of a bucket apart when
+        // their hashes are distinct
+        (((v as u32).wrapping_mul(self.slot_multiplier) >> self.slot_shift)
+            ^ displacement) as usize
+    }
+
+    #[inline]
+    fn contains(&self, v: i32) -> bool {
+        let displacement = self.displacements[self.bucket(v)];
+        self.slots[self.slot(v, displacement)] == v
+    }
+}
+
+/// Number of strings, not all short, from which a set of them is bucketed by
+/// their first byte
+const FIRST_BYTE_SET_THRESHOLD: usize = 256;
+
+/// Strings bucketed by their first byte, so each lookup probes a table of
+/// the strings sharing it: for large lists with diverse prefixes these are
+/// small enough to stay in cache. The empty string has no bucket.
+#[derive(Debug, Clone)]
+struct FirstByteStringSet {
+    contains_empty: bool,
+    buckets: Vec<HashSet<Box<str>, RandomState>>,
+}
+
+impl FirstByteStringSet {
+    fn new(values: &[&str]) -> Self {
+        let mut contains_empty = false;
+        let mut buckets = vec![HashSet::default(); 256];
+        for v in values {
+            match v.as_bytes().first() {
+                Some(b) => {
+                    buckets[*b as usize].insert(Box::from(*v));
+                }
+                None => contains_empty = true,
+            }
+        }
+        Self {
+            contains_empty,
+            buckets,
+        }
+    }
+
+    #[inline]
+    fn contains(&self, v: &str) -> bool {
+        match v.as_bytes().first() {
+            Some(b) => self.buckets[*b as usize].contains(v),
+            None => self.contains_empty,
+        }
+    }
+
+    fn evaluate<T: OffsetSizeTrait>(
+        &self,
+        array: &ArrayRef,
+        negated: bool,
+    ) -> ColumnarValue {
+        let array = array
+            .as_any()
+            .downcast_ref::<GenericStringArray<T>>()
+            .unwrap();
+        ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| x.map(|v| self.contains(v) != negated))
+                .collect::<BooleanArray>(),
+        ))
+    }
+}
+
+/// Number of strings above which a set of them is stored as an FST
+#[cfg(feature = "fst_string_set")]
+const FST_SET_THRESHOLD: usize = 100_000;
+
+/// Strings stored as a finite state transducer, which shares their prefixes
+/// and suffixes: far more compact than a hash set for huge lists of codes,
+/// with membership in time linear in the length of the probed string
+#[cfg(feature = "fst_string_set")]
+#[derive(Debug, Clone)]
+struct FstStringSet {
+    set: fst::Set<Vec<u8>>,
+}
+
+#[cfg(feature = "fst_string_set")]
+impl FstStringSet {
+    fn new(mut values: Vec<&str>) -> Self {
+        values.sort_unstable();
+        values.dedup();
+        // sorted distinct keys cannot fail to build
+        let set = fst::Set::from_iter(values).unwrap();
+        Self { set }
+    }
+
+    fn evaluate<T: OffsetSizeTrait>(
+        &self,
+        array: &ArrayRef,
+        negated: bool,
+    ) -> ColumnarValue {
+        let array = array
+            .as_any()
+            .downcast_ref::<GenericStringArray<T>>()
+            .unwrap();
+        ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| x.map(|v| self.set.contains(v) != negated))
+                .collect::<BooleanArray>(),
+        ))
+    }
+}
+
+/// Strings of at most 8 bytes packed into a `u64` each, so membership is an
+/// integer hash lookup rather than a string comparison. Values are kept per
+/// length as padding makes e.g. `"a"` and `"a\0"` pack the same.
+#[derive(Debug, Clone)]
+struct ShortStringSet {
+    by_len: [HashSet<u64>; 9],
+}
+
+impl ShortStringSet {
+    /// Returns `None` if any value is `None` or longer than 8 bytes
+    fn try_new<'a>(values: impl Iterator<Item = Option<&'a str>>) -> Option<Self> {
+        let mut by_len: [HashSet<u64>; 9] = Default::default();
+        for v in values {
+            let v = v?.as_bytes();
+            by_len.get_mut(v.len())?.insert(Self::pack(v));
+        }
+        Some(Self { by_len })
+    }
+
+    #[inline]
+    fn pack(v: &[u8]) -> u64 {
+        let mut buf = [0; 8];
+        buf[..v.len()].copy_from_slice(v);
+        u64::from_le_bytes(buf)
+    }
+
+    #[inline]
+    fn contains(&self, v: &str) -> bool {
+        let v = v.as_bytes();
+        match self.by_len.get(v.len()) {
+            Some(set) => set.contains(&Self::pack(v)),
+            None => false,
+        }
+    }
+
+    fn evaluate<T: OffsetSizeTrait>(
+        &self,
+        array: &ArrayRef,
+        negated: bool,
+    ) -> ColumnarValue {
+        let array = array
+            .as_any()
+            .downcast_ref::<GenericStringArray<T>>()
+            .unwrap();
+        ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| x.map(|v| self.contains(v) != negated))
+                .collect::<BooleanArray>(),
+        ))
+    }
+}
+
+/// Bitset of integers offset by their minimum, giving O(1) membership for
+/// values clustered in a small window however large the values themselves
+#[derive(Debug, Clone)]
+struct OffsetBitSet {
+    min: i64,
+    num_bits: u64,
+    bits: Vec<u64>,
+}
+
+impl OffsetBitSet {
+    /// Returns `None` if `values` is empty or spans more than
+    /// [`OFFSET_BITSET_MAX_SPAN`]
+    fn try_new(values: &[i64]) -> Option<Self> {
+        let min = *values.iter().min()?;
+        let max = *values.iter().max()?;
+        let span = max.wrapping_sub(min) as u64;
+        if span >= OFFSET_BITSET_MAX_SPAN {
+            return None;
+        }
+        let num_bits = span + 1;
+        let mut bits = vec![0u64; ((num_bits + 63) / 64) as usize];
+        for v in values {
+            let offset = v.wrapping_sub(min) as u64;
+            bits[(offset / 64) as usize] |= 1 << (offset % 64);
+        }
+        Some(Self {
+            min,
+            num_bits,
+            bits,
+        })
+    }
+
+    #[inline]
+    fn contains(&self, v: i64) -> bool {
+        // values below `min` wrap around to offsets past `num_bits`
+        let offset = v.wrapping_sub(self.min) as u64;
+        offset < self.num_bits
+            && self.bits[(offset / 64) as usize] & (1 << (offset % 64)) != 0
+    }
+}
+
+/// Integer codes from 0 to `u32::MAX` stored as a roaring bitmap, which keeps
+/// runs of codes as bitsets and sparse codes as sorted arrays: compact and fast
+/// for large domains however the codes are spread or clustered.
+#[cfg(feature = "roaring_set")]
+#[derive(Debug, Clone)]
+struct RoaringCodeSet {
+    bitmap: roaring::RoaringBitmap,
+}
+
+#[cfg(feature = "roaring_set")]
+impl RoaringCodeSet {
+    /// Returns `None` if any of `values` is not a code
+    fn try_new<T: Copy + TryInto<u32>>(values: &[T]) -> Option<Self> {
+        let bitmap = values
+            .iter()
+            .map(|v| (*v).try_into().ok())
+            .collect::<Option<roaring::RoaringBitmap>>()?;
+        Some(Self { bitmap })
+    }
+
+    fn evaluate<T: ArrowPrimitiveType>(
+        &self,
+        array: &ArrayRef,
+        negated: bool,
+    ) -> ColumnarValue
+    where
+        T::Native: TryInto<u32>,
+    {
+        let array = array.as_any().downcast_ref::<PrimitiveArray<T>>().unwrap();
+        // values that are not codes are never in the set
+        let contains =
+            |v: T::Native| v.try_into().map_or(false, |v| self.bitmap.contains(v));
+        ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| x.map(|v| contains(v) != negated))
+                .collect::<BooleanArray>(),
+        ))
+    }
+}
+
+/// Description of an `IN` predicate over a column that a Parquet reader can
+/// use to prune row groups and pages with bloom filters and min/max statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParquetInFilter {
+    column: expressions::Column,
+    values: Vec<ScalarValue>,
+}
+
+impl ParquetInFilter {
+    /// The column compared against
+    pub fn column(&self) -> &expressions::Column {
+        &self.column
+    }
+
+    /// The distinct non-null values of the list in ascending order
+    pub fn values(&self) -> &[ScalarValue] {
+        &self.values
+    }
+}
+
+/// Sorted distinct `Int64` values that an input known to be sorted is merged
+/// with rather than hashed, see [`InListExpr::new_sorted_merge`]
+#[derive(Debug, Clone)]
+pub struct SortedMergeInSet {
+    values: Vec<i64>,
+}
+
+impl SortedMergeInSet {
+    /// Returns an error unless `values` is strictly ascending
+    pub fn try_new(values: Vec<i64>) -> Result<Self> {
+        if values.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(DataFusionError::Plan(
+                "SortedMergeInSet values must be sorted and distinct".to_string(),
+            ));
+        }
+        Ok(Self { values })
+    }
+
+    /// The values in ascending order
+    pub fn values(&self) -> &[i64] {
+        &self.values
+    }
+
+    /// Merge `array` with the values in O(n + m), or `None` if `array` turns
+    /// out not to be sorted
+    fn merge(&self, array: &Int64Array, negated: bool) -> Option<BooleanArray> {
+        let mut next = 0;
+        let mut prev = i64::MIN;
+        let result = array
+            .iter()
+            .map(|x| match x {
+                Some(v) if v < prev => None,
+                Some(v) => {
+                    prev = v;
+                    while next < self.values.len() && self.values[next] < v {
+                        next += 1;
+                    }
+                    let found = next < self.values.len() && self.values[next] == v;
+                    Some(Some(found != negated))
+                }
+                None => Some(None),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(BooleanArray::from(result))
+    }
+
+    /// Probe each value of `array` by binary search, for any order
+    fn search(&self, array: &Int64Array, negated: bool) -> BooleanArray {
+        array
+            .iter()
+            .map(|x| x.map(|v| self.values.binary_search(&v).is_ok() != negated))
+            .collect()
+    }
+}
+
+/// Builds an [`InSet`] incrementally from arrays, e.g. the distinct keys of
+/// the first batches of a stream, without first collecting them all into a
+/// single list. NULLs are skipped as they never match.
+#[derive(Debug, Default)]
+pub struct InSetBuilder {
+    set: HashSet<ScalarValue, RandomState>,
+}
+
+impl InSetBuilder {
+    /// Create an empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the non-null values of `array` to the set
+    pub fn append(&mut self, array: &ArrayRef) -> Result<()> {
+        for i in 0..array.len() {
+            if array.is_valid(i) {
+                // a valid dictionary key can still reference a NULL value
+                let value = ScalarValue::try_from_array(array, i)?;
+                if !value.is_null() {
+                    self.set.insert(value);
+                }
             }
         }
+        Ok(())
+    }
+
+    /// Number of distinct values added so far
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Whether no value has been added yet
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Finish building the set
+    pub fn finish(self) -> InSet {
+        InSet::new(self.set)
     }
 }
 
-/// Creates a unary expression InList
-pub fn in_list(
-    expr: Arc<dyn PhysicalExpr>,
-    list: Vec<Arc<dyn PhysicalExpr>>,
-    negated: &bool,
-) -> Result<Arc<dyn PhysicalExpr>> {
-    Ok(Arc::new(InListExpr::new(expr, list, *negated)))
+/// A membership set that can be replaced between calls to `evaluate`, e.g. by
+/// the build side of a join pushing an updated runtime filter.
+///
+/// Clones share the same underlying set, so the producer keeps a clone and
+/// calls [`DynamicInSet::update`] while the [`InListExpr`] reads the latest
+/// contents at the start of every batch. Each update builds an [`InSet`], so
+/// batches probe its native representation.
+#[derive(Debug, Clone)]
+pub struct DynamicInSet {
+    set: Arc<RwLock<InSet>>,
+    /// Number of updates so far, changed while holding the write lock
+    generation: Arc<AtomicU64>,
 }
 
-#[cfg(test)]
-mod tests {
-    use arrow::{array::StringArray, datatypes::Field};
+impl DynamicInSet {
+    /// Create a new dynamic set with the given initial contents
+    pub fn new(set: HashSet<ScalarValue>) -> Self {
+        Self {
+            set: Arc::new(RwLock::new(InSet::new(set.into_iter().collect()))),
+            generation: Arc::default(),
+        }
+    }
 
-    use super::*;
-    use crate::expressions::{col, lit};
-    use datafusion_common::Result;
+    /// Replace the contents of the set, visible to subsequent evaluations
+    pub fn update(&self, set: HashSet<ScalarValue>) {
+        let mut current = self.set.write();
+        *current = InSet::new(set.into_iter().collect());
+        self.generation.fetch_add(1, AtomicOrdering::Release);
+    }
+
+    /// Lock the current contents of the set for reading
+    pub fn read(&self) -> MappedRwLockReadGuard<'_, HashSet<ScalarValue, RandomState>> {
+        RwLockReadGuard::map(self.set.read(), InSet::get_set)
+    }
+}
+
+impl Default for DynamicInSet {
+    fn default() -> Self {
+        Self::new(HashSet::new())
+    }
+}
+
+macro_rules! make_contains {
+    ($ARRAY:expr, $LIST_VALUES:expr, $NEGATED:expr, $SCALAR_VALUE:ident, $ARRAY_TYPE:ident) => {{
+        let array = $ARRAY.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+
+        let contains_null = $LIST_VALUES
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = $LIST_VALUES
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) => match s {
+                    ScalarValue::$SCALAR_VALUE(Some(v)) => Ok(Some(*v)),
+                    ScalarValue::$SCALAR_VALUE(None) => Ok(None),
+                    ScalarValue::Utf8(None) => Ok(None),
+                    datatype => Err(DataFusionError::Internal(format!(
+                        "Unexpected type {} for InList",
+                        datatype
+                    ))),
+                },
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        // decide on the NULL handling once rather than for every row
+        let result = if contains_null {
+            array
+                .iter()
+                .map(|x| match x.map(|x| values.contains(&x)) {
+                    Some(true) => Some(!$NEGATED),
+                    _ => None,
+                })
+                .collect::<BooleanArray>()
+        } else {
+            array
+                .iter()
+                .map(|x| x.map(|x| values.contains(&x) != $NEGATED))
+                .collect::<BooleanArray>()
+        };
+        Ok(ColumnarValue::Array(Arc::new(result)))
+    }};
+}
+
+// the non-null values of `$LIST_VALUES`, all `ScalarValue::$SCALAR_VALUE`
+macro_rules! primitive_list_values {
+    ($LIST_VALUES:expr, $SCALAR_VALUE:ident) => {{
+        $LIST_VALUES
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) => match s {
+                    ScalarValue::$SCALAR_VALUE(Some(v)) => Ok(Some(*v)),
+                    ScalarValue::$SCALAR_VALUE(None) => Ok(None),
+                    ScalarValue::Utf8(None) => Ok(None),
+                    datatype => Err(DataFusionError::Internal(format!(
+                        "Unexpected type {} for InList",
+                        datatype
+                    ))),
+                },
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+    }};
+}
+
+macro_rules! make_contains_primitive {
+    ($ARRAY:expr, $LIST_VALUES:expr, $NEGATED:expr, $SCALAR_VALUE:ident, $ARRAY_TYPE:ident) => {{
+        let array = $ARRAY.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+
+        let contains_null = $LIST_VALUES
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = primitive_list_values!($LIST_VALUES, $SCALAR_VALUE);
+        contains_primitive_values(array, &values, contains_null, $NEGATED)
+    }};
+}
+
+// like `make_contains_primitive`, binary searching lists of at least
+// `SORTED_LIST_MIN_LEN` values of an ordered type rather than scanning them
+macro_rules! make_contains_ordered {
+    ($ARRAY:expr, $LIST_VALUES:expr, $NEGATED:expr, $SCALAR_VALUE:ident, $ARRAY_TYPE:ident) => {{
+        let array = $ARRAY.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+
+        let contains_null = $LIST_VALUES
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let mut values = primitive_list_values!($LIST_VALUES, $SCALAR_VALUE);
+        if values.len() < SORTED_LIST_MIN_LEN {
+            contains_primitive_values(array, &values, contains_null, $NEGATED)
+        } else {
+            values.sort_unstable();
+            contains_sorted_values(array, &values, contains_null, $NEGATED)
+        }
+    }};
+}
+
+// probe an interval `array` with a set, wrapping each value in the
+// `ScalarValue` variant of its unit
+macro_rules! set_contains_interval {
+    ($ARRAY:expr, $SET:expr, $NEGATED:expr, $SCALAR_VALUE:ident, $ARRAY_TYPE:ident) => {{
+        let array = $ARRAY.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+        Ok(ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| {
+                    x.map(|v| {
+                        $SET.contains(&ScalarValue::$SCALAR_VALUE(Some(v))) != $NEGATED
+                    })
+                })
+                .collect::<BooleanArray>(),
+        )))
+    }};
+}
+
+// probe string `array` with a set of strings. Like every string set, this
+// takes the `&str` values as the array hands them out, which skips UTF-8
+// validation: arrays are valid UTF-8 by construction, and sets only compare
+// the bytes of those values, so no path re-validates them.
+fn evaluate_strings<T: OffsetSizeTrait>(
+    array: &ArrayRef,
+    strings: &HashSet<Box<str>, RandomState>,
+    negated: bool,
+) -> ColumnarValue {
+    let array = array
+        .as_any()
+        .downcast_ref::<GenericStringArray<T>>()
+        .unwrap();
+    ColumnarValue::Array(Arc::new(
+        array
+            .iter()
+            .map(|x| x.map(|v| strings.contains(v) != negated))
+            .collect::<BooleanArray>(),
+    ))
+}
+
+// probe binary `array` of offset size `T` with `values`
+fn evaluate_binaries<T: OffsetSizeTrait>(
+    array: &ArrayRef,
+    values: &HashSet<Box<[u8]>, RandomState>,
+    negated: bool,
+) -> ColumnarValue {
+    let array = array
+        .as_any()
+        .downcast_ref::<GenericBinaryArray<T>>()
+        .unwrap();
+    ColumnarValue::Array(Arc::new(
+        array
+            .iter()
+            .map(|x| x.map(|v| values.contains(v) != negated))
+            .collect::<BooleanArray>(),
+    ))
+}
+
+/// Number of `Float64` or integer literals from which the list is sorted and
+/// binary searched rather than scanned
+const SORTED_LIST_MIN_LEN: usize = 8;
+
+// key ordering floats like `f64::total_cmp`, which is newer than the MSRV,
+// with all NaNs mapped to one NaN and -0.0 to 0.0 first so that values equal
+// under the IN semantics have equal keys
+fn total_order_key(v: f64) -> i64 {
+    let v = if v.is_nan() {
+        f64::NAN
+    } else if v == 0.0 {
+        0.0
+    } else {
+        v
+    };
+    let bits = v.to_bits() as i64;
+    bits ^ (((bits >> 63) as u64) >> 1) as i64
+}
+
+// whether each value on the left (can be null) is contained in the non-null list
+fn in_list_primitive<T: ArrowPrimitiveType>(
+    array: &PrimitiveArray<T>,
+    values: &[<T as ArrowPrimitiveType>::Native],
+) -> Result<BooleanArray> {
+    compare_op_scalar!(
+        array,
+        values,
+        |x, v: &[<T as ArrowPrimitiveType>::Native]| v.contains(&x)
+    )
+}
+
+// whether each value on the left (can be null) is contained in the non-null list
+fn not_in_list_primitive<T: ArrowPrimitiveType>(
+    array: &PrimitiveArray<T>,
+    values: &[<T as ArrowPrimitiveType>::Native],
+) -> Result<BooleanArray> {
+    compare_op_scalar!(
+        array,
+        values,
+        |x, v: &[<T as ArrowPrimitiveType>::Native]| !v.contains(&x)
+    )
+}
+
+// apply the three-valued IN / NOT IN semantics for a primitive array against
+// the non-null list values, `contains_null` being whether the list had a NULL
+fn contains_primitive_values<T: ArrowPrimitiveType>(
+    array: &PrimitiveArray<T>,
+    values: &[<T as ArrowPrimitiveType>::Native],
+    contains_null: bool,
+    negated: bool,
+) -> Result<ColumnarValue> {
+    let result = match (negated, contains_null) {
+        (true, true) => array
+            .iter()
+            .map(|x| match x.map(|v| !values.contains(&v)) {
+                Some(true) => None,
+                x => x,
+            })
+            .collect::<BooleanArray>(),
+        (true, false) => not_in_list_primitive(array, values)?,
+        (false, true) => array
+            .iter()
+            .map(|x| match x.map(|v| values.contains(&v)) {
+                Some(false) => None,
+                x => x,
+            })
+            .collect::<BooleanArray>(),
+        (false, false) => in_list_primitive(array, values)?,
+    };
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+// like `contains_primitive_values`, binary searching the ascending `values`
+fn contains_sorted_values<T: ArrowPrimitiveType>(
+    array: &PrimitiveArray<T>,
+    values: &[<T as ArrowPrimitiveType>::Native],
+    contains_null: bool,
+    negated: bool,
+) -> Result<ColumnarValue>
+where
+    T::Native: Ord,
+{
+    let contains = |x: T::Native, v: &[T::Native]| v.binary_search(&x).is_ok();
+    let result = match (negated, contains_null) {
+        (true, true) => array
+            .iter()
+            .map(|x| match x.map(|v| !contains(v, values)) {
+                Some(true) => None,
+                x => x,
+            })
+            .collect::<BooleanArray>(),
+        (true, false) => {
+            let result: Result<BooleanArray> =
+                compare_op_scalar!(array, values, |x, v| !contains(x, v));
+            result?
+        }
+        (false, true) => array
+            .iter()
+            .map(|x| match x.map(|v| contains(v, values)) {
+                Some(false) => None,
+                x => x,
+            })
+            .collect::<BooleanArray>(),
+        (false, false) => {
+            let result: Result<BooleanArray> =
+                compare_op_scalar!(array, values, contains);
+            result?
+        }
+    };
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+const SECONDS_IN_DAY: i64 = 86_400;
+
+fn units_per_second(unit: &TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Second => 1,
+        TimeUnit::Millisecond => 1_000,
+        TimeUnit::Microsecond => 1_000_000,
+        TimeUnit::Nanosecond => 1_000_000_000,
+    }
+}
+
+/// Convert a non-null Timestamp, Date32 or Date64 literal to an instant in
+/// `unit`, returning `None` if it can't be represented exactly in that unit
+/// (and so can't match any value of the column).
+///
+/// Dates are taken as midnight UTC, the same instant `CAST(date AS
+/// TIMESTAMP)` produces. The timezone of a timestamp column is not applied
+/// to dates: as with other timestamp comparisons, values are compared by
+/// their stored (UTC based) instant.
+fn timestamp_literal_value(value: &ScalarValue, unit: &TimeUnit) -> Result<Option<i64>> {
+    let (v, from_unit) = match value {
+        ScalarValue::TimestampSecond(Some(v), _) => (*v, TimeUnit::Second),
+        ScalarValue::TimestampMillisecond(Some(v), _) => (*v, TimeUnit::Millisecond),
+        ScalarValue::TimestampMicrosecond(Some(v), _) => (*v, TimeUnit::Microsecond),
+        ScalarValue::TimestampNanosecond(Some(v), _) => (*v, TimeUnit::Nanosecond),
+        ScalarValue::Date32(Some(v)) => (*v as i64 * SECONDS_IN_DAY, TimeUnit::Second),
+        ScalarValue::Date64(Some(v)) => (*v, TimeUnit::Millisecond),
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Unexpected type {:?} for Timestamp InList",
+                other.get_datatype()
+            )))
+        }
+    };
+    let (from, to) = (units_per_second(&from_unit), units_per_second(unit));
+    Ok(if to >= from {
+        v.checked_mul(to / from)
+    } else if v % (from / to) == 0 {
+        Some(v / (from / to))
+    } else {
+        None
+    })
+}
+
+/// Convert a non-null literal to a time of day in `unit`, returning `None` if
+/// it can't be represented exactly in that unit. As there is no time
+/// [`ScalarValue`], times are given as `'HH:MM:SS[.fraction]'` strings, or as
+/// integers already in `unit` as stored by the column.
+fn time_literal_value(value: &ScalarValue, unit: &TimeUnit) -> Result<Option<i64>> {
+    let (v, from_unit) = match value {
+        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => {
+            let time =
+                chrono::NaiveTime::parse_from_str(v, "%H:%M:%S%.f").map_err(|e| {
+                    DataFusionError::Plan(format!(
+                        "Cannot parse {:?} as a time of day in InList: {}",
+                        v, e
+                    ))
+                })?;
+            let nanos = time.num_seconds_from_midnight() as i64 * 1_000_000_000
+                + time.nanosecond() as i64;
+            (nanos, TimeUnit::Nanosecond)
+        }
+        ScalarValue::Int32(Some(v)) => (*v as i64, unit.clone()),
+        ScalarValue::Int64(Some(v)) => (*v, unit.clone()),
+        other => {
+            return Err(DataFusionError::Internal(format!(
+                "Unexpected type {:?} for Time InList",
+                other.get_datatype()
+            )))
+        }
+    };
+    let (from, to) = (units_per_second(&from_unit), units_per_second(unit));
+    Ok(if to >= from {
+        v.checked_mul(to / from)
+    } else if v % (from / to) == 0 {
+        Some(v / (from / to))
+    } else {
+        None
+    })
+}
+
+// whether each value on the left (can be null) is contained in the non-null list
+fn in_list_utf8<OffsetSize: OffsetSizeTrait>(
+    array: &GenericStringArray<OffsetSize>,
+    values: &[&str],
+) -> Result<BooleanArray> {
+    compare_op_scalar!(array, values, |x, v: &[&str]| v.contains(&x))
+}
+
+fn not_in_list_utf8<OffsetSize: OffsetSizeTrait>(
+    array: &GenericStringArray<OffsetSize>,
+    values: &[&str],
+) -> Result<BooleanArray> {
+    compare_op_scalar!(array, values, |x, v: &[&str]| !v.contains(&x))
+}
+
+//check all filter values of In clause are static.
+//include any expression that is constant, e.g. `Literal`, `CastExpr + Literal`
+//or a `CaseExpr` over literals
+fn check_all_static_filter_expr(list: &[Arc<dyn PhysicalExpr>]) -> bool {
+    list.iter().all(|v| v.is_constant())
+}
+
+// evaluate the list to its scalar values if all filter values are static,
+// applying any casts so the values match the type compared against
+fn static_filter_values(list: &[Arc<dyn PhysicalExpr>]) -> Option<Vec<ScalarValue>> {
+    if !check_all_static_filter_expr(list) {
+        return None;
+    }
+    evaluate_static_filter_values(list).ok()
+}
+
+// evaluate filter values known to be static to their scalar values
+fn evaluate_static_filter_values(
+    list: &[Arc<dyn PhysicalExpr>],
+) -> Result<Vec<ScalarValue>> {
+    // constant expressions such as `CaseExpr` may still produce an array, so
+    // evaluate against a single row. Filtering a batch without columns fails,
+    // so it has a placeholder column.
+    let schema = Schema::new(vec![Field::new("", DataType::Boolean, true)]);
+    let column: ArrayRef = Arc::new(BooleanArray::from(vec![None]));
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![column])?;
+    list.iter()
+        .map(|expr| match expr.evaluate(&batch)? {
+            ColumnarValue::Scalar(s) => Ok(s),
+            ColumnarValue::Array(a) => ScalarValue::try_from_array(&a, 0),
+        })
+        .collect()
+}
+
+// whether set values of `value_type` compare with values of `data_type`,
+// exactly or as converted by the probe
+fn set_value_type_matches(value_type: &DataType, data_type: &DataType) -> bool {
+    match (data_type, value_type) {
+        (DataType::Dictionary(_, data_type), _) => {
+            set_value_type_matches(value_type, data_type)
+        }
+        (DataType::Float32, DataType::Float64)
+        | (DataType::Decimal(_, _), DataType::Decimal(_, _))
+        | (
+            DataType::Timestamp(_, _),
+            DataType::Timestamp(_, _) | DataType::Date32 | DataType::Date64,
+        )
+        | (DataType::Utf8 | DataType::LargeUtf8, DataType::Utf8 | DataType::LargeUtf8)
+        | (
+            DataType::Binary | DataType::LargeBinary,
+            DataType::Binary | DataType::LargeBinary,
+        ) => true,
+        (data_type, value_type) => data_type == value_type,
+    }
+}
+
+// whether `value` must be cast to be compared with a numeric `data_type`
+// column. `Float64` values are compared with `Float32` columns in `f64`.
+fn needs_coercion(value: &ScalarValue, data_type: &DataType) -> bool {
+    let numeric = matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Float32
+            | DataType::Float64
+    );
+    let value_type = value.get_datatype();
+    numeric
+        && &value_type != data_type
+        && !(data_type == &DataType::Float32 && value_type == DataType::Float64)
+}
+
+// cast `values` to the numeric `data_type`, dropping those that cannot
+// represent any of its values and so never match, and typing NULLs
+fn coerce_numeric_values(
+    values: impl IntoIterator<Item = ScalarValue>,
+    data_type: &DataType,
+) -> Result<Vec<ScalarValue>> {
+    let mut coerced = vec![];
+    for value in values {
+        if !needs_coercion(&value, data_type) {
+            coerced.push(value);
+        } else if value.is_null() {
+            coerced.push(ScalarValue::try_from(data_type)?);
+        } else if let Ok(value) = coerce_literal(&value, data_type)? {
+            coerced.push(value);
+        }
+    }
+    Ok(coerced)
+}
+
+// whether an [`InSet`] can be probed with values of `data_type`
+fn set_supports(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Float32
+        | DataType::Float64
+        | DataType::Timestamp(_, _)
+        | DataType::Decimal(_, _)
+        | DataType::Utf8
+        | DataType::LargeUtf8
+        | DataType::Interval(_)
+        | DataType::Binary
+        | DataType::LargeBinary => true,
+        DataType::Dictionary(_, value_type) => set_supports(value_type),
+        _ => false,
+    }
+}
+
+// the unscaled integer of decimal `value` at scale `from` rescaled to `to`,
+// `None` if too large for any decimal, and an error if it would lose digits
+fn rescale_decimal(value: i128, from: usize, to: usize) -> Result<Option<i128>> {
+    let factor = |digits: usize| 10i128.checked_pow(digits as u32);
+    if to >= from {
+        return Ok(factor(to - from).and_then(|f| value.checked_mul(f)));
+    }
+    match factor(from - to) {
+        Some(f) if value % f == 0 => Ok(Some(value / f)),
+        // a factor beyond i128 exceeds every non-zero value
+        None if value == 0 => Ok(Some(0)),
+        _ => Err(DataFusionError::Plan(format!(
+            "Decimal {} of scale {} cannot be compared at scale {} without loss",
+            value, from, to
+        ))),
+    }
+}
+
+// truncate the timestamps of `array` to `granularity`, keeping their type
+fn truncate_timestamps(array: ArrayRef, granularity: &str) -> Result<ArrayRef> {
+    let unit = match array.data_type() {
+        DataType::Timestamp(unit, _) => unit,
+        datatype => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "InList date_trunc does not support datatype {:?}.",
+                datatype
+            )))
+        }
+    };
+    // `date_trunc_single` works in nanoseconds
+    let scale = units_per_second(&TimeUnit::Nanosecond) / units_per_second(unit);
+    let values = kernels::cast::cast(&array, &DataType::Int64)?;
+    let values = values.as_any().downcast_ref::<Int64Array>().unwrap();
+    let truncated = values
+        .iter()
+        .map(|v| {
+            v.map(|v| {
+                let nanos = v.checked_mul(scale).ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "Timestamp {} is out of range for date_trunc",
+                        v
+                    ))
+                })?;
+                // a truncated instant is a whole number of seconds
+                Ok(date_trunc_single(granularity, nanos)? / scale)
+            })
+            .transpose()
+        })
+        .collect::<Result<Int64Array>>()?;
+    Ok(kernels::cast::cast(
+        &(Arc::new(truncated) as ArrayRef),
+        array.data_type(),
+    )?)
+}
+
+// evaluate a dictionary `array` by passing its values to `probe` once, however
+// many keys reference each of them, and gathering the results by key. The
+// result is not itself dictionary encoded, even for few distinct values: the
+// bit per row of a boolean array is smaller than any dictionary key.
+//
+// Rows with a NULL key are NULL without reading their key, which can hold any
+// value, unlike with `take`. The keys respect the offset of a sliced `array`.
+fn evaluate_dictionary(
+    array: &ArrayRef,
+    probe: impl FnOnce(ArrayRef) -> Result<ColumnarValue>,
+) -> Result<ColumnarValue> {
+    macro_rules! gather {
+        ($KEY:ty) => {{
+            let array = array
+                .as_any()
+                .downcast_ref::<DictionaryArray<$KEY>>()
+                .unwrap();
+            let values = array.values().clone();
+            let found = probe(values.clone())?.into_array(values.len());
+            let found = found.as_any().downcast_ref::<BooleanArray>().unwrap();
+            let result = array
+                .keys()
+                .iter()
+                .map(|key| {
+                    let key = match key {
+                        Some(key) => key,
+                        None => return Ok(None),
+                    };
+                    match key.to_usize().filter(|key| *key < found.len()) {
+                        Some(key) => Ok(found.is_valid(key).then(|| found.value(key))),
+                        None => Err(DataFusionError::Internal(format!(
+                            "Dictionary key {:?} out of bounds for {} values",
+                            key,
+                            found.len()
+                        ))),
+                    }
+                })
+                .collect::<Result<BooleanArray>>()?;
+            Ok(ColumnarValue::Array(Arc::new(result)))
+        }};
+    }
+    match array.data_type() {
+        DataType::Dictionary(key_type, _) => match key_type.as_ref() {
+            DataType::Int8 => gather!(Int8Type),
+            DataType::Int16 => gather!(Int16Type),
+            DataType::Int32 => gather!(Int32Type),
+            DataType::Int64 => gather!(Int64Type),
+            DataType::UInt8 => gather!(UInt8Type),
+            DataType::UInt16 => gather!(UInt16Type),
+            DataType::UInt32 => gather!(UInt32Type),
+            DataType::UInt64 => gather!(UInt64Type),
+            key_type => Err(DataFusionError::Internal(format!(
+                "Invalid dictionary key type {:?}",
+                key_type
+            ))),
+        },
+        datatype => Err(DataFusionError::Internal(format!(
+            "Expected a dictionary array, got {:?}",
+            datatype
+        ))),
+    }
+}
+
+// cast `value` to `target`, returning the reason instead if it cannot
+// represent any value of `target`
+fn coerce_literal(
+    value: &ScalarValue,
+    target: &DataType,
+) -> Result<std::result::Result<ScalarValue, String>> {
+    let source = value.get_datatype();
+    if !kernels::cast::can_cast_types(&source, target) {
+        return Err(DataFusionError::Plan(format!(
+            "Cannot coerce IN list value {} of type {:?} to {:?}",
+            value, source, target
+        )));
+    }
+    let array = value.to_array();
+    // a safe cast yields NULL for values out of range or unparseable
+    let cast = kernels::cast::cast(&array, target)?;
+    if !value.is_null() && cast.is_null(0) {
+        return Ok(Err(format!(
+            "{} cannot be represented as {:?}",
+            value, target
+        )));
+    }
+    // strings are parsed, other values must cast without loss, e.g. 1.5 is
+    // not an integer
+    if !matches!(source, DataType::Utf8 | DataType::LargeUtf8) {
+        let round_trip = kernels::cast::cast(&cast, &source)?;
+        if ScalarValue::try_from_array(&round_trip, 0)? != *value {
+            return Ok(Err(format!(
+                "{} is not exactly representable as {:?}",
+                value, target
+            )));
+        }
+    }
+    Ok(Ok(ScalarValue::try_from_array(&cast, 0)?))
+}
+
+// rough wire size of an expression: a tag and length prefix plus its payload
+fn serialized_expr_size_estimate(expr: &Arc<dyn PhysicalExpr>) -> usize {
+    const PREFIX: usize = 2;
+    if let Some(literal) = expr.as_any().downcast_ref::<expressions::Literal>() {
+        PREFIX + serialized_scalar_size_estimate(literal.value())
+    } else if let Some(cast) = expr.as_any().downcast_ref::<expressions::CastExpr>() {
+        // the target type is encoded as a small enum
+        PREFIX + 2 + serialized_expr_size_estimate(cast.expr())
+    } else {
+        // e.g. a column, serialized mostly as its name
+        PREFIX + expr.to_string().len()
+    }
+}
+
+fn serialized_scalar_size_estimate(value: &ScalarValue) -> usize {
+    // every value is prefixed with its type tag
+    1 + match value {
+        _ if value.is_null() => 1,
+        ScalarValue::Boolean(_) | ScalarValue::Int8(_) | ScalarValue::UInt8(_) => 1,
+        ScalarValue::Int16(_) | ScalarValue::UInt16(_) => 2,
+        ScalarValue::Int32(_)
+        | ScalarValue::UInt32(_)
+        | ScalarValue::Float32(_)
+        | ScalarValue::Date32(_)
+        | ScalarValue::IntervalYearMonth(_) => 4,
+        ScalarValue::Decimal128(_, _, _) | ScalarValue::IntervalMonthDayNano(_) => 16,
+        ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => 1 + v.len(),
+        ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)) => 1 + v.len(),
+        ScalarValue::List(Some(values), _) | ScalarValue::Struct(Some(values), _) => {
+            values.iter().map(serialized_scalar_size_estimate).sum()
+        }
+        _ => 8,
+    }
+}
+
+impl InListExpr {
+    /// Create a new InList expression
+    pub fn new(
+        expr: Arc<dyn PhysicalExpr>,
+        list: Vec<Arc<dyn PhysicalExpr>>,
+        negated: bool,
+    ) -> Self {
+        Self::new_with_threshold(expr, list, negated, OPTIMIZER_INSET_THRESHOLD)
+    }
+
+    /// Create a new InList expression probing a set if the list is all
+    /// literals and longer than `threshold`
+    pub fn new_with_threshold(
+        expr: Arc<dyn PhysicalExpr>,
+        list: Vec<Arc<dyn PhysicalExpr>>,
+        negated: bool,
+        threshold: usize,
+    ) -> Self {
+        let values = if list.len() > threshold {
+            static_filter_values(&list)
+        } else {
+            None
+        };
+        Self {
+            set: values.map(|values| InSet::new(values.into_iter().collect())),
+            set_threshold: threshold,
+            ..Self::new_without_set(expr, list, negated)
+        }
+    }
+
+    /// Use a set for lists of literals longer than `threshold`, rather than
+    /// the default of 30. Any set is built now, even if created with
+    /// [`Self::new_lazy`].
+    pub fn with_set_threshold(mut self, threshold: usize) -> Self {
+        self.set_threshold = threshold;
+        // a set from the build side has no list to rebuild it from
+        if !self.list.is_empty() {
+            self.set = self.rebuilt_set(&self.list);
+            self.lazy_set = None;
+        }
+        self
+    }
+
+    // the set for `list` under the threshold of this expression
+    fn rebuilt_set(&self, list: &[Arc<dyn PhysicalExpr>]) -> Option<InSet> {
+        Self::new_with_threshold(
+            self.expr.clone(),
+            list.to_vec(),
+            self.negated,
+            self.set_threshold,
+        )
+        .set
+    }
+
+    // an expression comparing against the list one value at a time, with
+    // every option disabled
+    fn new_without_set(
+        expr: Arc<dyn PhysicalExpr>,
+        list: Vec<Arc<dyn PhysicalExpr>>,
+        negated: bool,
+    ) -> Self {
+        Self {
+            expr,
+            list,
+            negated,
+            set: None,
+            dynamic_set: None,
+            float_eq: None,
+            lazy_set: None,
+            list_sorted: OnceCell::new(),
+            dropped_literals: None,
+            trim_trailing_whitespace: false,
+            null_indicator: None,
+            sorted_merge: None,
+            input_sorted: false,
+            null_handling: NullHandling::SqlStandard,
+            matched: None,
+            matched_last: None,
+            #[cfg(feature = "regex_expressions")]
+            regex: None,
+            date_trunc: None,
+            adaptive: None,
+            ignore_diacritics: false,
+            set_threshold: OPTIMIZER_INSET_THRESHOLD,
+            max_literal_bytes: None,
+            list_values: OnceCell::new(),
+            coerced_list_values: OnceCell::new(),
+            value_bounds: OnceCell::new(),
+            range_folding: false,
+            range: None,
+            probe_cache: None,
+        }
+    }
+
+    /// Create a new InList expression that, if the list is large enough to
+    /// use a set, defers building the set until the first call to `evaluate`.
+    /// This avoids the cost for plans that are built but never executed, at
+    /// the price of a one-time check per evaluation.
+    pub fn new_lazy(
+        expr: Arc<dyn PhysicalExpr>,
+        list: Vec<Arc<dyn PhysicalExpr>>,
+        negated: bool,
+    ) -> Self {
+        let lazy_set = (list.len() > OPTIMIZER_INSET_THRESHOLD
+            && check_all_static_filter_expr(&list))
+        .then(OnceCell::new);
+        Self {
+            lazy_set,
+            ..Self::new_without_set(expr, list, negated)
+        }
+    }
+
+    /// Create a new InList expression probing `set`, built beforehand from the
+    /// literals of `list`, rather than building a set from `list` again. For
+    /// many expressions over the same large list, e.g. one per partition, the
+    /// set can be built once and cloned, which copies it without hashing its
+    /// values again. Debug builds check that `set` holds exactly the values of
+    /// `list`; release builds trust the caller.
+    pub fn new_with_set(
+        expr: Arc<dyn PhysicalExpr>,
+        list: Vec<Arc<dyn PhysicalExpr>>,
+        negated: bool,
+        set: InSet,
+    ) -> Self {
+        debug_assert!(
+            static_filter_values(&list)
+                .map(|values| values.into_iter().collect::<HashSet<_, RandomState>>())
+                .as_ref()
+                == Some(set.get_set()),
+            "set does not match the values of the InList"
+        );
+        Self {
+            set: Some(set),
+            ..Self::new_without_set(expr, list, negated)
+        }
+    }
+
+    /// Create a new InList expression probing a set built elsewhere, e.g. with
+    /// an [`InSetBuilder`] from the build side of a stream
+    pub fn from_build_side(
+        expr: Arc<dyn PhysicalExpr>,
+        set: InSet,
+        negated: bool,
+    ) -> Self {
+        Self {
+            set: Some(set),
+            ..Self::new_without_set(expr, vec![], negated)
+        }
+    }
+
+    /// Create a new InList expression probing `set`, merging it with inputs
+    /// flagged as sorted by [`Self::with_sorted_input`]. The input must be
+    /// `Int64`.
+    pub fn new_sorted_merge(
+        expr: Arc<dyn PhysicalExpr>,
+        set: SortedMergeInSet,
+        negated: bool,
+    ) -> Self {
+        Self {
+            sorted_merge: Some(set),
+            ..Self::new_without_set(expr, vec![], negated)
+        }
+    }
+
+    /// Declare that input arrays are sorted ascending with NULLs anywhere, so
+    /// a [`SortedMergeInSet`] is merged with them. An input found not to be
+    /// sorted is still evaluated correctly, by binary search.
+    pub fn with_sorted_input(mut self) -> Self {
+        self.input_sorted = true;
+        self
+    }
+
+    /// Create a new InList expression from a bound list supplied as a
+    /// `values` array plus a `validity` bitmap, as used for Arrow null
+    /// buffers (a set bit marks a valid entry). This lets callers mark entries
+    /// NULL without wrapping them; such entries, like NULLs in `values`
+    /// itself, follow the usual three-valued semantics of a NULL in the list.
+    pub fn from_values_with_validity(
+        expr: Arc<dyn PhysicalExpr>,
+        values: &ArrayRef,
+        validity: &Buffer,
+        negated: bool,
+    ) -> Result<Self> {
+        if validity.len() * 8 < values.len() {
+            return Err(DataFusionError::Internal(format!(
+                "Validity bitmap of {} bytes is too short for {} IN list values",
+                validity.len(),
+                values.len()
+            )));
+        }
+        let mut set = HashSet::with_capacity_and_hasher(values.len(), RandomState::new());
+        let mut contains_null = false;
+        for i in 0..values.len() {
+            if values.is_null(i) || !bit_util::get_bit(validity.as_slice(), i) {
+                contains_null = true;
+            } else {
+                set.insert(ScalarValue::try_from_array(values, i)?);
+            }
+        }
+        let mut in_set = InSet::new(set);
+        in_set.contains_null = contains_null;
+        Ok(Self::from_build_side(expr, in_set, negated))
+    }
+
+    /// Create a new InList expression whose membership is read from a
+    /// [`DynamicInSet`] on every call to `evaluate`
+    pub fn new_with_dynamic_set(
+        expr: Arc<dyn PhysicalExpr>,
+        dynamic_set: DynamicInSet,
+        negated: bool,
+    ) -> Self {
+        Self {
+            dynamic_set: Some(dynamic_set),
+            ..Self::new_without_set(expr, vec![], negated)
+        }
+    }
+
+    /// Check that the values of a set bound at runtime, e.g. with
+    /// [`Self::from_values_with_validity`] or a [`DynamicInSet`], can be
+    /// compared with the input expression in `input_schema`, rather than
+    /// silently never matching. For a [`DynamicInSet`] this checks its current
+    /// contents. Returns an error naming both types otherwise.
+    pub fn validate_bound_set(&self, input_schema: &Schema) -> Result<()> {
+        let expected = self.expr.data_type(input_schema)?;
+        let validate = |mut values: std::collections::hash_set::Iter<ScalarValue>| {
+            match values
+            .find(|v| !v.is_null() && !set_value_type_matches(&v.get_datatype(), &expected))
+        {
+            Some(v) => Err(DataFusionError::Plan(format!(
+                "IN set bound with values of type {:?} cannot be compared with {} of type {:?}",
+                v.get_datatype(),
+                self.expr,
+                expected
+            ))),
+            None => Ok(()),
+        }
+        };
+        if let Some(dynamic_set) = &self.dynamic_set {
+            validate(dynamic_set.read().iter())?;
+        }
+        if let Some(sorted_merge) = &self.sorted_merge {
+            if !sorted_merge.values.is_empty()
+                && !set_value_type_matches(&DataType::Int64, &expected)
+            {
+                return Err(DataFusionError::Plan(format!(
+                    "IN set bound with values of type Int64 cannot be compared with {} of type {:?}",
+                    self.expr, expected
+                )));
+            }
+        }
+        match &self.set {
+            Some(in_set) if self.list.is_empty() => validate(in_set.set.iter()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Track which list entries are matched by `evaluate`, reported by
+    /// [`Self::unmatched_entries`], e.g. to detect stale filter lists. Copies
+    /// of this expression share the tracking. Off by default as it costs a
+    /// lookup per matching row.
+    pub fn with_match_tracking(mut self) -> Self {
+        self.matched = Some(Arc::default());
+        self
+    }
+
+    /// The distinct non-null values of the list, in list order, that no row
+    /// evaluated so far has matched. Empty unless enabled with
+    /// [`Self::with_match_tracking`], or if the list is not constant.
+    pub fn unmatched_entries(&self) -> Vec<ScalarValue> {
+        self.tracked_entries(&self.matched, false)
+    }
+
+    /// Track which list entries are matched by each `evaluate` call, reported
+    /// by [`Self::matched_values`], e.g. for audit logging. Copies of this
+    /// expression share the tracking.
+    pub fn with_matched_values(mut self) -> Self {
+        self.matched_last = Some(Arc::default());
+        self
+    }
+
+    /// The distinct non-null values of the list, in list order, that at least
+    /// one row of the most recent `evaluate` call matched. Empty unless enabled
+    /// with [`Self::with_matched_values`], or if the list is not constant.
+    pub fn matched_values(&self) -> Vec<ScalarValue> {
+        self.tracked_entries(&self.matched_last, true)
+    }
+
+    // the distinct non-null list values that were, or were not, matched by
+    // an input in `matched`
+    fn tracked_entries(
+        &self,
+        matched: &Option<Arc<Mutex<MatchedKeys>>>,
+        was_matched: bool,
+    ) -> Vec<ScalarValue> {
+        let matched = match matched {
+            Some(matched) => matched.lock(),
+            None => return vec![],
+        };
+        let is_matched = |v: &ScalarValue| match &matched.data_type {
+            Some(data_type) => self
+                .comparison_key(v, data_type)
+                .map_or(false, |key| matched.keys.contains(&key)),
+            None => false,
+        };
+        let values = match (&self.set, static_filter_values(&self.list)) {
+            (_, Some(values)) if !self.list.is_empty() => values,
+            (Some(in_set), _) => in_set.get_set().iter().cloned().collect(),
+            _ => vec![],
+        };
+        let mut seen = HashSet::new();
+        values
+            .into_iter()
+            .filter(|v| {
+                !v.is_null() && is_matched(v) == was_matched && seen.insert(v.clone())
+            })
+            .collect()
+    }
+
+    /// `value` as compared with inputs of `data_type`, so that an input value
+    /// and the list entries it matches have equal keys: numbers are cast to
+    /// the input type, or compared in f64 for floats, decimals rescaled to
+    /// its scale, timestamps given in its unit and strings folded. `None` for
+    /// a value no input can match.
+    fn comparison_key(
+        &self,
+        value: &ScalarValue,
+        data_type: &DataType,
+    ) -> Option<ScalarValue> {
+        match (data_type, value) {
+            (DataType::Float32 | DataType::Float64, ScalarValue::Float32(v)) => {
+                Some(ScalarValue::Float64(v.map(f64::from)))
+            }
+            (DataType::Float32, ScalarValue::Float64(_)) => Some(value.clone()),
+            (
+                DataType::Decimal(precision, scale),
+                ScalarValue::Decimal128(Some(v), _, from),
+            ) => rescale_decimal(*v, *from, *scale)
+                .ok()
+                .flatten()
+                .map(|v| ScalarValue::Decimal128(Some(v), *precision, *scale)),
+            (DataType::Timestamp(unit, _), value) => timestamp_literal_value(value, unit)
+                .ok()
+                .flatten()
+                .map(|v| ScalarValue::Int64(Some(v))),
+            (
+                DataType::Utf8 | DataType::LargeUtf8,
+                ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)),
+            ) => Some(ScalarValue::Utf8(Some(self.fold_utf8(v).into_owned()))),
+            (data_type, value) if needs_coercion(value, data_type) => {
+                coerce_literal(value, data_type).ok()?.ok()
+            }
+            _ => Some(value.clone()),
+        }
+    }
+
+    /// Compare NULLs according to `null_handling` rather than the SQL
+    /// standard
+    pub fn with_null_handling(mut self, null_handling: NullHandling) -> Self {
+        self.null_handling = null_handling;
+        self
+    }
+
+    /// Compare `Float32` / `Float64` values using `eq` instead of exact
+    /// equality. NULLs never reach `eq` and keep their usual three-valued
+    /// semantics. As `eq` need not be consistent with hashing, this disables
+    /// the set path.
+    pub fn with_float_eq(mut self, eq: FloatEq) -> Self {
+        self.set = None;
+        self.lazy_set = None;
+        self.float_eq = Some(eq);
+        self
+    }
+
+    /// Compare `Utf8` / `LargeUtf8` values ignoring trailing whitespace on
+    /// both sides, as for space padded `CHAR(n)` columns, so `'AB '` matches
+    /// `'AB'`. As the set holds the untrimmed literals, this disables the set
+    /// path for lists; sets built elsewhere still compare exactly.
+    pub fn with_trim_trailing_whitespace(mut self) -> Self {
+        if !self.list.is_empty() {
+            self.set = None;
+            self.lazy_set = None;
+        }
+        self.trim_trailing_whitespace = true;
+        self
+    }
+
+    /// Compare `Utf8` / `LargeUtf8` values ignoring diacritics on both sides,
+    /// so `'resume'` matches `'résumé'`. Values are folded by their canonical
+    /// (NFD) decomposition with the combining marks removed, which strips
+    /// accents but keeps letters without a decomposition such as `'ø'`. Like
+    /// [`Self::with_trim_trailing_whitespace`], this disables the set path for
+    /// lists.
+    #[cfg(feature = "unicode_expressions")]
+    pub fn with_ignore_diacritics(mut self) -> Self {
+        if !self.list.is_empty() {
+            self.set = None;
+            self.lazy_set = None;
+        }
+        self.ignore_diacritics = true;
+        self
+    }
+
+    // whether strings are folded before being compared
+    fn folds_utf8(&self) -> bool {
+        self.trim_trailing_whitespace || self.ignore_diacritics
+    }
+
+    // fold `v` as configured for comparison
+    fn fold_utf8<'a>(&self, v: &'a str) -> std::borrow::Cow<'a, str> {
+        let v = match self.trim_trailing_whitespace {
+            true => v.trim_end(),
+            false => v,
+        };
+        #[cfg(feature = "unicode_expressions")]
+        if self.ignore_diacritics {
+            use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+            return v.nfd().filter(|c| !is_combining_mark(*c)).collect();
+        }
+        v.into()
+    }
+
+    /// Reject string and binary literals in the list longer than `max_bytes`,
+    /// e.g. to bound the memory of plans built from untrusted queries. They
+    /// are rejected rather than truncated, which would change the result.
+    /// Returns an error for a literal over the limit, now or when rebuilt
+    /// with [`PhysicalExpr::with_new_children`]. Unlimited by default.
+    pub fn with_max_literal_bytes(mut self, max_bytes: usize) -> Result<Self> {
+        self.max_literal_bytes = Some(max_bytes);
+        self.check_literal_bytes()?;
+        Ok(self)
+    }
+
+    fn check_literal_bytes(&self) -> Result<()> {
+        let max_bytes = match self.max_literal_bytes {
+            Some(max_bytes) => max_bytes,
+            None => return Ok(()),
+        };
+        for entry in &self.list {
+            let len = match entry
+                .as_any()
+                .downcast_ref::<expressions::Literal>()
+                .map(|l| l.value())
+            {
+                Some(ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v))) => {
+                    v.len()
+                }
+                Some(
+                    ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)),
+                ) => v.len(),
+                _ => continue,
+            };
+            if len > max_bytes {
+                return Err(DataFusionError::Plan(format!(
+                    "IN list literal of {} bytes exceeds the maximum of {} bytes",
+                    len, max_bytes
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Match `Utf8` / `LargeUtf8` values against the list compiled to a single
+    /// anchored regex alternation `^(v1|v2|...)$` rather than hashing them.
+    /// This avoids building a [`ScalarValue`] per row. Returns an error unless
+    /// the list is constant strings, or if the
+    /// compiled regex exceeds the size limit, as for very long lists. Ignored
+    /// with [`Self::with_trim_trailing_whitespace`].
+    #[cfg(feature = "regex_expressions")]
+    pub fn with_regex_alternation(mut self) -> Result<Self> {
+        let values = static_filter_values(&self.list).ok_or_else(|| {
+            DataFusionError::NotImplemented(format!(
+                "Compiling {} to a regex requires a constant list",
+                self
+            ))
+        })?;
+        let mut alternatives = Vec::with_capacity(values.len());
+        for value in &values {
+            match value {
+                ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => {
+                    alternatives.push(regex::escape(v))
+                }
+                ScalarValue::Utf8(None) | ScalarValue::LargeUtf8(None) => {}
+                _ => {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "Compiling an IN list of {:?} to a regex",
+                        value.get_datatype()
+                    )))
+                }
+            }
+        }
+        // an empty alternation would match the empty string
+        if alternatives.is_empty() {
+            return Ok(self);
+        }
+        let pattern = format!(r"\A(?:{})\z", alternatives.join("|"));
+        let regex = regex::Regex::new(&pattern).map_err(|e| {
+            DataFusionError::Plan(format!("Cannot compile IN list to a regex: {}", e))
+        })?;
+        self.regex = Some((regex, alternatives.len() < values.len()));
+        Ok(self)
+    }
+
+    /// Choose between scanning a constant list and probing a set of its values
+    /// from the cost of the first batches, rather than from the list length
+    /// alone: after [`ADAPTIVE_WARMUP_BATCHES`] scans, a set replaces the scan
+    /// if the scan's observed comparisons per row cost more than a lookup.
+    /// Lists that use a set already, or compare with custom semantics, keep
+    /// their representation.
+    pub fn with_adaptive_representation(mut self) -> Self {
+        if self.set.is_none()
+            && self.lazy_set.is_none()
+            && self.float_eq.is_none()
+            && !self.folds_utf8()
+            && check_all_static_filter_expr(&self.list)
+        {
+            self.adaptive = Some(Arc::new(AdaptiveState::default()));
+        }
+        self
+    }
+
+    /// Evaluate an `IN` list of integer literals that is a single contiguous
+    /// run once deduplicated, such as `x IN (4, 2, 3)`, as the range
+    /// `x BETWEEN 2 AND 4`: two comparisons per row rather than a lookup.
+    /// Other lists, `NOT IN` and inputs of another type than the list keep
+    /// their representation.
+    pub fn with_range_folding(mut self) -> Self {
+        self.range_folding = true;
+        self.range = self.folded_range();
+        self
+    }
+
+    // the list as a range, if enabled and it is one
+    fn folded_range(&self) -> Option<IntegerRange> {
+        (self.range_folding && !self.negated)
+            .then(|| IntegerRange::try_new(&self.list))
+            .flatten()
+    }
+
+    /// Remember whether each of the `capacity` most recently probed
+    /// `Utf8` / `LargeUtf8` values is in the set, and compare values against
+    /// those first rather than hashing them, for streams repeating a few hot
+    /// values. A cache lookup compares with every cached value, so `capacity`
+    /// should be small. For 10M rows of 5 distinct short strings the `in_list`
+    /// benchmark measured no gain over probing the set, about 5% slower, as
+    /// such strings hash cheaply already, so measure before enabling.
+    /// Concurrent evaluations, e.g. of partitions, each use a cache of their
+    /// own. The cache is dropped when a [`DynamicInSet`] is updated, and
+    /// results are never affected. Without a set, for short lists, this has
+    /// no effect.
+    pub fn with_probe_cache(mut self, capacity: usize) -> Self {
+        self.probe_cache = (capacity > 0).then(|| Arc::new(ProbeCaches::new(capacity)));
+        self
+    }
+
+    // empty caches of the same capacity as any caches of `self`
+    fn fresh_probe_cache(&self) -> Option<Arc<ProbeCaches>> {
+        self.probe_cache
+            .as_ref()
+            .map(|caches| Arc::new(ProbeCaches::new(caches.capacity)))
+    }
+
+    /// Truncate timestamp input values to `granularity` before testing them,
+    /// as for `date_trunc(granularity, ts) IN (...)` but without a separate
+    /// pass, so that e.g. `'day'` matches timestamps against `Date32` list
+    /// values. Returns an error for a granularity `date_trunc` doesn't support.
+    pub fn with_date_trunc(mut self, granularity: &str) -> Result<Self> {
+        date_trunc_single(granularity, 0)?;
+        self.date_trunc = Some(granularity.to_string());
+        Ok(self)
+    }
+
+    /// Treat the value of rows for which `indicator` evaluates to true as NULL,
+    /// whatever the validity of the value array, for schemas that store
+    /// nullability in a separate boolean column. Rows where the indicator is
+    /// itself NULL are treated as NULL too.
+    pub fn with_null_indicator(mut self, indicator: Arc<dyn PhysicalExpr>) -> Self {
+        self.null_indicator = Some(indicator);
+        self
+    }
+
+    /// Record the literals dropped by [`Self::coerce_literals`], retrievable
+    /// with [`Self::dropped_literals`]. Nothing is collected by default.
+    pub fn with_literal_diagnostics(mut self) -> Self {
+        self.dropped_literals.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// Cast the literals of the list to the type of the input expression in
+    /// `input_schema`. Literals that cannot represent any value of that type,
+    /// e.g. out of range or unparseable, can never match and are dropped.
+    /// Returns an error if a literal's type cannot be cast to the input type.
+    pub fn coerce_literals(mut self, input_schema: &Schema) -> Result<Self> {
+        let target = self.expr.data_type(input_schema)?;
+        let mut list = Vec::with_capacity(self.list.len());
+        for expr in std::mem::take(&mut self.list) {
+            let value = match expr.as_any().downcast_ref::<expressions::Literal>() {
+                Some(literal) if literal.value().get_datatype() != target => {
+                    literal.value()
+                }
+                _ => {
+                    list.push(expr);
+                    continue;
+                }
+            };
+            match coerce_literal(value, &target)? {
+                Ok(coerced) => list.push(expressions::lit(coerced)),
+                Err(reason) => {
+                    if let Some(dropped) = &mut self.dropped_literals {
+                        dropped.push((value.clone(), reason));
+                    }
+                }
+            }
+        }
+        self.list = list;
+        self.list_sorted = OnceCell::new();
+        self.list_values = OnceCell::new();
+        self.coerced_list_values = OnceCell::new();
+        self.value_bounds = OnceCell::new();
+        // rebuild any set from the coerced values
+        if self.set.is_some() {
+            self.set = self.rebuilt_set(&self.list);
+        }
+        if self.lazy_set.is_some() {
+            self.lazy_set = Some(OnceCell::new());
+        }
+        if self.adaptive.is_some() {
+            self.adaptive = Some(Arc::default());
+        }
+        self.range = self.folded_range();
+        self.probe_cache = self.fresh_probe_cache();
+        Ok(self)
+    }
+
+    /// Drop the literals outside `[low, high]`, e.g. to prune `x IN (1, 5, 10,
+    /// 20) AND x BETWEEN 4 AND 15` to `x IN (5, 10)`. NULL literals are kept.
+    /// Returns an error unless the list is constant and its values are
+    /// ordered against the bounds.
+    pub fn restrict_to_range(
+        &self,
+        low: &ScalarValue,
+        high: &ScalarValue,
+    ) -> Result<InListExpr> {
+        let values = static_filter_values(&self.list).ok_or_else(|| {
+            DataFusionError::NotImplemented(format!(
+                "Restricting {} to a range requires a constant list",
+                self
+            ))
+        })?;
+        let mut list = Vec::with_capacity(values.len());
+        for (expr, value) in self.list.iter().zip(values) {
+            if value.is_null() {
+                list.push(expr.clone());
+                continue;
+            }
+            match (value.partial_cmp(low), value.partial_cmp(high)) {
+                (Some(Ordering::Less), _) | (_, Some(Ordering::Greater)) => {}
+                (Some(_), Some(_)) => list.push(expr.clone()),
+                _ => {
+                    return Err(DataFusionError::Plan(format!(
+                        "Cannot restrict IN list value {:?} to range [{:?}, {:?}]",
+                        value, low, high
+                    )))
+                }
+            }
+        }
+
+        Ok(self.with_list(list))
+    }
+
+    /// Rewrite `x IN (v)` to `x = v` and `x NOT IN (v)` to `x != v`, which
+    /// are cheaper and understood by pruning. `None` unless the list has one
+    /// entry, of the type of the value in `input_schema`, and the comparison
+    /// is the same: not for a NULL or NaN literal, whose equality differs, nor
+    /// with options that change how values compare or track matches.
+    pub fn try_simplify_to_binary(
+        &self,
+        input_schema: &Schema,
+    ) -> Option<Arc<dyn PhysicalExpr>> {
+        let entry = match self.list.as_slice() {
+            [entry] => entry,
+            _ => return None,
+        };
+        // `BinaryExpr` does not coerce its sides
+        if entry.data_type(input_schema).ok()?
+            != self.expr.data_type(input_schema).ok()?
+        {
+            return None;
+        }
+        if self.folds_utf8()
+            || self.null_handling != NullHandling::SqlStandard
+            || self.float_eq.is_some()
+            || self.date_trunc.is_some()
+            || self.null_indicator.is_some()
+            || self.matched.is_some()
+            || self.matched_last.is_some()
+        {
+            return None;
+        }
+        if let Some(values) = static_filter_values(&self.list) {
+            match &values[0] {
+                v if v.is_null() => return None,
+                ScalarValue::Float32(Some(v)) if v.is_nan() => return None,
+                ScalarValue::Float64(Some(v)) if v.is_nan() => return None,
+                _ => {}
+            }
+        }
+        let op = if self.negated {
+            Operator::NotEq
+        } else {
+            Operator::Eq
+        };
+        Some(Arc::new(expressions::BinaryExpr::new(
+            self.expr.clone(),
+            op,
+            entry.clone(),
+        )))
+    }
+
+    /// Split the list into `k` sublists of roughly equal length, so that the
+    /// expressions can be evaluated in parallel: their OR, or AND if negated,
+    /// equals this predicate. Returns fewer expressions if the list is shorter
+    /// than `k`, and only this one if the values are in a set from elsewhere.
+    pub fn split(&self, k: usize) -> Vec<InListExpr> {
+        if self.list.is_empty() {
+            return vec![self.clone()];
+        }
+        let chunk_size = (self.list.len() + k.max(1) - 1) / k.max(1);
+        self.list
+            .chunks(chunk_size)
+            .map(|list| self.with_list(list.to_vec()))
+            .collect()
+    }
+
+    /// Order the list by descending frequency in `freqs`, e.g. from column
+    /// statistics, so that a linear scan tests the most frequent values first.
+    /// Values without a frequency keep their order, after the others. Sets are
+    /// unaffected, and a list that isn't constant is not reordered.
+    pub fn reorder_by_frequency(
+        &self,
+        freqs: &HashMap<ScalarValue, usize>,
+    ) -> InListExpr {
+        let values = match static_filter_values(&self.list) {
+            Some(values) => values,
+            None => return self.clone(),
+        };
+        let mut list = self.list.iter().zip(values).collect::<Vec<_>>();
+        list.sort_by_key(|(_, value)| {
+            std::cmp::Reverse(freqs.get(value).copied().unwrap_or(0))
+        });
+        self.with_list(list.into_iter().map(|(expr, _)| expr.clone()).collect())
+    }
+
+    // this expression with its options over another list, rebuilding any
+    // representation derived from the list
+    fn with_list(&self, list: Vec<Arc<dyn PhysicalExpr>>) -> Self {
+        let mut expr = self.clone();
+        expr.list = list;
+        expr.list_sorted = OnceCell::new();
+        expr.list_values = OnceCell::new();
+        expr.coerced_list_values = OnceCell::new();
+        expr.value_bounds = OnceCell::new();
+        if expr.set.is_some() {
+            expr.set = self.rebuilt_set(&expr.list);
+        }
+        if expr.lazy_set.is_some() {
+            expr.lazy_set = Some(OnceCell::new());
+        }
+        if expr.adaptive.is_some() {
+            expr.adaptive = Some(Arc::default());
+        }
+        expr.range = expr.folded_range();
+        expr.probe_cache = self.fresh_probe_cache();
+        #[cfg(feature = "regex_expressions")]
+        if expr.regex.take().is_some() {
+            // a new list that does not compile, e.g. as it is no longer
+            // constant, is compared without the regex
+            expr = match expr.clone().with_regex_alternation() {
+                Ok(compiled) => compiled,
+                Err(_) => expr,
+            };
+        }
+        expr
+    }
+
+    /// The literals dropped by [`Self::coerce_literals`] and the reason for
+    /// each. Empty unless enabled with [`Self::with_literal_diagnostics`].
+    pub fn dropped_literals(&self) -> &[(ScalarValue, String)] {
+        self.dropped_literals.as_deref().unwrap_or(&[])
+    }
+
+    /// Input expression
+    pub fn expr(&self) -> &Arc<dyn PhysicalExpr> {
+        &self.expr
+    }
+
+    /// List to search in
+    pub fn list(&self) -> &[Arc<dyn PhysicalExpr>] {
+        &self.list
+    }
+
+    /// Is this negated e.g. NOT IN LIST
+    pub fn negated(&self) -> bool {
+        self.negated
+    }
+
+    /// The set the list is probed with, if built. This is `None` for lists
+    /// that are small, see [`InListExpr::with_set_threshold`], or not all
+    /// literals, for a dynamic set, and for a lazy set not yet built.
+    pub fn set(&self) -> Option<&InSet> {
+        self.set
+            .as_ref()
+            .or_else(|| self.lazy_set.as_ref().and_then(|set| set.get()))
+    }
+
+    /// Returns `Some(true)` if every value of an all-literal list satisfies
+    /// `pred`, `Some(false)` if any does not, and `None` if the list contains
+    /// non-literal expressions
+    pub fn all_literals_satisfy(
+        &self,
+        pred: impl Fn(&ScalarValue) -> bool,
+    ) -> Option<bool> {
+        let values = static_filter_values(&self.list)?;
+        Some(values.iter().all(pred))
+    }
+
+    /// Returns true if this is a non-negated IN whose list covers every value
+    /// of `domain`, the set of values the input is known to take. Such a
+    /// predicate is true for every non-null input and can be eliminated.
+    pub fn is_tautology_over(&self, domain: &HashSet<ScalarValue>) -> bool {
+        if self.negated {
+            return false;
+        }
+        match static_filter_values(&self.list) {
+            Some(values) => {
+                let values = values.into_iter().collect::<HashSet<_>>();
+                domain.is_subset(&values)
+            }
+            None => false,
+        }
+    }
+
+    /// Returns true if this predicate can never be true, so a branch filtering
+    /// on it can be pruned: a positive IN of an empty or all-NULL list, or,
+    /// with SQL standard NULLs, a NOT IN of a list with a NULL entry. With
+    /// [`NullHandling::NullEqualsNull`] a NULL entry matches NULL inputs,
+    /// unless `column_not_null` says the input has none. Lists that are not
+    /// constant, and dynamic sets, are assumed satisfiable.
+    pub fn is_unsatisfiable(&self, column_not_null: bool) -> bool {
+        if self.dynamic_set.is_some() || self.sorted_merge.is_some() {
+            return false;
+        }
+        let (non_null, has_null) = match (&self.set, static_filter_values(&self.list)) {
+            // a set bound at runtime need not hold the NULL it flags
+            (Some(in_set), _) if self.list.is_empty() => (
+                in_set.get_set().iter().filter(|v| !v.is_null()).count(),
+                in_set.contains_null,
+            ),
+            (_, Some(values)) => {
+                let nulls = values.iter().filter(|v| v.is_null()).count();
+                (values.len() - nulls, nulls > 0)
+            }
+            (_, None) => return false,
+        };
+        match (self.negated, self.null_handling) {
+            (false, NullHandling::NullEqualsNull) if has_null => {
+                non_null == 0 && column_not_null
+            }
+            (false, _) => non_null == 0,
+            (true, NullHandling::SqlStandard) => has_null,
+            (true, _) => false,
+        }
+    }
+
+    /// The share of list entries that are NULL literals, from 0.0 to 1.0, e.g.
+    /// for planner heuristics: such a list is never null-rejecting under
+    /// `NOT IN`, and its effective cardinality is lower. Duplicates count
+    /// every time, and an empty list, as for a build side set, gives 0.0.
+    pub fn null_literal_fraction(&self) -> f64 {
+        if self.list.is_empty() {
+            return 0.0;
+        }
+        let nulls = self
+            .list
+            .iter()
+            .filter(|expr| {
+                matches!(
+                    expr.as_any().downcast_ref::<expressions::Literal>(),
+                    Some(literal) if literal.value().is_null()
+                )
+            })
+            .count();
+        nulls as f64 / self.list.len() as f64
+    }
+
+    /// Combine this IN list with `other`, on another column, into a row-value
+    /// IN testing both at once: `a IN (1, 2)` zipped with `b IN ('x', 'y')`
+    /// is `(a, b) IN ((1, 'x'), (2, 'y'))`. The lists must be positionally
+    /// paired, so that the n-th literals of both form the n-th tuple. Unlike
+    /// `a IN (1, 2) AND b IN ('x', 'y')` it rejects `(1, 'y')` and `(2, 'x')`.
+    pub fn zip_with(&self, other: &InListExpr) -> Result<StructInListExpr> {
+        if self.negated != other.negated {
+            return Err(DataFusionError::Plan(
+                "Cannot zip IN with NOT IN".to_string(),
+            ));
+        }
+        let (left, right) = match (
+            static_filter_values(&self.list),
+            static_filter_values(&other.list),
+        ) {
+            (Some(left), Some(right)) => (left, right),
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "Cannot zip IN lists with non-literal values: {} and {}",
+                    self, other
+                )))
+            }
+        };
+        if left.len() != right.len() {
+            return Err(DataFusionError::Plan(format!(
+                "Cannot zip IN lists of {} and {} values",
+                left.len(),
+                right.len()
+            )));
+        }
+        let tuples = left
+            .into_iter()
+            .zip(right)
+            .map(|(l, r)| vec![l, r])
+            .collect();
+        StructInListExpr::try_new(
+            vec![self.expr.clone(), other.expr.clone()],
+            tuples,
+            self.negated,
+        )
+    }
+
+    /// Feed a deterministic fingerprint of this expression into `hasher`, e.g.
+    /// for plan caching. Lists with the same distinct values hash the same
+    /// regardless of their order or duplicates.
+    pub fn fingerprint(&self, hasher: &mut impl Hasher) {
+        physical_expr_hash(&self.expr, hasher);
+        self.negated.hash(hasher);
+        match static_filter_values(&self.list) {
+            Some(mut values) => {
+                values.sort_by(|a, b| {
+                    a.partial_cmp(b)
+                        .unwrap_or_else(|| format!("{:?}", a).cmp(&format!("{:?}", b)))
+                });
+                values.dedup();
+                values.hash(hasher);
+            }
+            None => {
+                let mut hashes = self
+                    .list
+                    .iter()
+                    .map(|e| {
+                        let mut entry_hasher = DefaultHasher::new();
+                        physical_expr_hash(e, &mut entry_hasher);
+                        entry_hasher.finish()
+                    })
+                    .collect::<Vec<_>>();
+                hashes.sort_unstable();
+                hashes.dedup();
+                hashes.hash(hasher);
+            }
+        }
+    }
+
+    /// Estimate of the size in bytes of this expression when serialized for
+    /// the wire (e.g. protobuf), based on the list length and element types.
+    /// Useful to decide whether to broadcast the predicate or reference it.
+    pub fn serialized_size_estimate(&self) -> usize {
+        // message tags plus the negated flag
+        const OVERHEAD: usize = 4;
+        OVERHEAD
+            + serialized_expr_size_estimate(&self.expr)
+            + self
+                .list
+                .iter()
+                .map(serialized_expr_size_estimate)
+                .sum::<usize>()
+    }
+
+    /// Estimate the cost of testing one row, in units of a single comparison
+    /// of two values, for ordering the predicates of a filter. Reflects the
+    /// representation chosen: a linear scan costs the list length while a
+    /// set lookup costs about the same for any number of values.
+    pub fn per_row_cost(&self) -> f64 {
+        if let Some(sorted_merge) = &self.sorted_merge {
+            return (sorted_merge.values().len() as f64).log2().max(1.0);
+        }
+        #[cfg(feature = "regex_expressions")]
+        if self.regex.is_some() && !self.folds_utf8() {
+            return SCALAR_HASH_COST;
+        }
+        let in_set = match (&self.set, &self.lazy_set, &self.adaptive) {
+            (Some(in_set), _, _) => Some(in_set),
+            (None, Some(lazy_set), _) => lazy_set.get(),
+            (None, None, Some(adaptive)) => {
+                adaptive.choice.get().and_then(Option::as_ref)
+            }
+            (None, None, None) => None,
+        };
+        match in_set {
+            Some(in_set) => in_set_cost(in_set),
+            // a dynamic set, or a lazy set not built yet
+            None if self.lazy_set.is_some() || self.dynamic_set.is_some() => {
+                SCALAR_HASH_COST
+            }
+            None => self.list.len().max(1) as f64,
+        }
+    }
+
+    /// Returns `Some(true)` if the non-null values of an all-literal list are
+    /// in ascending order, `Some(false)` if not, and `None` if the list
+    /// contains non-literal expressions or values that cannot be ordered. This
+    /// is computed on first use, so users needing sorted values can skip
+    /// sorting a list that was provided sorted.
+    pub fn is_list_sorted(&self) -> Option<bool> {
+        *self.list_sorted.get_or_init(|| {
+            let values = static_filter_values(&self.list)?;
+            let mut values = values.iter().filter(|v| !v.is_null());
+            let mut prev = match values.next() {
+                Some(v) => v,
+                None => return Some(true),
+            };
+            let mut sorted = true;
+            for v in values {
+                sorted &= prev.partial_cmp(v)? != Ordering::Greater;
+                prev = v;
+            }
+            Some(sorted)
+        })
+    }
+
+    // the values of a constant list, cast to the numeric `data_type` of the
+    // input if any differ, or `None` if the list is not constant. The cast is
+    // done once for the first input of another type.
+    fn static_list_values(
+        &self,
+        data_type: &DataType,
+    ) -> Result<Option<Cow<'_, [ScalarValue]>>> {
+        let values = match self
+            .list_values
+            .get_or_init(|| static_filter_values(&self.list))
+        {
+            Some(values) => values,
+            None => return Ok(None),
+        };
+        // as for sets, compare with f64 values in f64
+        let data_type = match data_type {
+            DataType::Float32
+                if values.iter().any(|v| matches!(v, ScalarValue::Float64(_))) =>
+            {
+                &DataType::Float64
+            }
+            data_type => data_type,
+        };
+        if !values.iter().any(|v| needs_coercion(v, data_type)) {
+            return Ok(Some(Cow::Borrowed(values)));
+        }
+        if let Some((coerced_type, coerced)) = self.coerced_list_values.get() {
+            if coerced_type == data_type {
+                return Ok(Some(Cow::Borrowed(coerced)));
+            }
+        }
+        let coerced = coerce_numeric_values(values.iter().cloned(), data_type)?;
+        // an input of another type again is coerced for every batch
+        match self
+            .coerced_list_values
+            .try_insert((data_type.clone(), coerced))
+        {
+            Ok((_, coerced)) => Ok(Some(Cow::Borrowed(coerced))),
+            Err((_, (_, coerced))) => Ok(Some(Cow::Owned(coerced))),
+        }
+    }
+
+    /// Returns the smallest and largest non-null values of an all-literal
+    /// list, e.g. for zone map pruning to skip data whose range does not
+    /// overlap them. Returns `None` for a list with no non-null values or
+    /// non-literal entries, or whose values cannot be ordered, such as values
+    /// of different types or NaN. This is computed on first use.
+    pub fn value_bounds(&self) -> Option<(ScalarValue, ScalarValue)> {
+        self.value_bounds
+            .get_or_init(|| {
+                let values = self
+                    .list_values
+                    .get_or_init(|| static_filter_values(&self.list))
+                    .as_ref()?;
+                // `ScalarValue` orders NaN above all other values, but it is
+                // not in the min / max statistics of e.g. Parquet
+                let is_nan = |v: &ScalarValue| match v {
+                    ScalarValue::Float32(Some(v)) => v.is_nan(),
+                    ScalarValue::Float64(Some(v)) => v.is_nan(),
+                    _ => false,
+                };
+                if values.iter().any(is_nan) {
+                    return None;
+                }
+                let mut values = values.iter().filter(|v| !v.is_null());
+                let first = values.next()?;
+                let (mut min, mut max) = (first, first);
+                for v in values {
+                    if v.partial_cmp(min)? == Ordering::Less {
+                        min = v;
+                    }
+                    if v.partial_cmp(max)? == Ordering::Greater {
+                        max = v;
+                    }
+                }
+                Some((min.clone(), max.clone()))
+            })
+            .clone()
+    }
+
+    /// Returns a copy of this predicate if it is provably null-rejecting, i.e.
+    /// never true for a NULL input, e.g. to turn an outer join into an inner
+    /// join. This holds for any positive IN, and for a NOT IN whose list has
+    /// no NULL entries; otherwise returns `None`.
+    pub fn into_null_rejecting(&self) -> Option<Arc<dyn PhysicalExpr>> {
+        if self.negated && self.list_has_null()? {
+            return None;
+        }
+        Some(Arc::new(self.clone()))
+    }
+
+    /// Returns `NOT (expr IN (list))` for a `NOT IN` whose list has no NULL
+    /// entries, for backends that only have a fast path for positive IN.
+    /// Returns `None` if the list may have a NULL entry, as positive IN fast
+    /// paths do not reliably turn misses into NULL, and for a positive IN.
+    pub fn as_not_wrapped_positive(&self) -> Option<Arc<dyn PhysicalExpr>> {
+        if !self.negated || self.list_has_null()? {
+            return None;
+        }
+        let mut positive = self.clone();
+        positive.negated = false;
+        Some(Arc::new(expressions::NotExpr::new(Arc::new(positive))))
+    }
+
+    // whether the list has a NULL entry, `None` if not known before execution
+    fn list_has_null(&self) -> Option<bool> {
+        if self.list.is_empty() {
+            Some(self.set.as_ref().map_or(false, |set| set.contains_null))
+        } else {
+            Some(
+                static_filter_values(&self.list)?
+                    .iter()
+                    .any(|v| v.is_null()),
+            )
+        }
+    }
+
+    /// Evaluate this predicate for a single `value`, without building a
+    /// batch, e.g. for interpreters probing one value at a time. Returns `None`
+    /// if the result is NULL. Requires the list to be constant unless this
+    /// probes a set.
+    pub fn contains_scalar(&self, value: &ScalarValue) -> Result<Option<bool>> {
+        let (found, contains_null) = if let Some(in_set) = &self.set {
+            (in_set.get_set().contains(value), in_set.contains_null)
+        } else if let Some(lazy_set) = &self.lazy_set {
+            let in_set = lazy_set.get_or_try_init(|| {
+                evaluate_static_filter_values(&self.list)
+                    .map(|values| InSet::new(values.into_iter().collect()))
+            })?;
+            (in_set.get_set().contains(value), in_set.contains_null)
+        } else if let Some(dynamic_set) = &self.dynamic_set {
+            let in_set = dynamic_set.set.read();
+            (in_set.get_set().contains(value), in_set.contains_null)
+        } else {
+            let list_values = self
+                .static_list_values(&value.get_datatype())?
+                .ok_or_else(|| {
+                    DataFusionError::NotImplemented(format!(
+                        "Probing {} with a single value requires a constant list",
+                        self
+                    ))
+                })?;
+            let contains_null = list_values.iter().any(|v| v.is_null());
+            let list_values = list_values
+                .iter()
+                .cloned()
+                .map(ColumnarValue::Scalar)
+                .collect();
+            // share the list path's comparison semantics
+            let result = self.evaluate_list(value.to_array(), list_values)?;
+            let result = result.into_array(1);
+            let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+            // a NULL result is a miss
+            let found = result.is_valid(0) && result.value(0) != self.negated;
+            (found, contains_null)
+        };
+        Ok(match (value.is_null(), self.null_handling) {
+            (true, NullHandling::SqlStandard) => None,
+            (true, NullHandling::NullEqualsNull) => Some(contains_null != self.negated),
+            (true, NullHandling::NullEqualsNothing) => Some(self.negated),
+            (false, _) if found => Some(!self.negated),
+            (false, NullHandling::SqlStandard) if contains_null => None,
+            (false, _) => Some(self.negated),
+        })
+    }
+
+    /// Evaluate this predicate with its three-valued result encoded as `0`
+    /// for false, `1` for true and `2` for unknown, for consumers that do
+    /// not read null bitmaps. The result never has NULL slots.
+    pub fn evaluate_tristate(&self, batch: &RecordBatch) -> Result<UInt8Array> {
+        let result = self.evaluate(batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        Ok(result
+            .iter()
+            .map(|r| match r {
+                Some(false) => 0,
+                Some(true) => 1,
+                None => 2,
+            })
+            .collect::<Vec<u8>>()
+            .into())
+    }
+
+    /// Evaluate this predicate to a boolean array without a validity bitmap,
+    /// for operators that require a non-nullable result. Returns an error if
+    /// any row evaluates to NULL, e.g. for a NULL input or list entry.
+    pub fn evaluate_non_null(&self, batch: &RecordBatch) -> Result<BooleanArray> {
+        let result = self.evaluate(batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        if result.null_count() > 0 {
+            return Err(DataFusionError::Execution(format!(
+                "{} evaluated to NULL for {} rows",
+                self,
+                result.null_count()
+            )));
+        }
+        Ok(result.iter().flatten().collect::<Vec<_>>().into())
+    }
+
+    /// Evaluate this predicate over `array`, the values of the column it
+    /// tests, without the rest of the batch, e.g. for a reader materializing
+    /// one column at a time. The list must be constant, and `array` must
+    /// have the type of the list values.
+    pub fn evaluate_over_array(&self, array: ArrayRef) -> Result<BooleanArray> {
+        let column = self
+            .expr
+            .as_any()
+            .downcast_ref::<expressions::Column>()
+            .ok_or_else(|| {
+                DataFusionError::Plan(format!(
+                    "{} can only be evaluated over an array if it tests a column",
+                    self
+                ))
+            })?;
+        let values = static_filter_values(&self.list).ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "{} can only be evaluated over an array with a constant list",
+                self
+            ))
+        })?;
+        let value_type = match array.data_type() {
+            DataType::Dictionary(_, value_type) => value_type.as_ref(),
+            data_type => data_type,
+        };
+        if let Some(v) = values
+            .iter()
+            .find(|v| !v.is_null() && &v.get_datatype() != value_type)
+        {
+            return Err(DataFusionError::Plan(format!(
+                "Cannot evaluate {} over an array of {:?}, the list has type {:?}",
+                self,
+                array.data_type(),
+                v.get_datatype()
+            )));
+        }
+
+        let schema = Schema::new(vec![Field::new(
+            column.name(),
+            array.data_type().clone(),
+            true,
+        )]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![array])?;
+        let expr = Self {
+            expr: Arc::new(expressions::Column::new(column.name(), 0)),
+            ..self.clone()
+        };
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        Ok(BooleanArray::from(result.data().clone()))
+    }
+
+    /// The distinct values of the list as a single nullable column named
+    /// `field_name`, with one NULL row if the list has a NULL entry, e.g. to
+    /// plan a small IN as the build side of a semi join. The inverse of
+    /// [`Self::from_build_side`] with an [`InSet`] of the column's values.
+    /// Requires the list to be constant.
+    pub fn to_build_batch(&self, field_name: &str) -> Result<RecordBatch> {
+        let values = static_filter_values(&self.list).ok_or_else(|| {
+            DataFusionError::NotImplemented(format!(
+                "Building a batch from {} requires a constant list",
+                self
+            ))
+        })?;
+        let mut seen = HashSet::with_capacity(values.len());
+        let mut has_null = false;
+        let mut distinct = Vec::with_capacity(values.len());
+        for value in values {
+            if value.is_null() {
+                has_null = true;
+            } else if seen.insert(value.clone()) {
+                distinct.push(value);
+            }
+        }
+        let array = match distinct.first() {
+            Some(first) => {
+                let null = ScalarValue::try_from(&first.get_datatype())?;
+                ScalarValue::iter_to_array(
+                    distinct.into_iter().chain(has_null.then(|| null)),
+                )?
+            }
+            None => new_null_array(&DataType::Null, has_null as usize),
+        };
+        let schema = Schema::new(vec![Field::new(
+            field_name,
+            array.data_type().clone(),
+            true,
+        )]);
+        Ok(RecordBatch::try_new(Arc::new(schema), vec![array])?)
+    }
+
+    /// Evaluate the position in [`Self::list`] of the entry each row's value
+    /// equals, NULL if none, whether or not this is negated. When several
+    /// entries are equal, e.g. literals made equal by [`Self::coerce_literals`],
+    /// the first is reported. Requires the list to be constant.
+    pub fn evaluate_match_index(&self, batch: &RecordBatch) -> Result<Int32Array> {
+        let values = static_filter_values(&self.list).ok_or_else(|| {
+            DataFusionError::NotImplemented(format!(
+                "Evaluating the match index of {} requires a constant list",
+                self
+            ))
+        })?;
+        let mut positions = HashMap::with_capacity(values.len());
+        for (i, value) in values.into_iter().enumerate() {
+            if !value.is_null() {
+                positions.entry(value).or_insert(i as i32);
+            }
+        }
+        let array = self.expr.evaluate(batch)?.into_array(batch.num_rows());
+        (0..array.len())
+            .map(|i| match array.is_valid(i) {
+                true => Ok(positions
+                    .get(&ScalarValue::try_from_array(&array, i)?)
+                    .copied()),
+                false => Ok(None),
+            })
+            .collect()
+    }
+
+    /// Evaluate a `NOT IN` over strings used as a denylist, along with the
+    /// position in [`Self::list`] of the entry each rejected row hit, NULL for
+    /// the rows not rejected, as from [`Self::evaluate_match_index`]. Returns
+    /// an error for a positive IN or an input that is not `Utf8` or
+    /// `LargeUtf8`.
+    pub fn evaluate_denylist(
+        &self,
+        batch: &RecordBatch,
+    ) -> Result<(BooleanArray, Int32Array)> {
+        let data_type = self.expr.data_type(&batch.schema())?;
+        if !self.negated || !matches!(data_type, DataType::Utf8 | DataType::LargeUtf8) {
+            return Err(DataFusionError::Plan(format!(
+                "{} is not a NOT IN over strings of type {:?}",
+                self, data_type
+            )));
+        }
+        let result = self.evaluate(batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        let index = self.evaluate_match_index(batch)?;
+        Ok((BooleanArray::from(result.data().clone()), index))
+    }
+
+    /// Capture this predicate as a closure returning the three-valued result
+    /// of [`Self::contains_scalar`] for a single value, `None` for NULL, so that
+    /// row oriented engines can share its semantics. Returns an error if the
+    /// list is not constant. Values the list cannot be compared with give NULL.
+    pub fn as_scalar_predicate(&self) -> Result<ScalarPredicate> {
+        if self.set.is_none()
+            && self.lazy_set.is_none()
+            && self.dynamic_set.is_none()
+            && !check_all_static_filter_expr(&self.list)
+        {
+            return Err(DataFusionError::NotImplemented(format!(
+                "A scalar predicate for {} requires a constant list",
+                self
+            )));
+        }
+        let expr = self.clone();
+        Ok(Box::new(move |value| {
+            expr.contains_scalar(value).ok().flatten()
+        }))
+    }
+
+    /// Describe this predicate for Parquet pruning. Returns `None` unless this
+    /// is a non-negated IN of a column against a list of static values of a
+    /// single type that Parquet statistics can be compared with.
+    pub fn as_parquet_filter(&self) -> Option<ParquetInFilter> {
+        if self.negated {
+            return None;
+        }
+        let column = self
+            .expr
+            .as_any()
+            .downcast_ref::<expressions::Column>()?
+            .clone();
+        let mut values = static_filter_values(&self.list)?
+            .into_iter()
+            // NULLs never match, so there is nothing to look for
+            .filter(|v| !v.is_null())
+            .collect::<Vec<_>>();
+        if let Some(first) = values.first() {
+            let data_type = first.get_datatype();
+            let supported = matches!(
+                data_type,
+                DataType::Boolean
+                    | DataType::Int8
+                    | DataType::Int16
+                    | DataType::Int32
+                    | DataType::Int64
+                    | DataType::UInt8
+                    | DataType::UInt16
+                    | DataType::UInt32
+                    | DataType::UInt64
+                    | DataType::Float32
+                    | DataType::Float64
+                    | DataType::Utf8
+                    | DataType::LargeUtf8
+                    | DataType::Binary
+                    | DataType::LargeBinary
+                    | DataType::Date32
+                    | DataType::Date64
+            );
+            if !supported || values.iter().any(|v| v.get_datatype() != data_type) {
+                return None;
+            }
+        }
+        // values of a single supported type are totally ordered
+        if self.is_list_sorted() != Some(true) {
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        }
+        values.dedup();
+        Some(ParquetInFilter { column, values })
+    }
+
+    /// Compare `array` row by row against a list with array entries, such as
+    /// other columns, as `expr = e1 OR expr = e2 ...`: entries are cast to the
+    /// type of `expr` and compared with `=`, and a NULL comparison makes the
+    /// row NULL unless another entry is equal
+    fn compare_row_wise(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+    ) -> Result<ColumnarValue> {
+        // keys of different dictionaries cannot be compared, so entries that
+        // are dictionaries are decoded by the cast below
+        let array = match array.data_type() {
+            DataType::Dictionary(_, value_type) => {
+                kernels::cast::cast(&array, value_type)?
+            }
+            _ => array,
+        };
+        let mut result = BooleanArray::from(vec![false; array.len()]);
+        for value in list_values {
+            let value = match value {
+                ColumnarValue::Array(value) if value.len() != array.len() => {
+                    return Err(DataFusionError::Internal(format!(
+                        "InList entry has {} rows, expected {}",
+                        value.len(),
+                        array.len()
+                    )))
+                }
+                value => value.into_array(array.len()),
+            };
+            let value = match value.data_type() == array.data_type() {
+                true => value,
+                false => kernels::cast::cast(&value, array.data_type())?,
+            };
+            let eq = kernels::comparison::eq_dyn(array.as_ref(), value.as_ref())?;
+            result = kernels::boolean::or_kleene(&result, &eq)?;
+        }
+        if self.negated {
+            result = kernels::boolean::not(&result)?;
+        }
+        Ok(ColumnarValue::Array(Arc::new(result)))
+    }
+
+    /// Compare float types using the user provided equality function
+    fn compare_float_with(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+        float_eq: &FloatEq,
+    ) -> Result<ColumnarValue> {
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) => match s {
+                    ScalarValue::Float32(Some(v)) => Ok(Some(*v as f64)),
+                    ScalarValue::Float64(Some(v)) => Ok(Some(*v)),
+                    ScalarValue::Float32(None) => Ok(None),
+                    ScalarValue::Float64(None) => Ok(None),
+                    ScalarValue::Utf8(None) => Ok(None),
+                    datatype => Err(DataFusionError::Internal(format!(
+                        "Unexpected type {} for InList",
+                        datatype
+                    ))),
+                },
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<f64>>();
+
+        let contains = |x: Option<f64>| {
+            x.and_then(|v| {
+                if values.iter().any(|l| float_eq.eq(v, *l)) {
+                    Some(!self.negated)
+                } else if contains_null {
+                    None
+                } else {
+                    Some(self.negated)
+                }
+            })
+        };
+        let result = match array.data_type() {
+            DataType::Float32 => array
+                .as_any()
+                .downcast_ref::<Float32Array>()
+                .unwrap()
+                .iter()
+                .map(|x| contains(x.map(|v| v as f64)))
+                .collect::<BooleanArray>(),
+            _ => array
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .unwrap()
+                .iter()
+                .map(contains)
+                .collect::<BooleanArray>(),
+        };
+        Ok(ColumnarValue::Array(Arc::new(result)))
+    }
+
+    /// Evaluate `array` against an empty list, which no value is in: `false`
+    /// for `IN ()` and `true` for `NOT IN ()`, or NULL for NULL values
+    fn evaluate_empty_list(&self, array: &ArrayRef) -> Result<ColumnarValue> {
+        if let DataType::Dictionary(_, _) = array.data_type() {
+            return evaluate_dictionary(array, |values| {
+                self.evaluate_empty_list(&values)
+            });
+        }
+        let len = array.len();
+        let values = MutableBuffer::new(bit_util::ceil(len, 8))
+            .with_bitset(bit_util::ceil(len, 8), self.negated);
+        let nulls = array
+            .data()
+            .null_buffer()
+            .map(|nulls| nulls.bit_slice(array.offset(), len));
+        let data = ArrayData::try_new(
+            DataType::Boolean,
+            len,
+            nulls,
+            0,
+            vec![values.into()],
+            vec![],
+        )?;
+        Ok(ColumnarValue::Array(Arc::new(BooleanArray::from(data))))
+    }
+
+    /// Evaluate `array` against the list folded to `range`, or return `None`
+    /// if it is not of the type of the list
+    fn evaluate_range(
+        &self,
+        array: &ArrayRef,
+        range: &IntegerRange,
+    ) -> Result<Option<ColumnarValue>> {
+        macro_rules! between {
+            ($ARRAY_TYPE:ident, $low:expr, $high:expr) => {{
+                let array = array.as_any().downcast_ref::<$ARRAY_TYPE>().unwrap();
+                let result: Result<BooleanArray> =
+                    compare_op_scalar!(array, ($low, $high), |x, (low, high)| low <= x
+                        && x <= high);
+                result?
+            }};
+        }
+        let result = match (array.data_type(), &range.low, &range.high) {
+            (DataType::Int8, ScalarValue::Int8(Some(l)), ScalarValue::Int8(Some(h))) => {
+                between!(Int8Array, *l, *h)
+            }
+            (
+                DataType::Int16,
+                ScalarValue::Int16(Some(l)),
+                ScalarValue::Int16(Some(h)),
+            ) => {
+                between!(Int16Array, *l, *h)
+            }
+            (
+                DataType::Int32,
+                ScalarValue::Int32(Some(l)),
+                ScalarValue::Int32(Some(h)),
+            ) => {
+                between!(Int32Array, *l, *h)
+            }
+            (
+                DataType::Int64,
+                ScalarValue::Int64(Some(l)),
+                ScalarValue::Int64(Some(h)),
+            ) => {
+                between!(Int64Array, *l, *h)
+            }
+            (
+                DataType::UInt8,
+                ScalarValue::UInt8(Some(l)),
+                ScalarValue::UInt8(Some(h)),
+            ) => {
+                between!(UInt8Array, *l, *h)
+            }
+            (
+                DataType::UInt16,
+                ScalarValue::UInt16(Some(l)),
+                ScalarValue::UInt16(Some(h)),
+            ) => between!(UInt16Array, *l, *h),
+            (
+                DataType::UInt32,
+                ScalarValue::UInt32(Some(l)),
+                ScalarValue::UInt32(Some(h)),
+            ) => between!(UInt32Array, *l, *h),
+            (
+                DataType::UInt64,
+                ScalarValue::UInt64(Some(l)),
+                ScalarValue::UInt64(Some(h)),
+            ) => between!(UInt64Array, *l, *h),
+            _ => return Ok(None),
+        };
+        let result = ColumnarValue::Array(Arc::new(result));
+        Ok(Some(match range.contains_null {
+            true => self.null_misses(result),
+            false => result,
+        }))
+    }
+
+    /// Compare a Float64 array against the list sorted in total order,
+    /// binary searching it for each value. NaN equals NaN and -0.0 equals 0.0,
+    /// as for `ScalarValue`
+    fn compare_sorted_f64(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+    ) -> Result<ColumnarValue> {
+        let array = array.as_any().downcast_ref::<Float64Array>().unwrap();
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let mut values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) => match s {
+                    ScalarValue::Float64(Some(v)) => Ok(Some(total_order_key(*v))),
+                    ScalarValue::Float64(None) => Ok(None),
+                    ScalarValue::Utf8(None) => Ok(None),
+                    datatype => Err(DataFusionError::Internal(format!(
+                        "Unexpected type {} for InList",
+                        datatype
+                    ))),
+                },
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<i64>>();
+        // an ascending list has ascending keys, as it has no NaN
+        if self.is_list_sorted() != Some(true) {
+            values.sort_unstable();
+        }
+
+        let contains = |x: f64, v: &[i64]| v.binary_search(&total_order_key(x)).is_ok();
+        let result: Result<BooleanArray> = if contains_null {
+            Ok(array
+                .iter()
+                .map(|x| match x.map(|x| contains(x, &values)) {
+                    Some(true) => Some(!self.negated),
+                    _ => None,
+                })
+                .collect())
+        } else if self.negated {
+            compare_op_scalar!(array, &values, |x, v| !contains(x, v))
+        } else {
+            compare_op_scalar!(array, &values, contains)
+        };
+        Ok(ColumnarValue::Array(Arc::new(result?)))
+    }
+
+    /// Compare timestamps against a list of Timestamp and Date literals,
+    /// converted to instants in the unit of the column
+    fn compare_timestamp(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+        unit: &TimeUnit,
+    ) -> Result<ColumnarValue> {
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) if s.is_null() => Ok(None),
+                ColumnarValue::Scalar(s) => timestamp_literal_value(s, unit),
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<i64>>();
+
+        // timestamps are stored as i64, compare them as such
+        let array = kernels::cast::cast(&array, &DataType::Int64)?;
+        let array = array.as_any().downcast_ref::<Int64Array>().unwrap();
+        contains_primitive_values::<Int64Type>(
+            array,
+            &values,
+            contains_null,
+            self.negated,
+        )
+    }
+
+    /// Compare times of day in `unit`, see [`time_literal_value`]
+    fn compare_time(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+        unit: &TimeUnit,
+    ) -> Result<ColumnarValue> {
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) if s.is_null() => Ok(None),
+                ColumnarValue::Scalar(s) => time_literal_value(s, unit),
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<i64>>();
+
+        // times are stored as i32 or i64, compare them as i64
+        let array = match array.data_type() {
+            DataType::Time32(_) => kernels::cast::cast(&array, &DataType::Int32)?,
+            _ => array,
+        };
+        let array = kernels::cast::cast(&array, &DataType::Int64)?;
+        let array = array.as_any().downcast_ref::<Int64Array>().unwrap();
+        contains_primitive_values::<Int64Type>(
+            array,
+            &values,
+            contains_null,
+            self.negated,
+        )
+    }
+
+    /// Compare decimals as their unscaled integers, after rescaling the list
+    /// values to the scale of the column
+    fn compare_decimal(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+        precision: usize,
+        scale: usize,
+    ) -> Result<ColumnarValue> {
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) if s.is_null() => Ok(None),
+                ColumnarValue::Scalar(ScalarValue::Decimal128(Some(v), _, s)) => {
+                    rescale_decimal(*v, *s, scale)
+                }
+                ColumnarValue::Scalar(s) => {
+                    Err(DataFusionError::NotImplemented(format!(
+                        "InList does not support value {:?} for a column of type {:?}",
+                        s,
+                        DataType::Decimal(precision, scale)
+                    )))
+                }
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<i128>>();
+
+        let array = array.as_any().downcast_ref::<DecimalArray>().unwrap();
+        Ok(ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| {
+                    x.and_then(|v| match values.contains(&v) {
+                        true => Some(!self.negated),
+                        false if contains_null => None,
+                        false => Some(self.negated),
+                    })
+                })
+                .collect::<BooleanArray>(),
+        )))
+    }
+
+    /// Compare utf8 types after folding both sides, see [`Self::fold_utf8`]
+    fn compare_utf8_folded<T: OffsetSizeTrait>(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+    ) -> Result<ColumnarValue> {
+        let array = array
+            .as_any()
+            .downcast_ref::<GenericStringArray<T>>()
+            .unwrap();
+
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) => match s {
+                    ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => {
+                        Ok(Some(self.fold_utf8(v)))
+                    }
+                    s if s.is_null() => Ok(None),
+                    s => Err(DataFusionError::NotImplemented(format!(
+                        "InList does not support value {:?} for a string column",
+                        s
+                    ))),
+                },
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<HashSet<_>>();
+
+        Ok(ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| {
+                    x.and_then(|v| match values.contains(&self.fold_utf8(v)) {
+                        true => Some(!self.negated),
+                        false if contains_null => None,
+                        false => Some(self.negated),
+                    })
+                })
+                .collect::<BooleanArray>(),
+        )))
+    }
+
+    /// Compare for specific utf8 types
+    #[allow(clippy::unnecessary_wraps)]
+    fn compare_utf8<T: OffsetSizeTrait>(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+        negated: bool,
+    ) -> Result<ColumnarValue> {
+        let array = array
+            .as_any()
+            .downcast_ref::<GenericStringArray<T>>()
+            .unwrap();
+
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) => match s {
+                    ScalarValue::Utf8(Some(v)) => Ok(Some(v.as_str())),
+                    ScalarValue::Utf8(None) => Ok(None),
+                    ScalarValue::LargeUtf8(Some(v)) => Ok(Some(v.as_str())),
+                    ScalarValue::LargeUtf8(None) => Ok(None),
+                    datatype => Err(DataFusionError::Internal(format!(
+                        "Unexpected type {} for InList",
+                        datatype
+                    ))),
+                },
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<&str>>();
+
+        if negated {
+            if contains_null {
+                Ok(ColumnarValue::Array(Arc::new(
+                    array
+                        .iter()
+                        .map(|x| match x.map(|v| !values.contains(&v)) {
+                            Some(true) => None,
+                            x => x,
+                        })
+                        .collect::<BooleanArray>(),
+                )))
+            } else {
+                Ok(ColumnarValue::Array(Arc::new(not_in_list_utf8(
+                    array, &values,
+                )?)))
+            }
+        } else if contains_null {
+            Ok(ColumnarValue::Array(Arc::new(
+                array
+                    .iter()
+                    .map(|x| match x.map(|v| values.contains(&v)) {
+                        Some(false) => None,
+                        x => x,
+                    })
+                    .collect::<BooleanArray>(),
+            )))
+        } else {
+            Ok(ColumnarValue::Array(Arc::new(in_list_utf8(
+                array, &values,
+            )?)))
+        }
+    }
+
+    /// Compare for specific binary types, byte by byte
+    fn compare_binary<T: OffsetSizeTrait>(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+    ) -> Result<ColumnarValue> {
+        let array = array
+            .as_any()
+            .downcast_ref::<GenericBinaryArray<T>>()
+            .unwrap();
+
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) => match s {
+                    ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)) => {
+                        Ok(Some(v.as_slice()))
+                    }
+                    s if s.is_null() => Ok(None),
+                    s => Err(DataFusionError::NotImplemented(format!(
+                        "InList does not support value {:?} for a binary column",
+                        s
+                    ))),
+                },
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<HashSet<&[u8]>>();
+
+        Ok(ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| {
+                    x.and_then(|v| match values.contains(v) {
+                        true => Some(!self.negated),
+                        false if contains_null => None,
+                        false => Some(self.negated),
+                    })
+                })
+                .collect::<BooleanArray>(),
+        )))
+    }
+
+    /// Compare a `FixedSizeBinary` array byte by byte against binary literals,
+    /// as there is no fixed size binary [`ScalarValue`]. Returns an error for a
+    /// literal of another length than `width`
+    fn compare_fixed_size_binary(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+        width: i32,
+    ) -> Result<ColumnarValue> {
+        let array = array
+            .as_any()
+            .downcast_ref::<FixedSizeBinaryArray>()
+            .unwrap();
+
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(s) => match s {
+                    ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v))
+                        if v.len() == width as usize =>
+                    {
+                        Ok(Some(v.as_slice()))
+                    }
+                    ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)) => {
+                        Err(DataFusionError::Plan(format!(
+                            "InList literal of {} bytes does not match FixedSizeBinary({})",
+                            v.len(),
+                            width
+                        )))
+                    }
+                    s if s.is_null() => Ok(None),
+                    s => Err(DataFusionError::NotImplemented(format!(
+                        "InList does not support value {:?} for a fixed size binary column",
+                        s
+                    ))),
+                },
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<HashSet<&[u8]>>();
+
+        // the array has no iterator, so values are read by index
+        Ok(ColumnarValue::Array(Arc::new(
+            (0..array.len())
+                .map(|i| {
+                    array.is_valid(i).then(|| array.value(i)).and_then(|v| {
+                        match values.contains(v) {
+                            true => Some(!self.negated),
+                            false if contains_null => None,
+                            false => Some(self.negated),
+                        }
+                    })
+                })
+                .collect::<BooleanArray>(),
+        )))
+    }
+
+    /// Compare each list of a `List` or `LargeList` array as a whole against
+    /// list literals. Lists compare element by element, like row values: a
+    /// NULL element compares as unknown, so a list equal to a literal but for
+    /// a NULL is not found, and is NULL rather than false if no literal
+    /// matches.
+    fn compare_list<T: OffsetSizeTrait>(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+    ) -> Result<ColumnarValue> {
+        let array = array
+            .as_any()
+            .downcast_ref::<GenericListArray<T>>()
+            .unwrap();
+        let element_type = array.value_type();
+
+        let contains_null = list_values
+            .iter()
+            .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+        let values = list_values
+            .iter()
+            .map(|expr| match expr {
+                ColumnarValue::Scalar(ScalarValue::List(Some(values), data_type))
+                    if **data_type == element_type =>
+                {
+                    Ok(Some(values))
+                }
+                ColumnarValue::Scalar(s) if s.is_null() => Ok(None),
+                ColumnarValue::Scalar(s) => Err(DataFusionError::Plan(format!(
+                    "InList value {:?} cannot be compared with lists of {:?}",
+                    s, element_type
+                ))),
+                ColumnarValue::Array(_) => Err(DataFusionError::NotImplemented(
+                    "InList does not yet support nested columns.".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        Ok(ColumnarValue::Array(Arc::new(
+            (0..array.len())
+                .map(|i| {
+                    if array.is_null(i) {
+                        return Ok(None);
+                    }
+                    let elements = array.value(i);
+                    let row = (0..elements.len())
+                        .map(|j| ScalarValue::try_from_array(&elements, j))
+                        .collect::<Result<Vec<_>>>()?;
+                    let mut unknown = contains_null;
+                    for value in &values {
+                        match compare_row_values(&row, value) {
+                            Some(true) => return Ok(Some(!self.negated)),
+                            Some(false) => {}
+                            None => unknown = true,
+                        }
+                    }
+                    Ok((!unknown).then(|| self.negated))
+                })
+                .collect::<Result<BooleanArray>>()?,
+        )))
+    }
+
+    /// Evaluate `array` against a prebuilt membership set
+    fn evaluate_set(&self, array: ArrayRef, in_set: &InSet) -> Result<ColumnarValue> {
+        let (set, native) = (&in_set.set, in_set.native.as_ref());
+        let result = match (array.data_type(), native) {
+            (_, Some(NativeSet::Primitive(values))) => {
+                values.evaluate(&array, self.negated)
+            }
+            (DataType::Utf8, Some(strings)) => {
+                strings.evaluate_strings::<i32>(&array, self.negated)
+            }
+            (DataType::LargeUtf8, Some(strings)) => {
+                strings.evaluate_strings::<i64>(&array, self.negated)
+            }
+            (DataType::Binary, Some(NativeSet::Binaries(values))) => {
+                Some(evaluate_binaries::<i32>(&array, values, self.negated))
+            }
+            (DataType::LargeBinary, Some(NativeSet::Binaries(values))) => {
+                Some(evaluate_binaries::<i64>(&array, values, self.negated))
+            }
+            _ => None,
+        };
+        if let Some(result) = result {
+            return Ok(result);
+        }
+        match (array.data_type(), native) {
+            (DataType::Int64, Some(NativeSet::Int64Bitset(bitset))) => {
+                let array = array.as_any().downcast_ref::<Int64Array>().unwrap();
+                Ok(ColumnarValue::Array(Arc::new(
+                    array
+                        .iter()
+                        .map(|x| x.map(|v| bitset.contains(v) != self.negated))
+                        .collect::<BooleanArray>(),
+                )))
+            }
+            (DataType::Int64, Some(NativeSet::Int64Hash(values))) => {
+                let array = array.as_any().downcast_ref::<Int64Array>().unwrap();
+                Ok(ColumnarValue::Array(Arc::new(
+                    array
+                        .iter()
+                        .map(|x| x.map(|v| values.contains(&v) != self.negated))
+                        .collect::<BooleanArray>(),
+                )))
+            }
+            #[cfg(feature = "roaring_set")]
+            (DataType::Int64, Some(NativeSet::Int64Roaring(codes))) => {
+                Ok(codes.evaluate::<Int64Type>(&array, self.negated))
+            }
+            #[cfg(feature = "roaring_set")]
+            (DataType::Int32, Some(NativeSet::Int32Roaring(codes))) => {
+                Ok(codes.evaluate::<Int32Type>(&array, self.negated))
+            }
+            (DataType::Int32, Some(NativeSet::Int32PerfectHash(table))) => {
+                let array = array.as_any().downcast_ref::<Int32Array>().unwrap();
+                Ok(ColumnarValue::Array(Arc::new(
+                    array
+                        .iter()
+                        .map(|x| x.map(|v| table.contains(v) != self.negated))
+                        .collect::<BooleanArray>(),
+                )))
+            }
+            (DataType::Decimal(_, s), native) => {
+                let rescaled;
+                let values = match native {
+                    // equal unscaled integers of equal scale are equal values
+                    Some(NativeSet::Decimal128 { scale, values, .. }) if scale == s => {
+                        values
+                    }
+                    _ => {
+                        rescaled = set
+                            .iter()
+                            .map(|v| match v {
+                                ScalarValue::Decimal128(Some(v), _, from) => {
+                                    rescale_decimal(*v, *from, *s)
+                                }
+                                ScalarValue::Decimal128(None, _, _) => Ok(None),
+                                other => Err(DataFusionError::NotImplemented(format!(
+                                    "InSet does not support value {:?} for a column of type {:?}",
+                                    other,
+                                    array.data_type()
+                                ))),
+                            })
+                            .collect::<Result<Vec<_>>>()?
+                            .into_iter()
+                            .flatten()
+                            .collect::<NativeHashSet<i128>>();
+                        &rescaled
+                    }
+                };
+                let array = array.as_any().downcast_ref::<DecimalArray>().unwrap();
+                Ok(ColumnarValue::Array(Arc::new(
+                    array
+                        .iter()
+                        .map(|x| x.map(|v| values.contains(&v) != self.negated))
+                        .collect::<BooleanArray>(),
+                )))
+            }
+            // as for lists, compare with f64 values in f64, probing the set
+            // widened once to f64
+            (DataType::Float32, _)
+                if set.iter().any(|v| matches!(v, ScalarValue::Float64(_))) =>
+            {
+                let array = kernels::cast::cast(&array, &DataType::Float64)?;
+                self.evaluate_set(array, &*in_set.coerced_to(&DataType::Float64)?)
+            }
+            (DataType::Dictionary(_, _), _) => {
+                evaluate_dictionary(&array, |values| self.evaluate_set(values, in_set))
+            }
+            _ => self.evaluate_scalar_set(array, set),
+        }
+    }
+
+    /// Evaluate `array` against the evaluated values of the list
+    fn evaluate_list(
+        &self,
+        array: ArrayRef,
+        list_values: Vec<ColumnarValue>,
+    ) -> Result<ColumnarValue> {
+        match array.data_type().clone() {
+            // e.g. other columns
+            _ if list_values
+                .iter()
+                .any(|v| matches!(v, ColumnarValue::Array(_))) =>
+            {
+                self.compare_row_wise(array, list_values)
+            }
+            DataType::Dictionary(_, _)
+                if list_values
+                    .iter()
+                    .all(|v| matches!(v, ColumnarValue::Scalar(_))) =>
+            {
+                evaluate_dictionary(&array, |values| {
+                    self.evaluate_list(values, list_values)
+                })
+            }
+            // e.g. Int64 scalars for an Int32 column from a list that is not
+            // constant, as constant lists are coerced once
+            data_type
+                if list_values.iter().any(|v| {
+                    matches!(v, ColumnarValue::Scalar(s) if needs_coercion(s, &data_type))
+                }) =>
+            {
+                let mut scalars = Vec::with_capacity(list_values.len());
+                for value in list_values {
+                    match value {
+                        ColumnarValue::Scalar(s) => scalars.push(s),
+                        ColumnarValue::Array(_) => {
+                            return Err(DataFusionError::Internal(
+                                "Unexpected array entry for InList".to_string(),
+                            ))
+                        }
+                    }
+                }
+                let list_values = coerce_numeric_values(scalars, &data_type)?
+                    .into_iter()
+                    .map(ColumnarValue::Scalar)
+                    .collect();
+                self.evaluate_list(array, list_values)
+            }
+            DataType::Float32 | DataType::Float64 if self.float_eq.is_some() => {
+                let float_eq = self.float_eq.as_ref().unwrap();
+                self.compare_float_with(array, list_values, float_eq)
+            }
+            DataType::Float64
+                if list_values.len() >= SORTED_LIST_MIN_LEN
+                    && list_values.iter().all(|v| {
+                        matches!(
+                            v,
+                            ColumnarValue::Scalar(
+                                ScalarValue::Float64(_) | ScalarValue::Utf8(None)
+                            )
+                        )
+                    }) =>
+            {
+                self.compare_sorted_f64(array, list_values)
+            }
+            // NaN equals itself, like for `ScalarValue` and in a set
+            DataType::Float32 | DataType::Float64
+                if list_values.iter().any(|v| match v {
+                    ColumnarValue::Scalar(ScalarValue::Float32(Some(v))) => v.is_nan(),
+                    ColumnarValue::Scalar(ScalarValue::Float64(Some(v))) => v.is_nan(),
+                    _ => false,
+                }) =>
+            {
+                let nan_eq = FloatEq::new(|l, r| l == r || (l.is_nan() && r.is_nan()));
+                self.compare_float_with(array, list_values, &nan_eq)
+            }
+            // compare in f64 rather than round the literals, which could make
+            // them equal to values they differ from
+            DataType::Float32
+                if list_values.iter().any(|v| {
+                    matches!(v, ColumnarValue::Scalar(ScalarValue::Float64(_)))
+                }) =>
+            {
+                let array = kernels::cast::cast(&array, &DataType::Float64)?;
+                let list_values = list_values
+                    .into_iter()
+                    .map(|v| match v {
+                        ColumnarValue::Scalar(ScalarValue::Float32(v)) => {
+                            ColumnarValue::Scalar(ScalarValue::Float64(v.map(f64::from)))
+                        }
+                        v => v,
+                    })
+                    .collect();
+                self.evaluate_list(array, list_values)
+            }
+            DataType::Float32 => {
+                make_contains_primitive!(
+                    array,
+                    list_values,
+                    self.negated,
+                    Float32,
+                    Float32Array
+                )
+            }
+            DataType::Float64 => {
+                make_contains_primitive!(
+                    array,
+                    list_values,
+                    self.negated,
+                    Float64,
+                    Float64Array
+                )
+            }
+            DataType::Int16 => {
+                make_contains_ordered!(
+                    array,
+                    list_values,
+                    self.negated,
+                    Int16,
+                    Int16Array
+                )
+            }
+            DataType::Int32 => {
+                make_contains_ordered!(
+                    array,
+                    list_values,
+                    self.negated,
+                    Int32,
+                    Int32Array
+                )
+            }
+            DataType::Int64 => {
+                make_contains_ordered!(
+                    array,
+                    list_values,
+                    self.negated,
+                    Int64,
+                    Int64Array
+                )
+            }
+            DataType::Int8 => {
+                make_contains_ordered!(
+                    array,
+                    list_values,
+                    self.negated,
+                    Int8,
+                    Int8Array
+                )
+            }
+            DataType::UInt16 => {
+                make_contains_ordered!(
+                    array,
+                    list_values,
+                    self.negated,
+                    UInt16,
+                    UInt16Array
+                )
+            }
+            DataType::UInt32 => {
+                make_contains_ordered!(
+                    array,
+                    list_values,
+                    self.negated,
+                    UInt32,
+                    UInt32Array
+                )
+            }
+            DataType::UInt64 => {
+                make_contains_ordered!(
+                    array,
+                    list_values,
+                    self.negated,
+                    UInt64,
+                    UInt64Array
+                )
+            }
+            DataType::UInt8 => {
+                make_contains_ordered!(
+                    array,
+                    list_values,
+                    self.negated,
+                    UInt8,
+                    UInt8Array
+                )
+            }
+            DataType::Boolean => {
+                make_contains!(array, list_values, self.negated, Boolean, BooleanArray)
+            }
+            DataType::Timestamp(unit, _) => {
+                self.compare_timestamp(array, list_values, &unit)
+            }
+            DataType::Time32(unit) | DataType::Time64(unit) => {
+                self.compare_time(array, list_values, &unit)
+            }
+            DataType::Decimal(precision, scale) => {
+                self.compare_decimal(array, list_values, precision, scale)
+            }
+            DataType::Utf8 if self.folds_utf8() => {
+                self.compare_utf8_folded::<i32>(array, list_values)
+            }
+            DataType::LargeUtf8 if self.folds_utf8() => {
+                self.compare_utf8_folded::<i64>(array, list_values)
+            }
+            DataType::Utf8 => self.compare_utf8::<i32>(array, list_values, self.negated),
+            DataType::LargeUtf8 => {
+                self.compare_utf8::<i64>(array, list_values, self.negated)
+            }
+            DataType::Interval(IntervalUnit::YearMonth) => {
+                make_contains_primitive!(
+                    array,
+                    list_values,
+                    self.negated,
+                    IntervalYearMonth,
+                    IntervalYearMonthArray
+                )
+            }
+            DataType::Interval(IntervalUnit::DayTime) => {
+                make_contains_primitive!(
+                    array,
+                    list_values,
+                    self.negated,
+                    IntervalDayTime,
+                    IntervalDayTimeArray
+                )
+            }
+            DataType::Interval(IntervalUnit::MonthDayNano) => {
+                make_contains_primitive!(
+                    array,
+                    list_values,
+                    self.negated,
+                    IntervalMonthDayNano,
+                    IntervalMonthDayNanoArray
+                )
+            }
+            DataType::Binary => self.compare_binary::<i32>(array, list_values),
+            DataType::LargeBinary => self.compare_binary::<i64>(array, list_values),
+            DataType::FixedSizeBinary(width) => {
+                self.compare_fixed_size_binary(array, list_values, width)
+            }
+            DataType::List(_) => self.compare_list::<i32>(array, list_values),
+            DataType::LargeList(_) => self.compare_list::<i64>(array, list_values),
+            DataType::Null => {
+                let null_array = new_null_array(&DataType::Boolean, array.len());
+                Ok(ColumnarValue::Array(Arc::new(null_array)))
+            }
+            datatype => Result::Err(DataFusionError::NotImplemented(format!(
+                "InList does not support datatype {:?}.",
+                datatype
+            ))),
+        }
+    }
+
+    /// Evaluate whether each value of `array` is an element of the list in the
+    /// same row of `lists`. Like for a list of literals the result is NULL if
+    /// the value is not found and the list has NULL elements, and false for an
+    /// empty list.
+    fn evaluate_array_contains<T: OffsetSizeTrait>(
+        &self,
+        array: ArrayRef,
+        lists: &ArrayRef,
+    ) -> Result<ColumnarValue> {
+        let lists = lists
+            .as_any()
+            .downcast_ref::<GenericListArray<T>>()
+            .unwrap();
+        let element_type = lists.value_type();
+        if array.data_type() != &element_type {
+            return Err(DataFusionError::Plan(format!(
+                "Cannot search lists of {:?} for a value of type {:?}",
+                element_type,
+                array.data_type()
+            )));
+        }
+
+        let offsets = lists
+            .value_offsets()
+            .iter()
+            .map(|o| o.to_usize().unwrap())
+            .collect::<Vec<_>>();
+        let first = offsets[0];
+        let elements = lists.values().slice(first, offsets[lists.len()] - first);
+        // the value of each row repeated for each element of its list
+        let indices = offsets
+            .windows(2)
+            .enumerate()
+            .flat_map(|(row, w)| std::iter::repeat(row as u32).take(w[1] - w[0]))
+            .collect::<UInt32Array>();
+        let probes = kernels::take::take(array.as_ref(), &indices, None)?;
+        let eq = kernels::comparison::eq_dyn(probes.as_ref(), elements.as_ref())?;
+
+        Ok(ColumnarValue::Array(Arc::new(
+            (0..lists.len())
+                .map(|row| {
+                    if array.is_null(row) || lists.is_null(row) {
+                        return None;
+                    }
+                    let mut contains_null = false;
+                    for i in offsets[row] - first..offsets[row + 1] - first {
+                        if eq.is_null(i) {
+                            contains_null = true;
+                        } else if eq.value(i) {
+                            return Some(!self.negated);
+                        }
+                    }
+                    (!contains_null).then(|| self.negated)
+                })
+                .collect::<BooleanArray>(),
+        )))
+    }
+
+    /// Evaluate `array` against `in_set`, NULL for values not in it if the
+    /// list it was built from had NULL entries. Probe caches are dropped when
+    /// `generation` changes, as a [`DynamicInSet`] does with each update.
+    fn evaluate_in_set(
+        &self,
+        array: ArrayRef,
+        in_set: &InSet,
+        generation: u64,
+    ) -> Result<ColumnarValue> {
+        let value_type = match array.data_type() {
+            DataType::Dictionary(_, value_type) => value_type.as_ref(),
+            data_type => data_type,
+        };
+        let in_set = in_set.coerced_to(value_type)?;
+        let cached = self.probe_cache.as_ref().and_then(|caches| {
+            caches.evaluate(&array, generation, self.negated, |v| in_set.contains_str(v))
+        });
+        let result = match (cached, &in_set.bloom, array.data_type()) {
+            (Some(result), _, _) => result,
+            (None, Some(bloom), DataType::Utf8) => {
+                in_set.evaluate_bloom::<i32>(&array, bloom, self.negated)
+            }
+            (None, Some(bloom), DataType::LargeUtf8) => {
+                in_set.evaluate_bloom::<i64>(&array, bloom, self.negated)
+            }
+            _ => self.evaluate_set(array, &in_set)?,
+        };
+        if !in_set.contains_null {
+            return Ok(result);
+        }
+        Ok(self.null_misses(result))
+    }
+
+    /// Evaluate string `array` against the list compiled to `regex`
+    #[cfg(feature = "regex_expressions")]
+    fn evaluate_regex<T: OffsetSizeTrait>(
+        &self,
+        array: &ArrayRef,
+        regex: &regex::Regex,
+    ) -> ColumnarValue {
+        let array = array
+            .as_any()
+            .downcast_ref::<GenericStringArray<T>>()
+            .unwrap();
+        ColumnarValue::Array(Arc::new(
+            array
+                .iter()
+                .map(|x| x.map(|v| regex.is_match(v) != self.negated))
+                .collect::<BooleanArray>(),
+        ))
+    }
+
+    /// Make the values not found in the list NULL, as when it has NULL entries
+    fn null_misses(&self, result: ColumnarValue) -> ColumnarValue {
+        let result = result.into_array(0);
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        ColumnarValue::Array(Arc::new(
+            result
+                .iter()
+                .map(|x| x.filter(|v| *v != self.negated))
+                .collect::<BooleanArray>(),
+        ))
+    }
+
+    /// Evaluate `array` against a set of [`ScalarValue`], for the types with
+    /// no [`NativeSet`]
+    fn evaluate_scalar_set<S: BuildHasher>(
+        &self,
+        array: ArrayRef,
+        set: &HashSet<ScalarValue, S>,
+    ) -> Result<ColumnarValue> {
+        match array.data_type() {
+            // the values of a set of these types are in its native set, so
+            // none is of the type of `array`
+            DataType::Boolean
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Binary
+            | DataType::LargeBinary => Ok(ColumnarValue::Array(Arc::new(
+                (0..array.len())
+                    .map(|i| array.is_valid(i).then(|| self.negated))
+                    .collect::<BooleanArray>(),
+            ))),
+            DataType::Timestamp(unit, _) => {
+                let values = set
+                    .iter()
+                    .filter(|v| !v.is_null())
+                    .map(|v| timestamp_literal_value(v, unit))
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .flatten()
+                    .collect::<HashSet<i64>>();
+                // timestamps are stored as i64, compare them as such
+                let array = kernels::cast::cast(&array, &DataType::Int64)?;
+                let array = array.as_any().downcast_ref::<Int64Array>().unwrap();
+                Ok(ColumnarValue::Array(Arc::new(
+                    array
+                        .iter()
+                        .map(|x| x.map(|v| values.contains(&v) != self.negated))
+                        .collect::<BooleanArray>(),
+                )))
+            }
+            DataType::Interval(IntervalUnit::YearMonth) => set_contains_interval!(
+                array,
+                set,
+                self.negated,
+                IntervalYearMonth,
+                IntervalYearMonthArray
+            ),
+            DataType::Interval(IntervalUnit::DayTime) => set_contains_interval!(
+                array,
+                set,
+                self.negated,
+                IntervalDayTime,
+                IntervalDayTimeArray
+            ),
+            DataType::Interval(IntervalUnit::MonthDayNano) => set_contains_interval!(
+                array,
+                set,
+                self.negated,
+                IntervalMonthDayNano,
+                IntervalMonthDayNanoArray
+            ),
+            datatype => Result::Err(DataFusionError::NotImplemented(format!(
+                "InSet does not support datatype {:?}.",
+                datatype
+            ))),
+        }
+    }
+}
+
+/// Expressions are equal if they test the same value against the same list
+/// with the same options. A set built from the list is a cache of it and
+/// not compared, unlike a set given without a list.
+impl PartialEq for InListExpr {
+    fn eq(&self, other: &Self) -> bool {
+        fn given_set(e: &InListExpr) -> Option<&HashSet<ScalarValue, RandomState>> {
+            e.list
+                .is_empty()
+                .then(|| e.set.as_ref().map(|s| &s.set))
+                .flatten()
+        }
+        physical_expr_eq(&self.expr, &other.expr)
+            && self.negated == other.negated
+            && self.list.len() == other.list.len()
+            && self
+                .list
+                .iter()
+                .zip(&other.list)
+                .all(|(l, r)| physical_expr_eq(l, r))
+            && given_set(self) == given_set(other)
+            && match (&self.dynamic_set, &other.dynamic_set) {
+                (Some(l), Some(r)) => Arc::ptr_eq(&l.set, &r.set),
+                (l, r) => l.is_none() && r.is_none(),
+            }
+            && self.sorted_merge.as_ref().map(|s| &s.values)
+                == other.sorted_merge.as_ref().map(|s| &s.values)
+            && match (&self.float_eq, &other.float_eq) {
+                (Some(l), Some(r)) => Arc::ptr_eq(&l.0, &r.0),
+                (l, r) => l.is_none() && r.is_none(),
+            }
+            && self.null_handling == other.null_handling
+            && self.trim_trailing_whitespace == other.trim_trailing_whitespace
+            && self.ignore_diacritics == other.ignore_diacritics
+            && self.date_trunc == other.date_trunc
+            && match (&self.null_indicator, &other.null_indicator) {
+                (Some(l), Some(r)) => physical_expr_eq(l, r),
+                (l, r) => l.is_none() && r.is_none(),
+            }
+    }
+}
+
+impl Eq for InListExpr {}
+
+impl Hash for InListExpr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fingerprint(state)
+    }
+}
+
+impl std::fmt::Display for InListExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.dynamic_set.is_some() {
+            let op = if self.negated { "NOT IN" } else { "IN" };
+            return write!(f, "{} {} (DYNAMIC SET)", self.expr, op);
+        }
+        let op = if self.negated { "NOT IN" } else { "IN" };
+        let set = if self.set.is_some() || self.lazy_set.is_some() {
+            " (SET)"
+        } else {
+            ""
+        };
+        let list = self
+            .list
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{} {}{} ({})", self.expr, op, set, list)
+    }
+}
+
+impl PhysicalExpr for InListExpr {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<Self>() == Some(self)
+    }
+
+    fn node_hash(&self, mut state: &mut dyn Hasher) {
+        self.fingerprint(&mut state)
+    }
+
+    fn data_type(&self, _input_schema: &Schema) -> Result<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
+        self.expr.nullable(input_schema)
+    }
+
+    /// The value expression, then every list entry, then the null indicator
+    /// if there is one
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        std::iter::once(self.expr.clone())
+            .chain(self.list.iter().cloned())
+            .chain(self.null_indicator.clone())
+            .collect()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn PhysicalExpr>> {
+        let expected = 1 + self.list.len() + self.null_indicator.iter().count();
+        if children.len() != expected {
+            return Err(DataFusionError::Internal(format!(
+                "InListExpr expects {} children, got {}",
+                expected,
+                children.len()
+            )));
+        }
+        let mut children = children.into_iter();
+        let value = children.next().unwrap();
+        let list = children.by_ref().take(self.list.len()).collect::<Vec<_>>();
+        let mut expr = self.with_list(list);
+        expr.expr = value;
+        expr.null_indicator = children.next();
+        // the new entries may all be literals, or no longer be
+        expr.check_literal_bytes()?;
+        Ok(Arc::new(expr))
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        // nothing to probe, so skip evaluating the value and list expressions
+        if batch.num_rows() == 0 {
+            return Ok(ColumnarValue::Array(new_empty_array(&DataType::Boolean)));
+        }
+
+        let result = self.evaluate_batch(batch)?;
+        match &self.null_indicator {
+            Some(indicator) => {
+                // any input value of a row marked NULL gives a NULL result
+                let indicator = indicator.evaluate(batch)?.into_array(batch.num_rows());
+                let indicator = indicator
+                    .as_any()
+                    .downcast_ref::<BooleanArray>()
+                    .ok_or_else(|| {
+                        DataFusionError::Plan(format!(
+                            "IN null indicator must be Boolean, not {:?}",
+                            indicator.data_type()
+                        ))
+                    })?;
+                let result = result.into_array(batch.num_rows());
+                let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+                Ok(ColumnarValue::Array(Arc::new(
+                    result
+                        .iter()
+                        .zip(indicator.iter())
+                        .map(|(r, is_null)| r.filter(|_| is_null == Some(false)))
+                        .collect::<BooleanArray>(),
+                )))
+            }
+            None => Ok(result),
+        }
+    }
+}
+
+impl InListExpr {
+    /// Evaluate this predicate, ignoring any null indicator
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        let (array, result, list_has_null) = self.evaluate_sql_standard(batch)?;
+        if self.matched.is_some() || self.matched_last.is_some() {
+            let hits = result.clone().into_array(array.len());
+            let hits = hits.as_any().downcast_ref::<BooleanArray>().unwrap();
+            let data_type = match array.data_type() {
+                DataType::Dictionary(_, value_type) => value_type.as_ref(),
+                data_type => data_type,
+            };
+            let mut keys = HashSet::new();
+            for i in 0..array.len() {
+                if array.is_valid(i) && hits.is_valid(i) && hits.value(i) != self.negated
+                {
+                    let value = ScalarValue::try_from_array(&array, i)?;
+                    keys.extend(self.comparison_key(&value, data_type));
+                }
+            }
+            if let Some(matched) = &self.matched {
+                let mut matched = matched.lock();
+                matched.data_type.get_or_insert_with(|| data_type.clone());
+                matched.keys.extend(keys.iter().cloned());
+            }
+            if let Some(matched_last) = &self.matched_last {
+                *matched_last.lock() = MatchedKeys {
+                    data_type: Some(data_type.clone()),
+                    keys,
+                };
+            }
+        }
+        if self.null_handling == NullHandling::SqlStandard {
+            return Ok(result);
+        }
+        // every representation computes the standard result, which is only
+        // adjusted here so that they all agree on the other modes
+        let result = result.into_array(array.len());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        let null_input = match self.null_handling {
+            NullHandling::NullEqualsNull => list_has_null != self.negated,
+            _ => self.negated,
+        };
+        Ok(ColumnarValue::Array(Arc::new(
+            result
+                .iter()
+                .enumerate()
+                .map(|(i, r)| match array.is_null(i) {
+                    true => Some(null_input),
+                    // a miss is never NULL
+                    false => Some(r.unwrap_or(self.negated)),
+                })
+                .collect::<BooleanArray>(),
+        )))
+    }
+
+    /// Evaluate this predicate with SQL standard NULL semantics, returning the
+    /// input values and whether the list has a NULL entry with the result
+    fn evaluate_sql_standard(
+        &self,
+        batch: &RecordBatch,
+    ) -> Result<(ArrayRef, ColumnarValue, bool)> {
+        let value = self.expr.evaluate(batch)?;
+        let value = match &self.date_trunc {
+            Some(granularity) => ColumnarValue::Array(truncate_timestamps(
+                value.into_array(batch.num_rows()),
+                granularity,
+            )?),
+            None => value,
+        };
+
+        #[cfg(feature = "regex_expressions")]
+        if let Some((regex, contains_null)) = &self.regex {
+            if !self.folds_utf8() {
+                let array = value.into_array(batch.num_rows());
+                let result = match array.data_type() {
+                    DataType::Utf8 => self.evaluate_regex::<i32>(&array, regex),
+                    DataType::LargeUtf8 => self.evaluate_regex::<i64>(&array, regex),
+                    other => {
+                        return Err(DataFusionError::NotImplemented(format!(
+                            "InList regex alternation does not support datatype {:?}.",
+                            other
+                        )))
+                    }
+                };
+                let result = if *contains_null {
+                    self.null_misses(result)
+                } else {
+                    result
+                };
+                return Ok((array, result, *contains_null));
+            }
+        }
+
+        if self.list.is_empty()
+            && self.set.is_none()
+            && self.dynamic_set.is_none()
+            && self.sorted_merge.is_none()
+        {
+            let array = value.into_array(batch.num_rows());
+            let result = self.evaluate_empty_list(&array)?;
+            return Ok((array, result, false));
+        }
+
+        let value = match &self.range {
+            Some(range) => {
+                let array = value.into_array(batch.num_rows());
+                if let Some(result) = self.evaluate_range(&array, range)? {
+                    return Ok((array, result, range.contains_null));
+                }
+                ColumnarValue::Array(array)
+            }
+            None => value,
+        };
+
+        // a set built from the list falls back to scanning it for a type
+        // only the scan supports
+        let uses_set = self.list.is_empty() || set_supports(&value.data_type());
+        if let Some(sorted_merge) = &self.sorted_merge {
+            // a scalar value is broadcast so every row gets a result
+            let array = value.into_array(batch.num_rows());
+            let values =
+                array.as_any().downcast_ref::<Int64Array>().ok_or_else(|| {
+                    DataFusionError::NotImplemented(format!(
+                        "SortedMergeInSet does not support datatype {:?}.",
+                        array.data_type()
+                    ))
+                })?;
+            let merged = self
+                .input_sorted
+                .then(|| sorted_merge.merge(values, self.negated))
+                .flatten();
+            let result =
+                merged.unwrap_or_else(|| sorted_merge.search(values, self.negated));
+            Ok((array, ColumnarValue::Array(Arc::new(result)), false))
+        } else if let Some(in_set) = self.set.as_ref().filter(|_| uses_set) {
+            let array = value.into_array(batch.num_rows());
+            let result = self.evaluate_in_set(array.clone(), in_set, 0)?;
+            Ok((array, result, in_set.contains_null))
+        } else if let Some(lazy_set) = self.lazy_set.as_ref().filter(|_| uses_set) {
+            let array = value.into_array(batch.num_rows());
+            let in_set = lazy_set.get_or_try_init(|| {
+                evaluate_static_filter_values(&self.list)
+                    .map(|values| InSet::new(values.into_iter().collect()))
+            })?;
+            let result = self.evaluate_in_set(array.clone(), in_set, 0)?;
+            Ok((array, result, in_set.contains_null))
+        } else if let Some(dynamic_set) = &self.dynamic_set {
+            let array = value.into_array(batch.num_rows());
+            // hold the read lock for the whole batch so a concurrent update
+            // is never observed halfway through
+            let in_set = dynamic_set.set.read();
+            // only changed under the write lock
+            let generation = dynamic_set.generation.load(AtomicOrdering::Acquire);
+            let result = self.evaluate_in_set(array.clone(), &in_set, generation)?;
+            Ok((array, result, in_set.contains_null))
+        } else {
+            let adaptive_set = self
+                .adaptive
+                .as_ref()
+                .and_then(|adaptive| adaptive.choice.get().and_then(Option::as_ref))
+                .filter(|_| uses_set);
+            if let Some(in_set) = adaptive_set {
+                let array = value.into_array(batch.num_rows());
+                let result = self.evaluate_in_set(array.clone(), in_set, 0)?;
+                return Ok((array, result, in_set.contains_null));
+            }
+
+            let value_type = match value.data_type() {
+                DataType::Dictionary(_, value_type) => *value_type,
+                data_type => data_type,
+            };
+            let list_values = match self.static_list_values(&value_type)? {
+                Some(values) => {
+                    values.iter().cloned().map(ColumnarValue::Scalar).collect()
+                }
+                None => self
+                    .list
+                    .iter()
+                    .map(|expr| expr.evaluate(batch))
+                    .collect::<Result<Vec<_>>>()?,
+            };
+            let contains_null = list_values
+                .iter()
+                .any(|v| matches!(v, ColumnarValue::Scalar(s) if s.is_null()));
+
+            // `value IN (list_column)` tests each row's list for the value
+            if let [ColumnarValue::Array(lists)] = list_values.as_slice() {
+                if matches!(
+                    lists.data_type(),
+                    DataType::List(_) | DataType::LargeList(_)
+                ) && self.null_handling != NullHandling::SqlStandard
+                {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "InList does not support {:?} for a list of lists",
+                        self.null_handling
+                    )));
+                }
+                match lists.data_type() {
+                    DataType::List(_) => {
+                        let array = value.into_array(batch.num_rows());
+                        let result =
+                            self.evaluate_array_contains::<i32>(array.clone(), lists)?;
+                        return Ok((array, result, false));
+                    }
+                    DataType::LargeList(_) => {
+                        let array = value.into_array(batch.num_rows());
+                        let result =
+                            self.evaluate_array_contains::<i64>(array.clone(), lists)?;
+                        return Ok((array, result, false));
+                    }
+                    _ => {}
+                }
+            }
+
+            let array = value.into_array(batch.num_rows());
+
+            let result = self.evaluate_list(array.clone(), list_values)?;
+            if let Some(adaptive) = &self.adaptive {
+                self.observe_scan(adaptive, &array, &result)?;
+            }
+            Ok((array, result, contains_null))
+        }
+    }
+
+    /// Record the hits of a list scan during warm-up, choosing the
+    /// representation once it completes
+    fn observe_scan(
+        &self,
+        adaptive: &AdaptiveState,
+        array: &ArrayRef,
+        result: &ColumnarValue,
+    ) -> Result<()> {
+        if adaptive.choice.get().is_some() {
+            return Ok(());
+        }
+        let result = result.clone().into_array(array.len());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        let hits = result
+            .iter()
+            .filter(|r| matches!(r, Some(r) if *r != self.negated))
+            .count();
+        let mut observed = adaptive.observed.lock();
+        observed.0 += 1;
+        observed.1 += array.len() - array.null_count();
+        observed.2 += hits;
+        if observed.0 < ADAPTIVE_WARMUP_BATCHES {
+            return Ok(());
+        }
+        let (_, rows, hits) = *observed;
+        let values = evaluate_static_filter_values(&self.list)?;
+        // a miss compares with every value, a hit with half of them on average
+        let len = values.iter().filter(|v| !v.is_null()).count() as f64;
+        let hit_rate = if rows == 0 {
+            0.0
+        } else {
+            hits as f64 / rows as f64
+        };
+        let scan_cost = len * (1.0 - hit_rate / 2.0);
+        let in_set = InSet::new(values.into_iter().collect());
+        let choice = (in_set_cost(&in_set) < scan_cost).then(|| in_set);
+        // a concurrent warm-up may have chosen already, equally
+        let _ = adaptive.choice.set(choice);
+        Ok(())
+    }
+}
+
+/// Creates a unary expression InList
+pub fn in_list(
+    expr: Arc<dyn PhysicalExpr>,
+    list: Vec<Arc<dyn PhysicalExpr>>,
+    negated: &bool,
+) -> Result<Arc<dyn PhysicalExpr>> {
+    Ok(Arc::new(InListExpr::new(expr, list, *negated)))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{array::StringArray, datatypes::Field};
+
+    use super::*;
+    use crate::expressions::{binary, case, col, lit};
+    use datafusion_common::Result;
+
+    // applies the in_list expr to an input batch and list
+    macro_rules! in_list {
+        ($BATCH:expr, $LIST:expr, $NEGATED:expr, $EXPECTED:expr, $COL:expr) => {{
+            let expr = in_list($COL, $LIST, $NEGATED).unwrap();
+            let result = expr.evaluate(&$BATCH)?.into_array($BATCH.num_rows());
+            let result = result
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .expect("failed to downcast to BooleanArray");
+            let expected = &BooleanArray::from($EXPECTED);
+            assert_eq!(expected, result);
+        }};
+    }
+
+    // evaluate `expr` by scanning its list, as a reference for its set
+    fn evaluate_linear(expr: &InListExpr, batch: &RecordBatch) -> Result<ArrayRef> {
+        let linear = InListExpr::new_without_set(
+            expr.expr.clone(),
+            expr.list.clone(),
+            expr.negated,
+        );
+        Ok(linear.evaluate(batch)?.into_array(batch.num_rows()))
+    }
+
+    #[test]
+    fn in_list_utf8() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let a = StringArray::from(vec![Some("a"), Some("d"), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in ("a", "b")"
+        let list = vec![
+            lit(ScalarValue::Utf8(Some("a".to_string()))),
+            lit(ScalarValue::Utf8(Some("b".to_string()))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(false), None],
+            col_a.clone()
+        );
+
+        // expression: "a not in ("a", "b")"
+        let list = vec![
+            lit(ScalarValue::Utf8(Some("a".to_string()))),
+            lit(ScalarValue::Utf8(Some("b".to_string()))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), Some(true), None],
+            col_a.clone()
+        );
+
+        // expression: "a not in ("a", "b")"
+        let list = vec![
+            lit(ScalarValue::Utf8(Some("a".to_string()))),
+            lit(ScalarValue::Utf8(Some("b".to_string()))),
+            lit(ScalarValue::Utf8(None)),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), None, None],
+            col_a.clone()
+        );
+
+        // expression: "a not in ("a", "b")"
+        let list = vec![
+            lit(ScalarValue::Utf8(Some("a".to_string()))),
+            lit(ScalarValue::Utf8(Some("b".to_string()))),
+            lit(ScalarValue::Utf8(None)),
+        ];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), None, None],
+            col_a.clone()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_binary() -> Result<()> {
+        for data_type in [DataType::Binary, DataType::LargeBinary] {
+            let schema = Schema::new(vec![Field::new("a", data_type.clone(), true)]);
+            let values: Vec<Option<&[u8]>> = vec![Some(&[0, 255]), Some(&[1]), None];
+            let a: ArrayRef = match data_type {
+                DataType::Binary => Arc::new(BinaryArray::from(values)),
+                _ => Arc::new(LargeBinaryArray::from(values)),
+            };
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![a])?;
+
+            // expression: "a in (X'00FF', X'AB')"
+            let list = vec![
+                lit(ScalarValue::Binary(Some(vec![0, 255]))),
+                lit(ScalarValue::Binary(Some(vec![171]))),
+            ];
+            in_list!(
+                batch,
+                list,
+                &false,
+                vec![Some(true), Some(false), None],
+                col_a.clone()
+            );
+
+            // expression: "a not in (X'00FF', X'AB')"
+            let list = vec![
+                lit(ScalarValue::LargeBinary(Some(vec![0, 255]))),
+                lit(ScalarValue::LargeBinary(Some(vec![171]))),
+            ];
+            in_list!(
+                batch,
+                list,
+                &true,
+                vec![Some(false), Some(true), None],
+                col_a.clone()
+            );
+
+            // expression: "a in (X'00FF', X'AB', NULL)"
+            let list = vec![
+                lit(ScalarValue::Binary(Some(vec![0, 255]))),
+                lit(ScalarValue::Binary(Some(vec![171]))),
+                lit(ScalarValue::Binary(None)),
+            ];
+            in_list!(
+                batch,
+                list,
+                &false,
+                vec![Some(true), None, None],
+                col_a.clone()
+            );
+
+            // expression: "a not in (X'00FF', X'AB', NULL)"
+            let list = vec![
+                lit(ScalarValue::Binary(Some(vec![0, 255]))),
+                lit(ScalarValue::Binary(Some(vec![171]))),
+                lit(ScalarValue::Binary(None)),
+            ];
+            in_list!(
+                batch,
+                list,
+                &true,
+                vec![Some(false), None, None],
+                col_a.clone()
+            );
+
+            // a list long enough to use a set
+            let list = (0..=OPTIMIZER_INSET_THRESHOLD as u8)
+                .map(|i| lit(ScalarValue::Binary(Some(vec![i]))))
+                .collect();
+            in_list!(
+                batch,
+                list,
+                &false,
+                vec![Some(false), Some(true), None],
+                col_a.clone()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_int64() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(0), Some(2), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in (0, 1)"
+        let list = vec![
+            lit(ScalarValue::Int64(Some(0))),
+            lit(ScalarValue::Int64(Some(1))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(false), None],
+            col_a.clone()
+        );
+
+        // expression: "a not in (0, 1)"
+        let list = vec![
+            lit(ScalarValue::Int64(Some(0))),
+            lit(ScalarValue::Int64(Some(1))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), Some(true), None],
+            col_a.clone()
+        );
+
+        // expression: "a in (0, 1, NULL)"
+        let list = vec![
+            lit(ScalarValue::Int64(Some(0))),
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Utf8(None)),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), None, None],
+            col_a.clone()
+        );
+
+        // expression: "a not in (0, 1, NULL)"
+        let list = vec![
+            lit(ScalarValue::Int64(Some(0))),
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Utf8(None)),
+        ];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), None, None],
+            col_a.clone()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_float64() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+        let a = Float64Array::from(vec![Some(0.0), Some(0.2), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in (0.0, 0.2)"
+        let list = vec![
+            lit(ScalarValue::Float64(Some(0.0))),
+            lit(ScalarValue::Float64(Some(0.1))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(false), None],
+            col_a.clone()
+        );
+
+        // expression: "a not in (0.0, 0.2)"
+        let list = vec![
+            lit(ScalarValue::Float64(Some(0.0))),
+            lit(ScalarValue::Float64(Some(0.1))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), Some(true), None],
+            col_a.clone()
+        );
+
+        // expression: "a in (0.0, 0.2, NULL)"
+        let list = vec![
+            lit(ScalarValue::Float64(Some(0.0))),
+            lit(ScalarValue::Float64(Some(0.1))),
+            lit(ScalarValue::Utf8(None)),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), None, None],
+            col_a.clone()
+        );
+
+        // expression: "a not in (0.0, 0.2, NULL)"
+        let list = vec![
+            lit(ScalarValue::Float64(Some(0.0))),
+            lit(ScalarValue::Float64(Some(0.1))),
+            lit(ScalarValue::Utf8(None)),
+        ];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), None, None],
+            col_a.clone()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_bool() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Boolean, true)]);
+        let a = BooleanArray::from(vec![Some(true), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in (true)"
+        let list = vec![lit(ScalarValue::Boolean(Some(true)))];
+        in_list!(batch, list, &false, vec![Some(true), None], col_a.clone());
+
+        // expression: "a not in (true)"
+        let list = vec![lit(ScalarValue::Boolean(Some(true)))];
+        in_list!(batch, list, &true, vec![Some(false), None], col_a.clone());
+
+        // expression: "a in (true, NULL)"
+        let list = vec![
+            lit(ScalarValue::Boolean(Some(true))),
+            lit(ScalarValue::Utf8(None)),
+        ];
+        in_list!(batch, list, &false, vec![Some(true), None], col_a.clone());
+
+        // expression: "a not in (true, NULL)"
+        let list = vec![
+            lit(ScalarValue::Boolean(Some(true))),
+            lit(ScalarValue::Utf8(None)),
+        ];
+        in_list!(batch, list, &true, vec![Some(false), None], col_a.clone());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_all_literals_satisfy() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+
+        // expression: "a in (1, 2, 3)"
+        let list = vec![
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Int64(Some(2))),
+            lit(ScalarValue::Int64(Some(3))),
+        ];
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        let positive =
+            |v: &ScalarValue| matches!(v, ScalarValue::Int64(Some(v)) if *v > 0);
+        assert_eq!(expr.all_literals_satisfy(positive), Some(true));
+
+        // expression: "a in (1, -2, 3)"
+        let list = vec![
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Int64(Some(-2))),
+            lit(ScalarValue::Int64(Some(3))),
+        ];
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        assert_eq!(expr.all_literals_satisfy(positive), Some(false));
+
+        // expression: "a in (1, a)"
+        let list = vec![lit(ScalarValue::Int64(Some(1))), col_a.clone()];
+        let expr = InListExpr::new(col_a, list, false);
+        assert_eq!(expr.all_literals_satisfy(positive), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_dynamic_set() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(1), Some(2), Some(3), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let dynamic_set = DynamicInSet::new(HashSet::from([
+            ScalarValue::Int64(Some(1)),
+            ScalarValue::Int64(Some(2)),
+        ]));
+        let expr = InListExpr::new_with_dynamic_set(col_a, dynamic_set.clone(), false);
+
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        let expected =
+            BooleanArray::from(vec![Some(true), Some(true), Some(false), None]);
+        assert_eq!(&expected, result);
+
+        // the build side shrinks the filter between batches, which is probed
+        // natively as built once by the update
+        dynamic_set.update(HashSet::from([ScalarValue::Int64(Some(3))]));
+        assert!(dynamic_set.set.read().native.is_some());
+
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        let expected =
+            BooleanArray::from(vec![Some(false), Some(false), Some(true), None]);
+        assert_eq!(&expected, result);
+
+        Ok(())
+    }
+
+    /// An expression that errors if it is ever evaluated
+    #[derive(Debug)]
+    struct UnreachableExpr {}
+
+    impl std::fmt::Display for UnreachableExpr {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "unreachable")
+        }
+    }
+
+    impl PhysicalExpr for UnreachableExpr {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn data_type(&self, _input_schema: &Schema) -> Result<DataType> {
+            Ok(DataType::Int64)
+        }
+
+        fn nullable(&self, _input_schema: &Schema) -> Result<bool> {
+            Ok(true)
+        }
+
+        fn evaluate(&self, _batch: &RecordBatch) -> Result<ColumnarValue> {
+            Err(DataFusionError::Internal(
+                "UnreachableExpr should not be evaluated".to_string(),
+            ))
+        }
+    }
+
+    #[test]
+    fn in_list_empty_batch() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(Vec::<i64>::new());
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in (0, <unreachable>)"
+        let list: Vec<Arc<dyn PhysicalExpr>> = vec![
+            lit(ScalarValue::Int64(Some(0))),
+            Arc::new(UnreachableExpr {}),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            Vec::<Option<bool>>::new(),
+            col_a.clone()
+        );
+
+        // expression: "a not in (0, <unreachable>)"
+        let list: Vec<Arc<dyn PhysicalExpr>> = vec![
+            lit(ScalarValue::Int64(Some(0))),
+            Arc::new(UnreachableExpr {}),
+        ];
+        in_list!(batch, list, &true, Vec::<Option<bool>>::new(), col_a);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_serialized_size_estimate() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+
+        let ints = (0..10).map(|i| lit(ScalarValue::Int64(Some(i)))).collect();
+        let int_expr = InListExpr::new(col("a", &schema)?, ints, false);
+
+        let strings = (0..10)
+            .map(|i| lit(ScalarValue::Utf8(Some(format!("some long value {}", i)))))
+            .collect();
+        let string_expr = InListExpr::new(col("b", &schema)?, strings, false);
+
+        assert!(
+            string_expr.serialized_size_estimate() > int_expr.serialized_size_estimate()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_float_eq() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+        let one_ulp_up = f64::from_bits(1.0f64.to_bits() + 1);
+        let two_ulp_up = f64::from_bits(1.0f64.to_bits() + 2);
+        let a = Float64Array::from(vec![Some(one_ulp_up), Some(two_ulp_up), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let ulp_eq = FloatEq::new(|l: f64, r: f64| {
+            (l.to_bits() as i64 - r.to_bits() as i64).abs() <= 1
+        });
+
+        // expression: "a in (1.0)" using a 1 ULP comparison
+        let list = vec![lit(ScalarValue::Float64(Some(1.0)))];
+        let expr =
+            InListExpr::new(col_a.clone(), list, false).with_float_eq(ulp_eq.clone());
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(
+            &BooleanArray::from(vec![Some(true), Some(false), None]),
+            result
+        );
+
+        // expression: "a not in (1.0, NULL)" using a 1 ULP comparison
+        let list = vec![
+            lit(ScalarValue::Float64(Some(1.0))),
+            lit(ScalarValue::Float64(None)),
+        ];
+        let expr = InListExpr::new(col_a, list, true).with_float_eq(ulp_eq);
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(&BooleanArray::from(vec![Some(false), None, None]), result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_is_tautology_over() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let col_a = col("a", &schema)?;
+        let domain = ["S", "M", "L"]
+            .iter()
+            .map(|v| ScalarValue::Utf8(Some(v.to_string())))
+            .collect::<HashSet<_>>();
+        let list = |values: &[&str]| {
+            values
+                .iter()
+                .map(|v| lit(ScalarValue::Utf8(Some(v.to_string()))))
+                .collect::<Vec<_>>()
+        };
+
+        // expression: "a in ('L', 'M', 'S', 'XL')"
+        let expr = InListExpr::new(col_a.clone(), list(&["L", "M", "S", "XL"]), false);
+        assert!(expr.is_tautology_over(&domain));
+
+        // expression: "a in ('S', 'M')"
+        let expr = InListExpr::new(col_a.clone(), list(&["S", "M"]), false);
+        assert!(!expr.is_tautology_over(&domain));
+
+        // expression: "a not in ('L', 'M', 'S')"
+        let expr = InListExpr::new(col_a, list(&["L", "M", "S"]), true);
+        assert!(!expr.is_tautology_over(&domain));
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_timestamp_and_date() -> Result<()> {
+        const NANOS_IN_HOUR: i64 = 3_600_000_000_000;
+        const NANOS_IN_DAY: i64 = 24 * NANOS_IN_HOUR;
+        // 2023-01-01 and 2023-01-02 as days since the epoch
+        let (jan_1, jan_2) = (19358, 19359);
+
+        let schema = Schema::new(vec![Field::new(
+            "a",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            true,
+        )]);
+        let a = TimestampNanosecondArray::from(vec![
+            Some(jan_1 * NANOS_IN_DAY + 10 * NANOS_IN_HOUR),
+            Some(jan_2 * NANOS_IN_DAY),
+            Some(jan_2 * NANOS_IN_DAY + 10 * NANOS_IN_HOUR),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in (TIMESTAMP '2023-01-01 10:00', DATE '2023-01-02')"
+        let list = vec![
+            lit(ScalarValue::TimestampSecond(
+                Some((jan_1 * 24 + 10) * 3600),
+                None,
+            )),
+            lit(ScalarValue::Date32(Some(jan_2 as i32))),
+        ];
+        in_list!(
+            batch,
+            list.clone(),
+            &false,
+            vec![Some(true), Some(true), Some(false), None],
+            col_a.clone()
+        );
+
+        // expression: "a not in (TIMESTAMP '2023-01-01 10:00', DATE '2023-01-02', NULL)"
+        let mut with_null = list.clone();
+        with_null.push(lit(ScalarValue::Date32(None)));
+        in_list!(
+            batch,
+            with_null,
+            &true,
+            vec![Some(false), Some(false), None, None],
+            col_a.clone()
+        );
+
+        // the same list padded with other days, large enough to use the set
+        let mut set_list = list;
+        for day in 0..OPTIMIZER_INSET_THRESHOLD as i32 {
+            set_list.push(lit(ScalarValue::Date32(Some(day))));
+        }
+        in_list!(
+            batch,
+            set_list,
+            &false,
+            vec![Some(true), Some(true), Some(false), None],
+            col_a
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_set_builder() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(1), Some(4), Some(7), Some(8), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let mut builder = InSetBuilder::new();
+        for keys in [vec![Some(1), Some(2)], vec![Some(2), None], vec![Some(7)]] {
+            let keys: ArrayRef = Arc::new(Int64Array::from(keys));
+            builder.append(&keys)?;
+        }
+        assert_eq!(builder.len(), 3);
+
+        let expr = InListExpr::from_build_side(col_a, builder.finish(), false);
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        let expected = BooleanArray::from(vec![
+            Some(true),
+            Some(false),
+            Some(true),
+            Some(false),
+            None,
+        ]);
+        assert_eq!(&expected, result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_fingerprint() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let fingerprint = |values: &[i64], negated: bool| {
+            let list = values
+                .iter()
+                .map(|v| lit(ScalarValue::Int64(Some(*v))))
+                .collect();
+            let expr = InListExpr::new(col_a.clone(), list, negated);
+            let mut hasher = DefaultHasher::new();
+            expr.fingerprint(&mut hasher);
+            hasher.finish()
+        };
+
+        assert_eq!(
+            fingerprint(&[1, 2, 3], false),
+            fingerprint(&[3, 1, 2, 1], false)
+        );
+        assert_ne!(
+            fingerprint(&[1, 2, 3], false),
+            fingerprint(&[1, 2, 3], true)
+        );
+        assert_ne!(
+            fingerprint(&[1, 2, 3], false),
+            fingerprint(&[1, 2, 4], false)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_null_free_list_parity() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Boolean, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let a = BooleanArray::from(vec![Some(true), Some(false), None]);
+        let b = StringArray::from(vec![Some("x"), Some("y"), None]);
+        let col_a = col("a", &schema)?;
+        let col_b = col("b", &schema)?;
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a), Arc::new(b)])?;
+
+        // expression: "a in (true)" and "b in ('x')"
+        let list = vec![lit(ScalarValue::Boolean(Some(true)))];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(false), None],
+            col_a.clone()
+        );
+        let list = vec![lit(ScalarValue::Utf8(Some("x".to_string())))];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(false), None],
+            col_b.clone()
+        );
+
+        // expression: "a not in (true)" and "b not in ('x')"
+        let list = vec![lit(ScalarValue::Boolean(Some(true)))];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), Some(true), None],
+            col_a.clone()
+        );
+        let list = vec![lit(ScalarValue::Utf8(Some("x".to_string())))];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), Some(true), None],
+            col_b.clone()
+        );
+
+        // expression: "a not in (true, false)"
+        let list = vec![
+            lit(ScalarValue::Boolean(Some(true))),
+            lit(ScalarValue::Boolean(Some(false))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), Some(false), None],
+            col_a
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_lazy_set() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let a = StringArray::from(vec![Some("a0"), Some("b"), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let list = (0..=OPTIMIZER_INSET_THRESHOLD)
+            .map(|i| lit(ScalarValue::Utf8(Some(format!("a{}", i)))))
+            .collect();
+        let expr = InListExpr::new_lazy(col_a, list, false);
+        assert!(expr.set.is_none());
+        assert!(expr.lazy_set.as_ref().unwrap().get().is_none());
+
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(
+            &BooleanArray::from(vec![Some(true), Some(false), None]),
+            result
+        );
+        assert!(expr.lazy_set.as_ref().unwrap().get().is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_int64_offset_bitset() -> Result<()> {
+        let base = 1_000_000_000_000i64;
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![
+            Some(base),
+            Some(base + 9_999),
+            Some(base + 5_000),
+            Some(base + 5_001),
+            Some(base - 1),
+            Some(base + 10_000),
+            Some(i64::MIN),
+            Some(i64::MAX),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // even offsets in a 10k window starting at `base`, plus its last value
+        let mut values = (0..10_000).step_by(2).map(|i| base + i).collect::<Vec<_>>();
+        values.push(base + 9_999);
+        let list = values
+            .iter()
+            .map(|v| lit(ScalarValue::Int64(Some(*v))))
+            .collect::<Vec<_>>();
+
+        let expr = InListExpr::new(col_a.clone(), list.clone(), false);
+        assert!(matches!(
+            expr.set.as_ref().unwrap().native,
+            Some(NativeSet::Int64Bitset(_))
+        ));
+        let expected = vec![
+            Some(true),
+            Some(true),
+            Some(true),
+            Some(false),
+            Some(false),
+            Some(false),
+            Some(false),
+            Some(false),
+            None,
+        ];
+        in_list!(batch, list.clone(), &false, expected.clone(), col_a.clone());
+
+        // the bitset agrees with the hash set it replaces
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        assert_eq!(&result, &evaluate_linear(&expr, &batch)?);
+
+        let negated = expected.iter().map(|v| v.map(|v| !v)).collect::<Vec<_>>();
+        in_list!(batch, list, &true, negated, col_a.clone());
+
+        // a span too large for the bitset keeps a hash set of the values
+        let list = (0..=OPTIMIZER_INSET_THRESHOLD as i64)
+            .map(|i| lit(ScalarValue::Int64(Some(i * (i64::MAX / 64)))))
+            .collect();
+        let expr = InListExpr::new(col_a, list, false);
+        assert!(matches!(
+            expr.set.as_ref().unwrap().native,
+            Some(NativeSet::Int64Hash(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_as_parquet_filter() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ]);
+        let col_a = col("a", &schema)?;
+        let list = vec![
+            lit(ScalarValue::Int64(Some(5))),
+            lit(ScalarValue::Int64(None)),
+            lit(ScalarValue::Int64(Some(-3))),
+            lit(ScalarValue::Int64(Some(5))),
+            lit(ScalarValue::Int64(Some(2))),
+        ];
+
+        let filter = InListExpr::new(col_a.clone(), list.clone(), false)
+            .as_parquet_filter()
+            .unwrap();
+        assert_eq!(filter.column(), &expressions::Column::new("a", 0));
+        assert_eq!(
+            filter.values(),
+            &[
+                ScalarValue::Int64(Some(-3)),
+                ScalarValue::Int64(Some(2)),
+                ScalarValue::Int64(Some(5)),
+            ]
+        );
+
+        // NOT IN cannot prune on the values it excludes
+        assert!(InListExpr::new(col_a.clone(), list, true)
+            .as_parquet_filter()
+            .is_none());
+        // nor can a list of non-literals
+        let list = vec![lit(ScalarValue::Int64(Some(1))), col("b", &schema)?];
+        assert!(InListExpr::new(col_a.clone(), list, false)
+            .as_parquet_filter()
+            .is_none());
+        // nor a list of an unsupported type
+        let list = vec![lit(ScalarValue::IntervalYearMonth(Some(1)))];
+        assert!(InListExpr::new(col_a, list, false)
+            .as_parquet_filter()
+            .is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_float_infinity_and_subnormal() -> Result<()> {
+        let subnormal = f64::MIN_POSITIVE / 4.0;
+        assert!(subnormal.is_subnormal());
+        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+        let a = Float64Array::from(vec![
+            Some(f64::INFINITY),
+            Some(f64::NEG_INFINITY),
+            Some(subnormal),
+            Some(subnormal * 2.0),
+            Some(f64::MAX),
+            Some(-0.0),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let special = vec![f64::INFINITY, f64::NEG_INFINITY, subnormal, 0.0];
+        let expected = vec![
+            Some(true),
+            Some(true),
+            Some(true),
+            Some(false),
+            Some(false),
+            Some(true),
+            None,
+        ];
+        let negated = expected.iter().map(|v| v.map(|v| !v)).collect::<Vec<_>>();
+
+        // list path, then padded with values absent from the batch to use the set
+        let padding = (1..=OPTIMIZER_INSET_THRESHOLD).map(|i| i as f64);
+        for values in [
+            special.clone(),
+            special.iter().copied().chain(padding).collect(),
+        ] {
+            let list: Vec<_> = values
+                .iter()
+                .map(|v| lit(ScalarValue::Float64(Some(*v))))
+                .collect();
+            in_list!(batch, list.clone(), &false, expected.clone(), col_a.clone());
+            in_list!(batch, list, &true, negated.clone(), col_a.clone());
+        }
+
+        let schema = Schema::new(vec![Field::new("a", DataType::Float32, true)]);
+        let a = Float32Array::from(vec![
+            Some(f32::INFINITY),
+            Some(f32::NEG_INFINITY),
+            Some(f32::MIN_POSITIVE / 4.0),
+            Some(1.0),
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        let list = vec![
+            lit(ScalarValue::Float32(Some(f32::INFINITY))),
+            lit(ScalarValue::Float32(Some(f32::NEG_INFINITY))),
+            lit(ScalarValue::Float32(Some(f32::MIN_POSITIVE / 4.0))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(true), Some(true), Some(false)],
+            col_a
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_from_values_with_validity() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(1), Some(2), Some(3), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let values: ArrayRef = Arc::new(Int64Array::from(vec![1, 2, 4]));
+        // the second entry is NULL
+        let validity = Buffer::from([0b101]);
+        let evaluate = |negated| -> Result<ArrayRef> {
+            let expr = InListExpr::from_values_with_validity(
+                col_a.clone(),
+                &values,
+                &validity,
+                negated,
+            )?;
+            Ok(expr.evaluate(&batch)?.into_array(batch.num_rows()))
+        };
+
+        // a IN (1, NULL, 4)
+        let expected: ArrayRef =
+            Arc::new(BooleanArray::from(vec![Some(true), None, None, None]));
+        assert_eq!(&evaluate(false)?, &expected);
+        // a NOT IN (1, NULL, 4)
+        let expected: ArrayRef =
+            Arc::new(BooleanArray::from(vec![Some(false), None, None, None]));
+        assert_eq!(&evaluate(true)?, &expected);
+
+        // without NULL entries values not in the list are false
+        let expr = InListExpr::from_values_with_validity(
+            col_a.clone(),
+            &values,
+            &Buffer::from([0b111]),
+            false,
+        )?;
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(true),
+            Some(false),
+            None,
+        ]));
+        assert_eq!(
+            &expr.evaluate(&batch)?.into_array(batch.num_rows()),
+            &expected
+        );
+
+        let values: ArrayRef = Arc::new(Int64Array::from_iter_values(0..10));
+        assert!(InListExpr::from_values_with_validity(
+            col_a,
+            &values,
+            &Buffer::from([0xff]),
+            false
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_short_string_set() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let a = StringArray::from(vec![
+            Some("exactly8"),
+            Some("exactly8!"),
+            Some("a"),
+            Some("a\0"),
+            Some(""),
+            Some("b"),
+            Some("nine_byte"),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let padding = (0..OPTIMIZER_INSET_THRESHOLD).map(|i| format!("p{}", i));
+        let short = ["exactly8", "a", ""]
+            .iter()
+            .map(|v| v.to_string())
+            .chain(padding.clone())
+            .collect::<Vec<_>>();
+        let mixed = ["exactly8", "a", "", "nine_byte"]
+            .iter()
+            .map(|v| v.to_string())
+            .chain(padding)
+            .collect::<Vec<_>>();
+
+        for (values, packed) in [(short, true), (mixed, false)] {
+            let list = values
+                .iter()
+                .map(|v| lit(ScalarValue::Utf8(Some(v.clone()))))
+                .collect::<Vec<_>>();
+            let expr = InListExpr::new(col_a.clone(), list.clone(), false);
+            let in_set = expr.set.as_ref().unwrap();
+            assert_eq!(
+                matches!(in_set.native, Some(NativeSet::ShortUtf8(_))),
+                packed
+            );
+
+            // the packed set agrees with the string set it replaces
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            assert_eq!(&result, &evaluate_linear(&expr, &batch)?);
+
+            let expected = vec![
+                Some(true),
+                Some(false),
+                Some(true),
+                Some(false),
+                Some(true),
+                Some(false),
+                Some(!packed),
+                None,
+            ];
+            in_list!(batch, list.clone(), &false, expected.clone(), col_a.clone());
+            let negated = expected.iter().map(|v| v.map(|v| !v)).collect::<Vec<_>>();
+            in_list!(batch, list, &true, negated, col_a.clone());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_is_list_sorted() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ]);
+        let col_a = col("a", &schema)?;
+        let is_sorted = |values: Vec<Option<i64>>| {
+            let list = values
+                .into_iter()
+                .map(|v| lit(ScalarValue::Int64(v)))
+                .collect();
+            InListExpr::new(col_a.clone(), list, false).is_list_sorted()
+        };
+
+        assert_eq!(
+            is_sorted(vec![Some(-1), Some(2), Some(2), Some(7)]),
+            Some(true)
+        );
+        // NULLs are ignored
+        assert_eq!(is_sorted(vec![Some(1), None, Some(3)]), Some(true));
+        assert_eq!(is_sorted(vec![]), Some(true));
+        assert_eq!(is_sorted(vec![Some(1), Some(3), Some(2)]), Some(false));
+
+        let list = vec![lit(ScalarValue::Int64(Some(1))), col("b", &schema)?];
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        assert_eq!(expr.is_list_sorted(), None);
+
+        // values of different types cannot be ordered
+        let list = vec![
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Utf8(Some("a".to_string()))),
+        ];
+        let expr = InListExpr::new(col_a, list, false);
+        assert_eq!(expr.is_list_sorted(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_constant_case_expr() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(1), Some(2), Some(3), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(a)])?;
+
+        // CASE WHEN true THEN 1 ELSE 2 END, followed by values absent from the batch
+        let constant_case = case(
+            None,
+            &[(
+                lit(ScalarValue::Boolean(Some(true))),
+                lit(ScalarValue::Int64(Some(1))),
+            )],
+            Some(lit(ScalarValue::Int64(Some(2)))),
+        )?;
+        assert!(constant_case.is_constant());
+        let mut list = vec![constant_case];
+        list.extend((0..OPTIMIZER_INSET_THRESHOLD).map(|i| {
+            // cast entries are folded with their cast applied
+            expressions::cast(
+                lit(ScalarValue::Int32(Some(100 + i as i32))),
+                &schema,
+                DataType::Int64,
+            )
+            .unwrap()
+        }));
+
+        let expr = InListExpr::new(col_a.clone(), list.clone(), false);
+        let set = expr.set.as_ref().unwrap().get_set();
+        assert!(set.contains(&ScalarValue::Int64(Some(1))));
+        assert!(set.contains(&ScalarValue::Int64(Some(100))));
+        in_list!(
+            batch,
+            list.clone(),
+            &false,
+            vec![Some(true), Some(false), Some(false), None],
+            col_a.clone()
+        );
+
+        let lazy = InListExpr::new_lazy(col_a.clone(), list, false);
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(false),
+            Some(false),
+            None,
+        ]));
+        assert_eq!(&lazy.evaluate(&batch)?.into_array(4), &expected);
+
+        // a CASE over a column is not constant
+        let column_case = case(
+            None,
+            &[(
+                expressions::is_null(col_a.clone())?,
+                lit(ScalarValue::Int64(Some(1))),
+            )],
+            None,
+        )?;
+        assert!(!column_case.is_constant());
+        let mut list = vec![column_case];
+        list.extend(
+            (0..OPTIMIZER_INSET_THRESHOLD)
+                .map(|i| lit(ScalarValue::Int64(Some(100 + i as i64)))),
+        );
+        assert!(InListExpr::new(col_a, list, false).set.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_dropped_literals() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int8, true)]);
+        let a = Int8Array::from(vec![Some(1), Some(44), Some(-128), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(a)])?;
+
+        let list = vec![
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Int64(Some(300))),
+            lit(ScalarValue::Float64(Some(44.5))),
+            lit(ScalarValue::Utf8(Some("-128".to_string()))),
+            lit(ScalarValue::Utf8(Some("abc".to_string()))),
+            lit(ScalarValue::Int8(Some(5))),
+        ];
+
+        // nothing is collected by default
+        let expr = InListExpr::new(col_a.clone(), list.clone(), false)
+            .coerce_literals(&schema)?;
+        assert!(expr.dropped_literals().is_empty());
+        assert_eq!(expr.list().len(), 3);
+
+        let expr = InListExpr::new(col_a.clone(), list, false)
+            .with_literal_diagnostics()
+            .coerce_literals(&schema)?;
+        let dropped = expr.dropped_literals();
+        assert_eq!(dropped.len(), 3);
+        assert_eq!(dropped[0].0, ScalarValue::Int64(Some(300)));
+        assert_eq!(dropped[0].1, "300 cannot be represented as Int8");
+        assert_eq!(dropped[1].0, ScalarValue::Float64(Some(44.5)));
+        assert_eq!(dropped[1].1, "44.5 is not exactly representable as Int8");
+        assert_eq!(dropped[2].0, ScalarValue::Utf8(Some("abc".to_string())));
+
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(false),
+            Some(true),
+            None,
+        ]));
+        assert_eq!(&result, &expected);
+
+        // a type that cannot be cast at all is an error
+        let list = vec![lit(ScalarValue::IntervalYearMonth(Some(1)))];
+        assert!(InListExpr::new(col_a, list, false)
+            .coerce_literals(&schema)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_decimal_matched_scale() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Decimal(10, 2), true)]);
+        let a = [Some(100), Some(-255), Some(9_999_999_999), Some(7), None]
+            .into_iter()
+            .collect::<DecimalArray>()
+            .with_precision_and_scale(10, 2)?;
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let decimal = |v| lit(ScalarValue::Decimal128(v, 10, 2));
+        let short = vec![decimal(Some(100)), decimal(Some(-255))];
+        // padded with values absent from the batch to use the set
+        let mut long = short.clone();
+        long.extend(
+            (0..OPTIMIZER_INSET_THRESHOLD as i128).map(|i| decimal(Some(1000 + i))),
+        );
+        long.push(decimal(Some(9_999_999_999)));
+
+        let expr = InListExpr::new(col_a.clone(), long.clone(), false);
+        assert!(matches!(
+            expr.set.as_ref().unwrap().native,
+            Some(NativeSet::Decimal128 { .. })
+        ));
+
+        for (list, expected) in [
+            (
+                short,
+                vec![Some(true), Some(true), Some(false), Some(false), None],
+            ),
+            (
+                long,
+                vec![Some(true), Some(true), Some(true), Some(false), None],
+            ),
+        ] {
+            in_list!(batch, list.clone(), &false, expected.clone(), col_a.clone());
+            let negated = expected.iter().map(|v| v.map(|v| !v)).collect::<Vec<_>>();
+            in_list!(batch, list.clone(), &true, negated, col_a.clone());
+
+            // a NULL in the list makes misses NULL on the list path
+            if list.len() <= OPTIMIZER_INSET_THRESHOLD {
+                let mut list = list;
+                list.push(decimal(None));
+                let expected = expected
+                    .iter()
+                    .map(|v| v.filter(|v| *v))
+                    .collect::<Vec<_>>();
+                in_list!(batch, list, &false, expected, col_a.clone());
+            }
+        }
+
+        // values of a different scale are rescaled, unless that loses digits
+        let list = vec![lit(ScalarValue::Decimal128(Some(1005), 10, 3))];
+        let expr = in_list(col_a, list, &false)?;
+        assert!(expr.evaluate(&batch).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_into_null_rejecting() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ]);
+        let a = Int64Array::from(vec![None, Some(1), Some(3)]);
+        let b = Int64Array::from(vec![Some(1), Some(1), Some(1)]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(a), Arc::new(b)],
+        )?;
+        let list = vec![
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Int64(Some(2))),
+        ];
+
+        // a IN (1, 2)
+        let expr = InListExpr::new(col_a.clone(), list.clone(), false);
+        let rejecting = expr.into_null_rejecting().unwrap();
+        let result = rejecting.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected: ArrayRef =
+            Arc::new(BooleanArray::from(vec![None, Some(true), Some(false)]));
+        assert_eq!(&result, &expected);
+
+        // a NOT IN (1, 2)
+        let expr = InListExpr::new(col_a.clone(), list.clone(), true);
+        let rejecting = expr.into_null_rejecting().unwrap();
+        let result = rejecting.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected: ArrayRef =
+            Arc::new(BooleanArray::from(vec![None, Some(false), Some(true)]));
+        assert_eq!(&result, &expected);
+
+        // a NOT IN (1, 2, NULL)
+        let mut with_null = list.clone();
+        with_null.push(lit(ScalarValue::Int64(None)));
+        let expr = InListExpr::new(col_a.clone(), with_null, true);
+        assert!(expr.into_null_rejecting().is_none());
+
+        // a NOT IN (1, b) may see a NULL in the list at runtime
+        let mut with_column = list;
+        with_column.push(col("b", &schema)?);
+        let expr = InListExpr::new(col_a, with_column, true);
+        assert!(expr.into_null_rejecting().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_trim_trailing_whitespace() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let a = StringArray::from(vec![
+            Some("AB  "),
+            Some("AB"),
+            Some(" AB"),
+            Some("CD\t"),
+            Some("EF "),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        let evaluate = |list: Vec<Arc<dyn PhysicalExpr>>, negated| -> Result<ArrayRef> {
+            let expr = InListExpr::new(col_a.clone(), list, negated)
+                .with_trim_trailing_whitespace();
+            assert!(expr.set.is_none());
+            Ok(expr.evaluate(&batch)?.into_array(batch.num_rows()))
+        };
+
+        let mut list = vec![
+            lit(ScalarValue::Utf8(Some("AB".to_string()))),
+            lit(ScalarValue::Utf8(Some("CD ".to_string()))),
+        ];
+        // exact by default
+        in_list!(
+            batch,
+            list.clone(),
+            &false,
+            vec![
+                Some(false),
+                Some(true),
+                Some(false),
+                Some(false),
+                Some(false),
+                None
+            ],
+            col_a.clone()
+        );
+
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(true),
+            Some(false),
+            Some(true),
+            Some(false),
+            None,
+        ]));
+        assert_eq!(&evaluate(list.clone(), false)?, &expected);
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(false),
+            Some(false),
+            Some(true),
+            Some(false),
+            Some(true),
+            None,
+        ]));
+        assert_eq!(&evaluate(list.clone(), true)?, &expected);
+
+        // also for lists long enough to otherwise use the set
+        list.extend(
+            (0..OPTIMIZER_INSET_THRESHOLD)
+                .map(|i| lit(ScalarValue::Utf8(Some(format!("pad{}", i))))),
+        );
+        list.push(lit(ScalarValue::Utf8(None)));
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(true),
+            None,
+            Some(true),
+            None,
+            None,
+        ]));
+        assert_eq!(&evaluate(list, false)?, &expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_contains_scalar() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ]);
+        let col_a = col("a", &schema)?;
+        let int64 = ScalarValue::Int64;
+        let short = vec![lit(int64(Some(1))), lit(int64(Some(2)))];
+        let mut long = short.clone();
+        long.extend(
+            (0..OPTIMIZER_INSET_THRESHOLD as i64).map(|i| lit(int64(Some(100 + i)))),
+        );
+
+        for list in [short, long] {
+            let expr = InListExpr::new(col_a.clone(), list.clone(), false);
+            assert_eq!(expr.contains_scalar(&int64(Some(1)))?, Some(true));
+            assert_eq!(expr.contains_scalar(&int64(Some(3)))?, Some(false));
+            assert_eq!(expr.contains_scalar(&int64(None))?, None);
+
+            let expr = InListExpr::new(col_a.clone(), list.clone(), true);
+            assert_eq!(expr.contains_scalar(&int64(Some(1)))?, Some(false));
+            assert_eq!(expr.contains_scalar(&int64(Some(3)))?, Some(true));
+            assert_eq!(expr.contains_scalar(&int64(None))?, None);
+
+            let lazy = InListExpr::new_lazy(col_a.clone(), list, false);
+            assert_eq!(lazy.contains_scalar(&int64(Some(2)))?, Some(true));
+        }
+
+        // NULL in the list makes misses unknown
+        let list = vec![lit(int64(Some(1))), lit(int64(None))];
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        assert_eq!(expr.contains_scalar(&int64(Some(1)))?, Some(true));
+        assert_eq!(expr.contains_scalar(&int64(Some(3)))?, None);
+        let values: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), None]));
+        let expr = InListExpr::from_values_with_validity(
+            col_a.clone(),
+            &values,
+            &Buffer::from([0b11]),
+            true,
+        )?;
+        assert_eq!(expr.contains_scalar(&int64(Some(1)))?, Some(false));
+        assert_eq!(expr.contains_scalar(&int64(Some(3)))?, None);
+
+        let dynamic = DynamicInSet::new(vec![int64(Some(5))].into_iter().collect());
+        let expr = InListExpr::new_with_dynamic_set(col_a.clone(), dynamic, false);
+        assert_eq!(expr.contains_scalar(&int64(Some(5)))?, Some(true));
+        assert_eq!(expr.contains_scalar(&int64(Some(6)))?, Some(false));
+
+        let list = vec![lit(int64(Some(1))), col("b", &schema)?];
+        let expr = InListExpr::new(col_a, list, false);
+        assert!(expr.contains_scalar(&int64(Some(1))).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_array_contains() -> Result<()> {
+        let field = Field::new("item", DataType::Int32, true);
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::List(Box::new(field)), true),
+            Field::new("b", DataType::Int32, true),
+        ]);
+        let a =
+            ListArray::from_iter_primitive::<arrow::datatypes::Int32Type, _, _>(vec![
+                Some(vec![Some(1), Some(5)]),
+                Some(vec![]),
+                None,
+                Some(vec![Some(2), None]),
+                Some(vec![None, Some(5)]),
+                Some(vec![Some(5)]),
+            ]);
+        let b = Int32Array::from(vec![Some(1), Some(1), Some(1), Some(2), Some(3), None]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(a), Arc::new(b)],
+        )?;
+        let list = vec![col("a", &schema)?];
+
+        // 5 IN (a)
+        let five = lit(ScalarValue::Int32(Some(5)));
+        let expected = vec![Some(true), Some(false), None, None, Some(true), Some(true)];
+        in_list!(batch, list.clone(), &false, expected, five.clone());
+        let expected = vec![
+            Some(false),
+            Some(true),
+            None,
+            None,
+            Some(false),
+            Some(false),
+        ];
+        in_list!(batch, list.clone(), &true, expected, five);
+
+        // NULL IN (a)
+        let null = lit(ScalarValue::Int32(None));
+        in_list!(batch, list.clone(), &false, vec![None; 6], null);
+
+        // b IN (a), per row
+        let expected = vec![Some(true), Some(false), None, Some(true), None, None];
+        in_list!(batch, list.clone(), &false, expected, col("b", &schema)?);
+
+        // the value must have the element type
+        let expr = in_list(lit(ScalarValue::Int64(Some(5))), list, &false)?;
+        assert!(expr.evaluate(&batch).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_as_not_wrapped_positive() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+        ]);
+        let a = Int64Array::from(vec![Some(1), Some(3), None]);
+        let b = Int64Array::from(vec![Some(4), Some(4), Some(4)]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(a), Arc::new(b)],
+        )?;
+        let list = vec![
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Int64(Some(2))),
+        ];
+
+        // a NOT IN (1, 2) => NOT (a IN (1, 2))
+        let expr = InListExpr::new(col_a.clone(), list.clone(), true);
+        let rewritten = expr.as_not_wrapped_positive().unwrap();
+        let not = rewritten
+            .as_any()
+            .downcast_ref::<expressions::NotExpr>()
+            .unwrap();
+        let positive = not.arg().as_any().downcast_ref::<InListExpr>().unwrap();
+        assert!(!positive.negated());
+        assert_eq!(
+            &rewritten.evaluate(&batch)?.into_array(batch.num_rows()),
+            &expr.evaluate(&batch)?.into_array(batch.num_rows())
+        );
+
+        // a NOT IN (1, 2, NULL) relies on misses being NULL
+        let mut with_null = list.clone();
+        with_null.push(lit(ScalarValue::Int64(None)));
+        let expr = InListExpr::new(col_a.clone(), with_null, true);
+        assert!(expr.as_not_wrapped_positive().is_none());
+
+        // b may be NULL at runtime
+        let mut with_column = list.clone();
+        with_column.push(col("b", &schema)?);
+        let expr = InListExpr::new(col_a.clone(), with_column, true);
+        assert!(expr.as_not_wrapped_positive().is_none());
+
+        // nothing to rewrite
+        let expr = InListExpr::new(col_a, list, false);
+        assert!(expr.as_not_wrapped_positive().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_null_indicator() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("a_is_null", DataType::Boolean, true),
+        ]);
+        let a = Int64Array::from(vec![1, 1, 2, 2, 3]);
+        let a_is_null = BooleanArray::from(vec![
+            Some(false),
+            Some(true),
+            Some(false),
+            Some(true),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(a), Arc::new(a_is_null)],
+        )?;
+        let list = vec![lit(ScalarValue::Int64(Some(1)))];
+
+        for (negated, expected) in [
+            (false, vec![Some(true), None, Some(false), None, None]),
+            (true, vec![Some(false), None, Some(true), None, None]),
+        ] {
+            let expr = InListExpr::new(col_a.clone(), list.clone(), negated)
+                .with_null_indicator(col("a_is_null", &schema)?);
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            let expected: ArrayRef = Arc::new(BooleanArray::from(expected));
+            assert_eq!(&result, &expected);
+        }
+
+        let expr = InListExpr::new(col_a.clone(), list, false)
+            .with_null_indicator(col_a.clone());
+        assert!(expr.evaluate(&batch).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_sorted_merge() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let values = (0..40).map(|i| i * 3 - 30).collect::<Vec<i64>>();
+        let set = SortedMergeInSet::try_new(values.clone())?;
+        let list = values
+            .iter()
+            .map(|v| lit(ScalarValue::Int64(Some(*v))))
+            .collect::<Vec<_>>();
+
+        let sorted = Int64Array::from_iter((-40..100).map(|i| (i % 7 != 0).then(|| i)));
+        let mut unsorted = (-40..100).rev().map(Some).collect::<Vec<_>>();
+        unsorted.swap(3, 90);
+        unsorted[50] = None;
+        let unsorted = Int64Array::from(unsorted);
+
+        for array in [sorted, unsorted] {
+            let batch =
+                RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(array)])?;
+            for negated in [false, true] {
+                let hashed = InListExpr::new(col_a.clone(), list.clone(), negated);
+                assert!(hashed.set.is_some());
+                let expected = hashed.evaluate(&batch)?.into_array(batch.num_rows());
+
+                let merged =
+                    InListExpr::new_sorted_merge(col_a.clone(), set.clone(), negated);
+                for expr in [merged.clone(), merged.with_sorted_input()] {
+                    let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                    assert_eq!(&result, &expected);
+                }
+            }
+        }
+
+        assert!(SortedMergeInSet::try_new(vec![1, 3, 2]).is_err());
+        assert!(SortedMergeInSet::try_new(vec![1, 1]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_null_handling_parity() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(1), Some(3), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // 1 and, unlike 3, values absent from the batch
+        let values = |len: i64, null: bool| {
+            std::iter::once(Some(1))
+                .chain((5..len + 4).map(Some))
+                .chain(null.then(|| None))
+                .map(ScalarValue::Int64)
+                .collect::<Vec<_>>()
+        };
+        let list = |len, null| values(len, null).into_iter().map(lit).collect();
+
+        use NullHandling::*;
+        for (mode, null, negated, expected) in [
+            (SqlStandard, false, false, [Some(true), Some(false), None]),
+            (SqlStandard, true, false, [Some(true), None, None]),
+            (SqlStandard, true, true, [Some(false), None, None]),
+            (
+                NullEqualsNull,
+                false,
+                false,
+                [Some(true), Some(false), Some(false)],
+            ),
+            (
+                NullEqualsNull,
+                true,
+                false,
+                [Some(true), Some(false), Some(true)],
+            ),
+            (
+                NullEqualsNull,
+                true,
+                true,
+                [Some(false), Some(true), Some(false)],
+            ),
+            (
+                NullEqualsNothing,
+                false,
+                false,
+                [Some(true), Some(false), Some(false)],
+            ),
+            (
+                NullEqualsNothing,
+                true,
+                false,
+                [Some(true), Some(false), Some(false)],
+            ),
+            (
+                NullEqualsNothing,
+                true,
+                true,
+                [Some(false), Some(true), Some(true)],
+            ),
+        ] {
+            let expected: ArrayRef = Arc::new(BooleanArray::from(expected.to_vec()));
+            let short = InListExpr::new(col_a.clone(), list(5, null), negated);
+            let long = InListExpr::new(col_a.clone(), list(40, null), negated);
+            assert!(short.set.is_none());
+            assert!(long.set.is_some());
+            let lazy = InListExpr::new_lazy(col_a.clone(), list(40, null), negated);
+            let set = values(40, null).into_iter().collect();
+            let dynamic = InListExpr::new_with_dynamic_set(
+                col_a.clone(),
+                DynamicInSet::new(set),
+                negated,
+            );
+            for expr in [short, long, lazy, dynamic] {
+                let expr = expr.with_null_handling(mode);
+                let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                assert_eq!(&result, &expected, "{:?} {}", mode, expr);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_unmatched_entries() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let batch = |values: Vec<Option<i64>>| {
+            let a = Int64Array::from(values);
+            RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(a)])
+        };
+        let list = [1, 2, 3, 4, 5, 2]
+            .iter()
+            .map(|v| lit(ScalarValue::Int64(Some(*v))))
+            .chain(std::iter::once(lit(ScalarValue::Int64(None))))
+            .collect::<Vec<_>>();
+
+        for negated in [false, true] {
+            let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+            assert!(expr.unmatched_entries().is_empty());
+
+            let expr = expr.with_match_tracking();
+            let int64 = |v| ScalarValue::Int64(Some(v));
+            assert_eq!(
+                expr.unmatched_entries(),
+                vec![int64(1), int64(2), int64(3), int64(4), int64(5)]
+            );
+            expr.evaluate(&batch(vec![Some(1), Some(7), None, Some(3)])?)?;
+            expr.evaluate(&batch(vec![Some(5), Some(1)])?)?;
+            assert_eq!(expr.unmatched_entries(), vec![int64(2), int64(4)]);
+        }
+
+        // Int64 literals matched by an Int32 column, through the list and a set
+        let schema = Schema::new(vec![Field::new("b", DataType::Int32, true)]);
+        let col_b = col("b", &schema)?;
+        let b = Int32Array::from(vec![Some(1), Some(7), Some(3)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(b)])?;
+        let padded = list
+            .iter()
+            .cloned()
+            .chain(
+                (0..OPTIMIZER_INSET_THRESHOLD as i64)
+                    .map(|v| lit(ScalarValue::Int64(Some(100 + v)))),
+            )
+            .collect::<Vec<_>>();
+        for list in [list, padded] {
+            let expr = InListExpr::new(col_b.clone(), list, false).with_match_tracking();
+            expr.evaluate(&batch)?;
+            let unmatched = expr.unmatched_entries();
+            assert!(!unmatched.contains(&ScalarValue::Int64(Some(1))));
+            assert!(!unmatched.contains(&ScalarValue::Int64(Some(3))));
+            assert!(unmatched.contains(&ScalarValue::Int64(Some(2))));
+        }
+
+        // literals matched by strings with trailing whitespace
+        let schema = Schema::new(vec![Field::new("c", DataType::Utf8, true)]);
+        let col_c = col("c", &schema)?;
+        let c = StringArray::from(vec![Some("ab  "), Some("x")]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(c)])?;
+        let utf8 = |v: &str| ScalarValue::Utf8(Some(v.to_string()));
+        let list = vec![lit(utf8("ab")), lit(utf8("cd "))];
+        let expr = InListExpr::new(col_c, list, false)
+            .with_trim_trailing_whitespace()
+            .with_match_tracking();
+        expr.evaluate(&batch)?;
+        assert_eq!(expr.unmatched_entries(), vec![utf8("cd ")]);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "fst_string_set")]
+    #[test]
+    fn in_list_fst_string_set() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let a = StringArray::from(vec![
+            Some("code_0"),
+            Some("code_99999"),
+            Some("code_100000"),
+            Some("code_"),
+            Some(""),
+            Some("other"),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let list = (0..=FST_SET_THRESHOLD)
+            .map(|i| lit(ScalarValue::Utf8(Some(format!("code_{}", i)))))
+            .chain(std::iter::once(lit(ScalarValue::Utf8(Some(
+                "".to_string(),
+            )))))
+            .collect::<Vec<_>>();
+        for negated in [false, true] {
+            let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+            let in_set = expr.set.as_ref().unwrap();
+            assert!(matches!(in_set.native, Some(NativeSet::FstUtf8(_))));
+
+            // the FST agrees with the hash set it replaces
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            assert_eq!(&result, &evaluate_linear(&expr, &batch)?);
+
+            let expected = [true, true, true, false, true, false]
+                .iter()
+                .map(|v| Some(*v != negated))
+                .chain(std::iter::once(None))
+                .collect::<Vec<_>>();
+            let expected: ArrayRef = Arc::new(BooleanArray::from(expected));
+            assert_eq!(&result, &expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_evaluate_tristate() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(1), Some(2), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // a IN (1, NULL)
+        let list = vec![
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Int64(None)),
+        ];
+        let expr = InListExpr::new(col_a.clone(), list.clone(), false);
+        let result = expr.evaluate_tristate(&batch)?;
+        assert_eq!(result, UInt8Array::from(vec![1, 2, 2]));
+        assert_eq!(result.null_count(), 0);
+
+        // a IN (1, 3)
+        let list = vec![
+            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Int64(Some(3))),
+        ];
+        let expr = InListExpr::new(col_a, list, false);
+        let result = expr.evaluate_tristate(&batch)?;
+        assert_eq!(result, UInt8Array::from(vec![1, 0, 2]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_null_scalar_value() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(1), Some(2), Some(3)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        let value = lit(ScalarValue::Int64(None));
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![None, None, None]));
+
+        // list path, then set path
+        let list = vec![lit(ScalarValue::Int64(Some(1)))];
+        let set_list = (0..(OPTIMIZER_INSET_THRESHOLD as i64 + 1))
+            .map(|i| lit(ScalarValue::Int64(Some(i))))
+            .collect::<Vec<_>>();
+        for list in [list, set_list] {
+            for negated in [false, true] {
+                let expr = InListExpr::new(value.clone(), list.clone(), negated);
+                match expr.evaluate(&batch)? {
+                    ColumnarValue::Array(result) => assert_eq!(&result, &expected),
+                    ColumnarValue::Scalar(_) => unreachable!(),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_restrict_to_range() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(5), Some(10), Some(20), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // a IN (1, 5, 10, 20) restricted to [4, 15]
+        let list = [1, 5, 10, 20]
+            .iter()
+            .map(|v| lit(ScalarValue::Int64(Some(*v))))
+            .collect::<Vec<_>>();
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        let restricted = expr.restrict_to_range(
+            &ScalarValue::Int64(Some(4)),
+            &ScalarValue::Int64(Some(15)),
+        )?;
+        assert_eq!(restricted.to_string(), "a@0 IN (5, 10)");
+        let result = restricted.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(true),
+            Some(false),
+            None,
+        ]));
+        assert_eq!(&result, &expected);
+
+        // a set is rebuilt from the remaining values
+        let list = (0..100)
+            .map(|v| lit(ScalarValue::Int64(Some(v))))
+            .collect::<Vec<_>>();
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        let restricted = expr.restrict_to_range(
+            &ScalarValue::Int64(Some(0)),
+            &ScalarValue::Int64(Some(9)),
+        )?;
+        assert_eq!(restricted.list().len(), 10);
+        let result = restricted.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(false),
+            Some(false),
+            None,
+        ]));
+        assert_eq!(&result, &expected);
+
+        // values are not ordered against bounds of another type
+        let list = vec![lit(ScalarValue::Int64(Some(1)))];
+        let expr = InListExpr::new(col_a, list, false);
+        let low = ScalarValue::Utf8(Some("a".to_string()));
+        assert!(expr.restrict_to_range(&low, &low).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_bool_null_equals_null() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Boolean, true)]);
+        let a = BooleanArray::from(vec![Some(true), Some(false), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // a IN (true, NULL)
+        let list = vec![
+            lit(ScalarValue::Boolean(Some(true))),
+            lit(ScalarValue::Boolean(None)),
+        ];
+        for (null_handling, expected) in [
+            (NullHandling::SqlStandard, vec![Some(true), None, None]),
+            (
+                NullHandling::NullEqualsNull,
+                vec![Some(true), Some(false), Some(true)],
+            ),
+        ] {
+            let expr = InListExpr::new(col_a.clone(), list.clone(), false)
+                .with_null_handling(null_handling);
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            let expected: ArrayRef = Arc::new(BooleanArray::from(expected));
+            assert_eq!(&result, &expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_per_row_cost() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Float64, true),
+        ]);
+        let col_a = col("a", &schema)?;
+        let col_b = col("b", &schema)?;
+        let ints = |n: i64| {
+            (0..n)
+                .map(|v| lit(ScalarValue::Int64(Some(v))))
+                .collect::<Vec<_>>()
+        };
+        let floats = |n: usize| {
+            (0..n)
+                .map(|v| lit(ScalarValue::Float64(Some(v as f64))))
+                .collect::<Vec<_>>()
+        };
+
+        // a linear scan costs more for a longer list
+        let short = InListExpr::new(col_b.clone(), floats(2), false);
+        let long = InListExpr::new(col_b.clone(), floats(20), false);
+        assert!(long.per_row_cost() > short.per_row_cost());
+
+        // a hash set stays constant however many values it holds
+        let set = InListExpr::new(col_b.clone(), floats(100), false);
+        let larger_set = InListExpr::new(col_b, floats(10_000), false);
+        assert_eq!(set.per_row_cost(), larger_set.per_row_cost());
+        assert!(set.per_row_cost() < long.per_row_cost());
+
+        // a bitset is cheaper than a hash set
+        let bitset = InListExpr::new(col_a, ints(100), false);
+        assert!(bitset.per_row_cost() < set.per_row_cost());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "regex_expressions")]
+    #[test]
+    fn in_list_regex_alternation() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let a = StringArray::from(vec![
+            Some("a.c"),
+            Some("abc"),
+            Some("(x|y)"),
+            Some("x"),
+            Some("$1^"),
+            Some("a.c\n"),
+            Some("pa.c"),
+            Some(""),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // literals containing regex metacharacters match only themselves
+        let literals = ["a.c", "(x|y)", "$1^", "[", "\\d+", "?"];
+        let list = literals
+            .iter()
+            .map(|v| lit(ScalarValue::Utf8(Some(v.to_string()))))
+            .collect::<Vec<_>>();
+        let list_with_null = list
+            .iter()
+            .cloned()
+            .chain(std::iter::once(lit(ScalarValue::Utf8(None))))
+            .collect::<Vec<_>>();
+        for list in [list, list_with_null] {
+            for negated in [false, true] {
+                let expected = InListExpr::new(col_a.clone(), list.clone(), negated)
+                    .evaluate(&batch)?
+                    .into_array(batch.num_rows());
+                let expr = InListExpr::new(col_a.clone(), list.clone(), negated)
+                    .with_regex_alternation()?;
+                assert!(expr.regex.is_some());
+                let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                assert_eq!(&result, &expected);
+            }
+        }
+
+        // only constant string lists can be compiled
+        let expr = InListExpr::new(col_a, vec![lit(ScalarValue::Int64(Some(1)))], false);
+        assert!(expr.with_regex_alternation().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_dictionary() -> Result<()> {
+        let field_type =
+            DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+        let schema = Schema::new(vec![Field::new("a", field_type, true)]);
+        let distinct = (0..100).map(|i| format!("value_{}", i)).collect::<Vec<_>>();
+        let a = (0..1_000_000)
+            .map(|i| match i % 101 {
+                100 => None,
+                i => Some(distinct[i].as_str()),
+            })
+            .collect::<DictionaryArray<Int32Type>>();
+        assert_eq!(a.values().len(), 100);
+        let col_a = col("a", &schema)?;
+        let a: ArrayRef = Arc::new(a);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![a.clone()])?;
+        let plain = kernels::cast::cast(&a, &DataType::Utf8)?;
+        let plain_batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Utf8, true)])),
+            vec![plain],
+        )?;
+
+        let literals = |n: usize| {
+            (0..n)
+                .map(|i| lit(ScalarValue::Utf8(Some(format!("value_{}", i * 3)))))
+                .chain(std::iter::once(lit(ScalarValue::Utf8(None))))
+                .collect::<Vec<_>>()
+        };
+        // list path, then set path
+        for list in [literals(5), literals(50)] {
+            for negated in [false, true] {
+                let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+                let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                let expected = expr
+                    .evaluate(&plain_batch)?
+                    .into_array(plain_batch.num_rows());
+                assert_eq!(&result, &expected);
+            }
+        }
+
+        // each distinct value is probed once, however many keys reference it
+        let expr = InListExpr::new(col_a, literals(50), false);
+        let in_set = expr.set.as_ref().unwrap();
+        let mut probed = 0;
+        let result = evaluate_dictionary(&a, |values| {
+            probed += values.len();
+            expr.evaluate_set(values, in_set)
+        })?;
+        assert_eq!(probed, 100);
+        assert_eq!(result.into_array(0).len(), 1_000_000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_evaluate_non_null() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let schema = Arc::new(schema);
+        let one = lit(ScalarValue::Int64(Some(1)));
+        let null = lit(ScalarValue::Int64(None));
+
+        // neither the input nor the list has NULLs
+        let a = Int64Array::from(vec![Some(1), Some(2)]);
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(a)])?;
+        let expr = InListExpr::new(col_a.clone(), vec![one.clone()], false);
+        let result = expr.evaluate_non_null(&batch)?;
+        assert_eq!(result, BooleanArray::from(vec![true, false]));
+        assert!(result.data().null_buffer().is_none());
+
+        // a miss against a NULL list entry is NULL
+        let expr = InListExpr::new(col_a.clone(), vec![one.clone(), null], false);
+        assert!(expr.evaluate_non_null(&batch).is_err());
+
+        // as is a NULL input
+        let a = Int64Array::from(vec![Some(1), None]);
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(a)])?;
+        let expr = InListExpr::new(col_a, vec![one], false);
+        assert!(expr.evaluate_non_null(&batch).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_split() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a =
+            Int64Array::from(vec![Some(0), Some(7), Some(33), Some(99), Some(100), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        for len in [3, 100] {
+            let list = (0..len)
+                .map(|v| lit(ScalarValue::Int64(Some(v * 3))))
+                .chain(std::iter::once(lit(ScalarValue::Int64(None))))
+                .collect::<Vec<_>>();
+            for negated in [false, true] {
+                let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+                let expected = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                let splits = expr.split(4);
+                assert_eq!(splits.len(), 4);
+                let mut combined = BooleanArray::from(vec![negated; batch.num_rows()]);
+                for split in splits {
+                    let result = split.evaluate(&batch)?.into_array(batch.num_rows());
+                    let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+                    combined = match negated {
+                        false => kernels::boolean::or_kleene(&combined, result)?,
+                        true => kernels::boolean::and_kleene(&combined, result)?,
+                    };
+                }
+                let combined: ArrayRef = Arc::new(combined);
+                assert_eq!(&combined, &expected);
+            }
+        }
+
+        // a list shorter than k gives fewer expressions
+        let list = vec![lit(ScalarValue::Int64(Some(1)))];
+        let expr = InListExpr::new(col_a, list, false);
+        assert_eq!(expr.split(4).len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_date_trunc() -> Result<()> {
+        const HOUR: i64 = 3600;
+        const DAY: i64 = 24 * HOUR;
+        for unit in [TimeUnit::Second, TimeUnit::Nanosecond] {
+            let scale = units_per_second(&unit);
+            let schema = Schema::new(vec![Field::new(
+                "a",
+                DataType::Timestamp(unit.clone(), None),
+                true,
+            )]);
+            // 2023-01-01 05:00, 2023-01-02 23:00, 2023-01-03 00:00
+            let base = 19358 * DAY;
+            let a = Arc::new(
+                [
+                    Some(base + 5 * HOUR),
+                    Some(base + DAY + 23 * HOUR),
+                    Some(base + 2 * DAY),
+                    None,
+                ]
+                .iter()
+                .map(|v| v.map(|v| v * scale))
+                .collect::<Int64Array>(),
+            ) as ArrayRef;
+            let a = kernels::cast::cast(&a, &DataType::Timestamp(unit, None))?;
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![a])?;
+
+            // date_trunc('day', a) IN (DATE '2023-01-01', DATE '2023-01-02'),
+            // through the list and set paths
+            let dates = |n: i32| {
+                vec![
+                    lit(ScalarValue::Date32(Some(19358))),
+                    lit(ScalarValue::Date32(Some(19359))),
+                ]
+                .into_iter()
+                .chain((0..n).map(|i| lit(ScalarValue::Date32(Some(i)))))
+                .collect::<Vec<_>>()
+            };
+            for list in [dates(0), dates(40)] {
+                let expr =
+                    InListExpr::new(col_a.clone(), list, false).with_date_trunc("day")?;
+                let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+                    Some(true),
+                    Some(true),
+                    Some(false),
+                    None,
+                ]));
+                assert_eq!(&result, &expected);
+            }
+        }
+
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let expr = InListExpr::new(col("a", &schema)?, vec![], false);
+        assert!(expr.with_date_trunc("fortnight").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_reorder_by_frequency() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+        let a = Float64Array::from(vec![
+            Some(9.0),
+            Some(9.0),
+            Some(9.0),
+            Some(1.0),
+            Some(4.0),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let comparisons = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = comparisons.clone();
+        // counts each comparison of the scan
+        let float_eq = FloatEq::new(move |l, r| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            l == r
+        });
+        let list = (1..10)
+            .map(|v| lit(ScalarValue::Float64(Some(v as f64))))
+            .collect::<Vec<_>>();
+        let expr = InListExpr::new(col_a, list, false).with_float_eq(float_eq);
+        let freqs = HashMap::from([
+            (ScalarValue::Float64(Some(9.0)), 300),
+            (ScalarValue::Float64(Some(1.0)), 100),
+        ]);
+        let reordered = expr.reorder_by_frequency(&freqs);
+        let order = static_filter_values(reordered.list()).unwrap();
+        assert_eq!(order[0], ScalarValue::Float64(Some(9.0)));
+        assert_eq!(order[1], ScalarValue::Float64(Some(1.0)));
+        // the remaining values keep their order
+        assert_eq!(order[2], ScalarValue::Float64(Some(2.0)));
+
+        let expected = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let before = comparisons.swap(0, std::sync::atomic::Ordering::Relaxed);
+        let result = reordered.evaluate(&batch)?.into_array(batch.num_rows());
+        let after = comparisons.load(std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(&result, &expected);
+        // 9 + 9 + 9 + 1 + 4 comparisons before, 1 + 1 + 1 + 2 + 5 after
+        assert_eq!(before, 32);
+        assert_eq!(after, 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_float32_against_float64() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Float32, true)]);
+        let a = Float32Array::from(vec![Some(1.0), Some(0.1), Some(2.0), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // 1.0 + 2^-40 rounds to 1.0 as an f32, but must not match it
+        let literals = vec![
+            lit(ScalarValue::Float64(Some(1.0 + 2f64.powi(-40)))),
+            lit(ScalarValue::Float64(Some(0.1f32 as f64))),
+            lit(ScalarValue::Float32(Some(2.0))),
+        ];
+        let padding = (0..40).map(|i| lit(ScalarValue::Float64(Some(100.0 + i as f64))));
+        // list path, then set path
+        for list in [
+            literals.clone(),
+            literals.into_iter().chain(padding).collect(),
+        ] {
+            let expr = InListExpr::new(col_a.clone(), list, false);
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+                Some(false),
+                Some(true),
+                Some(true),
+                None,
+            ]));
+            assert_eq!(&result, &expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_matched_values() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let schema = Arc::new(schema);
+        let list = [3, 1, 2, 1]
+            .iter()
+            .map(|v| lit(ScalarValue::Int64(Some(*v))))
+            .chain(std::iter::once(lit(ScalarValue::Int64(None))))
+            .collect::<Vec<_>>();
+        let expr =
+            InListExpr::new(col_a.clone(), list.clone(), false).with_matched_values();
+        assert!(expr.matched_values().is_empty());
+
+        let a = Int64Array::from(vec![Some(1), Some(4), None, Some(1), Some(3)]);
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(a)])?;
+        expr.evaluate(&batch)?;
+        assert_eq!(
+            expr.matched_values(),
+            vec![ScalarValue::Int64(Some(3)), ScalarValue::Int64(Some(1))]
+        );
+
+        // only the most recent call is reported
+        let a = Int64Array::from(vec![Some(2), Some(5)]);
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(a)])?;
+        expr.evaluate(&batch)?;
+        assert_eq!(expr.matched_values(), vec![ScalarValue::Int64(Some(2))]);
+
+        // Int64 literals matched by an Int32 column
+        let int32_schema = Schema::new(vec![Field::new("b", DataType::Int32, true)]);
+        let col_b = col("b", &int32_schema)?;
+        let b = Int32Array::from(vec![Some(3), Some(4), None]);
+        let int32_batch =
+            RecordBatch::try_new(Arc::new(int32_schema), vec![Arc::new(b)])?;
+        let expr = InListExpr::new(col_b, list.clone(), false).with_matched_values();
+        expr.evaluate(&int32_batch)?;
+        assert_eq!(expr.matched_values(), vec![ScalarValue::Int64(Some(3))]);
+
+        // not enabled by cumulative tracking
+        let expr =
+            InListExpr::new(col_a.clone(), list.clone(), false).with_match_tracking();
+        expr.evaluate(&batch)?;
+        assert!(expr.matched_values().is_empty());
+
+        // both kinds of tracking together each behave as on their own
+        let expr = InListExpr::new(col_a, list, false)
+            .with_match_tracking()
+            .with_matched_values();
+        let a = Int64Array::from(vec![Some(1), Some(4)]);
+        expr.evaluate(&RecordBatch::try_new(schema.clone(), vec![Arc::new(a)])?)?;
+        expr.evaluate(&batch)?;
+        assert_eq!(expr.matched_values(), vec![ScalarValue::Int64(Some(2))]);
+        assert_eq!(expr.unmatched_entries(), vec![ScalarValue::Int64(Some(3))]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_first_byte_string_set() -> Result<()> {
+        let value = |i: usize| format!("{}_value_{}", (b'!' + (i % 90) as u8) as char, i);
+        let values = (0..1000)
+            .map(|i| Some(value(i)))
+            .chain([Some("".to_string()), Some("é_long_value".to_string()), None])
+            .collect::<Vec<_>>();
+        let list = (0..FIRST_BYTE_SET_THRESHOLD)
+            .map(|i| value(i * 3))
+            .chain(["".to_string(), "é_long_value".to_string()])
+            .map(|v| lit(ScalarValue::Utf8(Some(v))))
+            .collect::<Vec<_>>();
+        let list_with_null = list
+            .iter()
+            .cloned()
+            .chain(std::iter::once(lit(ScalarValue::Utf8(None))))
+            .collect::<Vec<_>>();
+
+        for large in [false, true] {
+            let (data_type, a): (_, ArrayRef) = match large {
+                false => (
+                    DataType::Utf8,
+                    Arc::new(
+                        values.iter().map(|v| v.as_deref()).collect::<StringArray>(),
+                    ),
+                ),
+                true => (
+                    DataType::LargeUtf8,
+                    Arc::new(
+                        values
+                            .iter()
+                            .map(|v| v.as_deref())
+                            .collect::<LargeStringArray>(),
+                    ),
+                ),
+            };
+            let schema = Schema::new(vec![Field::new("a", data_type, true)]);
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![a])?;
+            for list in [&list, &list_with_null] {
+                let list = match large {
+                    false => list.clone(),
+                    true => list
+                        .iter()
+                        .map(|e| {
+                            let v = e
+                                .as_any()
+                                .downcast_ref::<expressions::Literal>()
+                                .unwrap();
+                            match v.value() {
+                                ScalarValue::Utf8(v) => {
+                                    lit(ScalarValue::LargeUtf8(v.clone()))
+                                }
+                                _ => unreachable!(),
+                            }
+                        })
+                        .collect(),
+                };
+                for negated in [false, true] {
+                    let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+                    let in_set = expr.set.as_ref().unwrap();
+                    assert!(matches!(
+                        in_set.native,
+                        Some(
+                            NativeSet::FirstByteUtf8(_)
+                                | NativeSet::FirstByteLargeUtf8(_)
+                        )
+                    ));
+                    let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                    // the list path compares the same values one at a time
+                    let expected =
+                        InListExpr::new_without_set(col_a.clone(), list.clone(), negated)
+                            .evaluate(&batch)?;
+                    assert_eq!(&result, &expected.into_array(batch.num_rows()));
+                    let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+                    // the empty string and multi-byte first characters match
+                    assert_eq!(result.value(1000), !negated);
+                    assert_eq!(result.value(1001), !negated);
+                    assert_eq!(result.value(3), !negated);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_match_index_tie_break() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(1), Some(2), Some(3), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(a)])?;
+
+        // '2' and '02' both coerce to 2
+        let list = ["3", "2", "02", "1"]
+            .iter()
+            .map(|v| lit(ScalarValue::Utf8(Some(v.to_string()))))
+            .collect::<Vec<_>>();
+        let expr = InListExpr::new(col_a, list, false)
+            .with_matched_values()
+            .coerce_literals(&schema)?;
+        assert_eq!(
+            static_filter_values(expr.list()).unwrap()[1..3],
+            [ScalarValue::Int64(Some(2)), ScalarValue::Int64(Some(2))]
+        );
+        let result = expr.evaluate_match_index(&batch)?;
+        assert_eq!(
+            result,
+            Int32Array::from(vec![Some(3), Some(1), Some(0), None])
+        );
+
+        // the duplicate is reported once, at the first position
+        expr.evaluate(&batch)?;
+        assert_eq!(
+            expr.matched_values(),
+            vec![
+                ScalarValue::Int64(Some(3)),
+                ScalarValue::Int64(Some(2)),
+                ScalarValue::Int64(Some(1))
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_int32_perfect_hash() -> Result<()> {
+        let values = (0..64).map(|i| i * 97 - 3000).collect::<Vec<i32>>();
+        let table = Int32PerfectHash::try_new(&values).unwrap();
+        // two values colliding in the bucket and slot hashes of the first table
+        let colliding = (1..)
+            .map(|i| values[0].wrapping_add(i))
+            .find(|v| {
+                table.bucket(*v) == table.bucket(values[0])
+                    && table.slot(*v, 0) == table.slot(values[0], 0)
+            })
+            .unwrap();
+        let values = values
+            .into_iter()
+            .chain([colliding, i32::MIN, i32::MAX, 0])
+            .collect::<Vec<_>>();
+
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let a = (-10_000..10_000)
+            .map(Some)
+            .chain([Some(colliding), Some(i32::MIN), Some(i32::MAX), None])
+            .collect::<Int32Array>();
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        let list = values
+            .iter()
+            .map(|v| lit(ScalarValue::Int32(Some(*v))))
+            .collect::<Vec<_>>();
+        for negated in [false, true] {
+            let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+            let in_set = expr.set.as_ref().unwrap();
+            assert!(matches!(
+                in_set.native,
+                Some(NativeSet::Int32PerfectHash(_))
+            ));
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            assert_eq!(&result, &evaluate_linear(&expr, &batch)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_as_scalar_predicate() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let one = lit(ScalarValue::Int64(Some(1)));
+        let null = lit(ScalarValue::Int64(None));
+        let many = (0..40)
+            .map(|v| lit(ScalarValue::Int64(Some(v))))
+            .collect::<Vec<_>>();
+
+        let hit = ScalarValue::Int64(Some(1));
+        let miss = ScalarValue::Int64(Some(100));
+        let null_value = ScalarValue::Int64(None);
+        // list path and set path
+        for list in [vec![one.clone()], many] {
+            let predicate = InListExpr::new(col_a.clone(), list.clone(), false)
+                .as_scalar_predicate()?;
+            assert_eq!(predicate(&hit), Some(true));
+            assert_eq!(predicate(&miss), Some(false));
+            assert_eq!(predicate(&null_value), None);
+
+            let predicate =
+                InListExpr::new(col_a.clone(), list, true).as_scalar_predicate()?;
+            assert_eq!(predicate(&hit), Some(false));
+            assert_eq!(predicate(&miss), Some(true));
+            assert_eq!(predicate(&null_value), None);
+        }
+
+        // a miss against a NULL entry is NULL
+        let predicate = InListExpr::new(col_a.clone(), vec![one, null], false)
+            .as_scalar_predicate()?;
+        assert_eq!(predicate(&hit), Some(true));
+        assert_eq!(predicate(&miss), None);
+
+        // a list depending on the input can't be captured
+        let expr = InListExpr::new(col_a.clone(), vec![col_a], false);
+        assert!(expr.as_scalar_predicate().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_garbage_in_null_slots() -> Result<()> {
+        // the null slots hold values in the list, and arbitrary bits
+        let values = Buffer::from_slice_ref(&[1i64, 2, -1, i64::MIN, 3]);
+        let validity = Buffer::from([0b10001]);
+        let a = Int64Array::from(
+            ArrayData::builder(DataType::Int64)
+                .len(5)
+                .add_buffer(values)
+                .null_bit_buffer(Some(validity.clone()))
+                .build()?,
+        );
+        let offsets = Buffer::from_slice_ref(&[0i32, 1, 2, 6, 6, 7]);
+        let strings = Buffer::from(b"ab\xF0\x9F\x98\x80c".as_ref());
+        let b = StringArray::from(
+            ArrayData::builder(DataType::Utf8)
+                .len(5)
+                .add_buffer(offsets)
+                .add_buffer(strings)
+                .null_bit_buffer(Some(validity))
+                .build()?,
+        );
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let col_a = col("a", &schema)?;
+        let col_b = col("b", &schema)?;
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a), Arc::new(b)])?;
+
+        let ints = [1, 2, -1, i64::MIN]
+            .iter()
+            .map(|v| lit(ScalarValue::Int64(Some(*v))))
+            .collect::<Vec<_>>();
+        let strings = ["a", "b", "\u{1F600}"]
+            .iter()
+            .map(|v| lit(ScalarValue::Utf8(Some(v.to_string()))))
+            .collect::<Vec<_>>();
+        for (col, list) in [(col_a, ints), (col_b, strings)] {
+            for negated in [false, true] {
+                let expr = InListExpr::new(col.clone(), list.clone(), negated);
+                let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+                    Some(!negated),
+                    None,
+                    None,
+                    None,
+                    Some(negated),
+                ]));
+                assert_eq!(&result, &expected);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_adaptive_representation() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+        let col_a = col("a", &schema)?;
+        let schema = Arc::new(schema);
+        let list = |n: usize| {
+            (0..n)
+                .map(|v| lit(ScalarValue::Float64(Some(v as f64))))
+                .chain(std::iter::once(lit(ScalarValue::Float64(None))))
+                .collect::<Vec<_>>()
+        };
+        let batch = |i: usize| -> Result<RecordBatch> {
+            let a = Float64Array::from(vec![Some(i as f64 % 7.0), Some(100.0), None]);
+            Ok(RecordBatch::try_new(schema.clone(), vec![Arc::new(a)])?)
+        };
+
+        // a scan missing often is replaced by a set after warm-up, a short
+        // scan hitting often is kept
+        for (len, switches) in [(20, true), (1, false)] {
+            for negated in [false, true] {
+                let expr = InListExpr::new(col_a.clone(), list(len), negated)
+                    .with_adaptive_representation();
+                let reference = InListExpr::new(col_a.clone(), list(len), negated);
+                let adaptive = expr.adaptive.clone().unwrap();
+                for i in 0..3 * ADAPTIVE_WARMUP_BATCHES {
+                    assert_eq!(
+                        adaptive.choice.get().is_some(),
+                        i >= ADAPTIVE_WARMUP_BATCHES
+                    );
+                    let batch = batch(i)?;
+                    let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                    let expected =
+                        reference.evaluate(&batch)?.into_array(batch.num_rows());
+                    assert_eq!(&result, &expected);
+                }
+                let choice = adaptive.choice.get().unwrap();
+                assert_eq!(choice.is_some(), switches);
+            }
+        }
+
+        // a list that uses a set already is not adaptive
+        let expr = InListExpr::new(col_a, list(40), false).with_adaptive_representation();
+        assert!(expr.adaptive.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_decimal_rescaled() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Decimal(38, 10), true)]);
+        // 1.00, 2.50, 3.1234567891, NULL
+        let a = [
+            Some(10_000_000_000),
+            Some(25_000_000_000),
+            Some(31_234_567_891),
+            None,
+        ]
+        .into_iter()
+        .collect::<DecimalArray>()
+        .with_precision_and_scale(38, 10)?;
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // amount IN (1.00, 2.5), literals of other scales and precisions
+        let literals = vec![
+            lit(ScalarValue::Decimal128(Some(100), 3, 2)),
+            lit(ScalarValue::Decimal128(Some(25), 2, 1)),
+        ];
+        let padding =
+            (0..40).map(|i| lit(ScalarValue::Decimal128(Some(1000 + i), 10, 0)));
+        // list path, then set path
+        for list in [
+            literals.clone(),
+            literals.into_iter().chain(padding).collect(),
+        ] {
+            for negated in [false, true] {
+                let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+                let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                let expected: ArrayRef = Arc::new(BooleanArray::from(vec![
+                    Some(!negated),
+                    Some(!negated),
+                    Some(negated),
+                    None,
+                ]));
+                assert_eq!(&result, &expected);
+            }
+        }
+
+        // a literal with more digits than the column scale is an error
+        let list = vec![lit(ScalarValue::Decimal128(Some(1), 38, 11))];
+        let expr = InListExpr::new(col_a, list, false);
+        assert!(expr.evaluate(&batch).is_err());
+        assert!(rescale_decimal(10, 12, 11)?.is_some());
+        assert!(rescale_decimal(i128::MAX, 0, 1)?.is_none());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "unicode_expressions")]
+    #[test]
+    fn in_list_ignore_diacritics() -> Result<()> {
+        // precomposed and decomposed accents
+        let values = vec![
+            Some("résumé"),
+            Some("re\u{301}sume\u{301}"),
+            Some("Résumé"),
+            Some("naïve"),
+            Some("søren"),
+            Some("resume"),
+            None,
+        ];
+        let list = ["resume", "naive", "soren"]
+            .iter()
+            .map(|v| lit(ScalarValue::Utf8(Some(v.to_string()))))
+            .collect::<Vec<_>>();
+        // padded to use the set without the mode
+        let long = list
+            .iter()
+            .cloned()
+            .chain((0..40).map(|i| lit(ScalarValue::Utf8(Some(format!("v{}", i))))))
+            .collect::<Vec<_>>();
+
+        for data_type in [DataType::Utf8, DataType::LargeUtf8] {
+            let schema = Schema::new(vec![Field::new("a", data_type.clone(), true)]);
+            let a: ArrayRef = Arc::new(StringArray::from(values.clone()));
+            let a = kernels::cast::cast(&a, &data_type)?;
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![a])?;
+            for list in [&list, &long] {
+                for negated in [false, true] {
+                    let expr = InListExpr::new(col_a.clone(), list.clone(), negated)
+                        .with_ignore_diacritics();
+                    assert!(expr.set.is_none());
+                    let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                    // case still matters, and 'ø' has no decomposition
+                    let expected: ArrayRef = Arc::new(
+                        [true, true, false, true, false, true]
+                            .iter()
+                            .map(|v| Some(*v != negated))
+                            .chain(std::iter::once(None))
+                            .collect::<BooleanArray>(),
+                    );
+                    assert_eq!(&result, &expected);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_is_unsatisfiable() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let one = lit(ScalarValue::Int64(Some(1)));
+        let null = lit(ScalarValue::Int64(None));
+
+        // a IN (NULL) is never true
+        let expr = InListExpr::new(col_a.clone(), vec![null.clone()], false);
+        assert!(expr.is_unsatisfiable(true));
+        assert!(expr.is_unsatisfiable(false));
+        // unless NULL inputs match it
+        let expr = expr.with_null_handling(NullHandling::NullEqualsNull);
+        assert!(expr.is_unsatisfiable(true));
+        assert!(!expr.is_unsatisfiable(false));
+
+        // an empty positive IN, but not an empty NOT IN
+        assert!(InListExpr::new(col_a.clone(), vec![], false).is_unsatisfiable(false));
+        assert!(!InListExpr::new(col_a.clone(), vec![], true).is_unsatisfiable(false));
+
+        // a NOT IN with a NULL entry is never true
+        let list = vec![one.clone(), null];
+        let expr = InListExpr::new(col_a.clone(), list.clone(), true);
+        assert!(expr.is_unsatisfiable(true));
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        assert!(!expr.is_unsatisfiable(true));
+
+        // an ordinary list
+        for negated in [false, true] {
+            let expr = InListExpr::new(col_a.clone(), vec![one.clone()], negated);
+            assert!(!expr.is_unsatisfiable(true));
+        }
+        // a list depending on the input
+        let expr = InListExpr::new(col_a.clone(), vec![col_a], false);
+        assert!(!expr.is_unsatisfiable(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_set_threshold() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(3), Some(10), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        let list: Vec<_> = (0..10).map(|i| lit(ScalarValue::Int64(Some(i)))).collect();
+
+        assert!(InListExpr::new(col_a.clone(), list.clone(), false)
+            .set
+            .is_none());
+        let expr = InListExpr::new_with_threshold(col_a.clone(), list.clone(), false, 5);
+        assert!(expr.set.is_some());
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(
+            result,
+            &BooleanArray::from(vec![Some(true), Some(false), None])
+        );
+
+        let expr =
+            InListExpr::new(col_a.clone(), list.clone(), false).with_set_threshold(5);
+        assert!(expr.set.is_some());
+        assert!(expr.with_set_threshold(10).set.is_none());
+        let expr = InListExpr::new_lazy(col_a, list, false).with_set_threshold(5);
+        assert!(expr.set.is_some() && expr.lazy_set.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_interval_day_time() -> Result<()> {
+        // days in the high 32 bits and milliseconds in the low 32 bits
+        let one_day = 1_i64 << 32;
+        let one_day_as_millis = 86_400_000_i64;
+        let schema = Schema::new(vec![Field::new(
+            "a",
+            DataType::Interval(IntervalUnit::DayTime),
+            true,
+        )]);
+        let a = IntervalDayTimeArray::from(vec![
+            Some(one_day),
+            Some(one_day_as_millis),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in (INTERVAL '1' DAY)", which does not match 24 hours
+        let list = vec![lit(ScalarValue::IntervalDayTime(Some(one_day)))];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(false), None],
+            col_a.clone()
+        );
+
+        // expression: "a not in (INTERVAL '1' DAY, NULL)"
+        let list = vec![
+            lit(ScalarValue::IntervalDayTime(Some(one_day))),
+            lit(ScalarValue::IntervalDayTime(None)),
+        ];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), None, None],
+            col_a.clone()
+        );
+
+        // the same through a set
+        let list = (0..=OPTIMIZER_INSET_THRESHOLD as i64)
+            .map(|i| lit(ScalarValue::IntervalDayTime(Some(one_day * (i + 1)))))
+            .collect();
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(false), None],
+            col_a
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_evaluate_over_array() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let list = vec![
+            lit(ScalarValue::Utf8(Some("x".to_string()))),
+            lit(ScalarValue::Utf8(Some("y".to_string()))),
+        ];
+        let expr = InListExpr::new(col("b", &schema)?, list, false);
+
+        let b: ArrayRef = Arc::new(StringArray::from(vec![Some("y"), Some("z"), None]));
+        assert_eq!(
+            expr.evaluate_over_array(b)?,
+            BooleanArray::from(vec![Some(true), Some(false), None])
+        );
+
+        let a: ArrayRef = Arc::new(Int64Array::from(vec![1, 2]));
+        assert!(expr.evaluate_over_array(a).is_err());
+        let list = vec![col("a", &schema)?];
+        let expr = InListExpr::new(col("b", &schema)?, list, false);
+        let b: ArrayRef = Arc::new(StringArray::from(vec!["y"]));
+        assert!(expr.evaluate_over_array(b).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_eq() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let list: Vec<_> = (0..10).map(|i| lit(ScalarValue::Int64(Some(i)))).collect();
+
+        let expr = InListExpr::new(col_a.clone(), list.clone(), false);
+        let with_set =
+            InListExpr::new_with_threshold(col_a.clone(), list.clone(), false, 5);
+        assert!(expr.set.is_none() && with_set.set.is_some());
+        assert_eq!(expr, with_set);
+        assert_eq!(HashSet::from([expr.clone(), with_set]).len(), 1);
+
+        assert_ne!(expr, InListExpr::new(col_a.clone(), list.clone(), true));
+        assert_ne!(
+            expr,
+            InListExpr::new(col_a.clone(), list[1..].to_vec(), false)
+        );
+        assert_ne!(
+            expr,
+            InListExpr::new(col_a.clone(), list.clone(), false)
+                .with_null_handling(NullHandling::NullEqualsNull)
+        );
+        let set = |values: &[i64]| {
+            InSet::new(
+                values
+                    .iter()
+                    .map(|v| ScalarValue::Int64(Some(*v)))
+                    .collect(),
+            )
+        };
+        assert_eq!(
+            InListExpr::from_build_side(col_a.clone(), set(&[1, 2]), false),
+            InListExpr::from_build_side(col_a.clone(), set(&[2, 1]), false)
+        );
+        assert_ne!(
+            InListExpr::from_build_side(col_a.clone(), set(&[1, 2]), false),
+            InListExpr::from_build_side(col_a.clone(), set(&[1, 3]), false)
+        );
+
+        // columns compare by name and index, not by how they display
+        let renamed = Arc::new(expressions::Column::new("a", 1));
+        assert_ne!(expr, InListExpr::new(renamed, list.clone(), false));
+        let same = Arc::new(expressions::Column::new("a", 0));
+        assert_eq!(expr, InListExpr::new(same, list.clone(), false));
+        // other expressions compare by their fields and children
+        let cast_a = |t| expressions::cast(col_a.clone(), &schema, t).unwrap();
+        assert_eq!(
+            InListExpr::new(cast_a(DataType::Int32), list.clone(), false),
+            InListExpr::new(cast_a(DataType::Int32), list.clone(), false)
+        );
+        assert_ne!(
+            InListExpr::new(cast_a(DataType::Int32), list.clone(), false),
+            InListExpr::new(cast_a(DataType::Int16), list.clone(), false)
+        );
+        let try_cast_a = |t| expressions::try_cast(col_a.clone(), &schema, t).unwrap();
+        assert_ne!(
+            InListExpr::new(try_cast_a(DataType::Int32), list.clone(), false),
+            InListExpr::new(cast_a(DataType::Int32), list.clone(), false)
+        );
+        let plus = |op, v| {
+            expressions::binary(
+                col_a.clone(),
+                op,
+                lit(ScalarValue::Int64(Some(v))),
+                &schema,
+            )
+            .unwrap()
+        };
+        let case = |op, v| {
+            let then = lit(ScalarValue::Int64(Some(0)));
+            expressions::case(None, &[(plus(op, v), then)], Some(col_a.clone())).unwrap()
+        };
+        let expr = InListExpr::new(plus(Operator::Plus, 1), list.clone(), false);
+        assert_eq!(
+            expr,
+            InListExpr::new(plus(Operator::Plus, 1), list.clone(), false)
+        );
+        assert_ne!(
+            expr,
+            InListExpr::new(plus(Operator::Minus, 1), list.clone(), false)
+        );
+        assert_ne!(
+            expr,
+            InListExpr::new(plus(Operator::Plus, 2), list.clone(), false)
+        );
+        let exprs = [
+            InListExpr::new(case(Operator::Eq, 1), list.clone(), false),
+            InListExpr::new(case(Operator::Eq, 1), list.clone(), false),
+            InListExpr::new(case(Operator::Eq, 2), list.clone(), false),
+            InListExpr::new(case(Operator::Lt, 1), list, false),
+        ];
+        assert_eq!(exprs[0], exprs[1]);
+        assert_eq!(HashSet::from(exprs).len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_with_new_children() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let a = Int32Array::from(vec![Some(1), Some(4), None]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(a)])?;
+
+        // expression: "a in (1, 2)"
+        let list = vec![
+            lit(ScalarValue::Int32(Some(1))),
+            lit(ScalarValue::Int32(Some(2))),
+        ];
+        let expr: Arc<dyn PhysicalExpr> =
+            Arc::new(InListExpr::new(col("a", &schema)?, list, false));
+        assert_eq!(expr.children().len(), 3);
+
+        // expression: "CAST(a AS Int64) in (CAST(1 AS Int64), CAST(2 AS Int64))"
+        let children = expr
+            .children()
+            .into_iter()
+            .map(|child| expressions::cast(child, &schema, DataType::Int64))
+            .collect::<Result<Vec<_>>>()?;
+        let rebuilt = expr.clone().with_new_children(children)?;
+        let result = rebuilt.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(
+            result,
+            &BooleanArray::from(vec![Some(true), Some(false), None])
+        );
+
+        // an expression without a set keeps probing the list
+        let list = (0..=OPTIMIZER_INSET_THRESHOLD as i32)
+            .map(|i| lit(ScalarValue::Int32(Some(i))))
+            .collect();
+        let expr: Arc<dyn PhysicalExpr> =
+            Arc::new(InListExpr::new_without_set(col("a", &schema)?, list, false));
+        let rebuilt = expr.clone().with_new_children(expr.children())?;
+        let rebuilt = rebuilt.as_any().downcast_ref::<InListExpr>().unwrap();
+        assert!(rebuilt.set.is_none());
+
+        assert!(matches!(
+            expr.with_new_children(vec![col("a", &schema)?]),
+            Err(DataFusionError::Internal(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_max_literal_bytes() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let col_a = col("a", &schema)?;
+        let list = |values: &[&str]| {
+            values
+                .iter()
+                .map(|v| lit(ScalarValue::Utf8(Some(v.to_string()))))
+                .collect::<Vec<_>>()
+        };
+
+        let expr = InListExpr::new(col_a.clone(), list(&["abc", "de"]), false);
+        assert!(expr.with_max_literal_bytes(3).is_ok());
+        let expr = InListExpr::new(col_a.clone(), list(&["abc", "defg"]), false);
+        let err = expr.with_max_literal_bytes(3).unwrap_err();
+        assert!(err.to_string().contains("4 bytes exceeds the maximum of 3"));
+
+        let expr: Arc<dyn PhysicalExpr> = Arc::new(
+            InListExpr::new(col_a.clone(), list(&["abc"]), false)
+                .with_max_literal_bytes(3)?,
+        );
+        let children = vec![col_a, list(&["defg"]).remove(0)];
+        assert!(expr.with_new_children(children).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_to_build_batch() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(1), Some(2), Some(3), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in (1, 3, 1)" and "a in (1, 3, NULL)"
+        for last in [Some(1), None] {
+            let list = vec![
+                lit(ScalarValue::Int64(Some(1))),
+                lit(ScalarValue::Int64(Some(3))),
+                lit(ScalarValue::Int64(last)),
+            ];
+            let expr = InListExpr::new(col_a.clone(), list, false);
+            let build = expr.to_build_batch("k")?;
+            assert_eq!(build.schema().field(0).name(), "k");
+            assert_eq!(build.num_rows(), 2 + last.is_none() as usize);
+
+            let column = build.column(0);
+            let set = (0..column.len())
+                .map(|i| ScalarValue::try_from_array(column, i))
+                .collect::<Result<HashSet<_, RandomState>>>()?;
+            let round_trip =
+                InListExpr::from_build_side(col_a.clone(), InSet::new(set), false);
+            let expected = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            let result = round_trip.evaluate(&batch)?.into_array(batch.num_rows());
+            assert_eq!(&expected, &result);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_caches_list_values() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let batch = |values: Vec<Option<i64>>| {
+            RecordBatch::try_new(
+                Arc::new(schema.clone()),
+                vec![Arc::new(Int64Array::from(values))],
+            )
+        };
+        // expression: "a in (1, CAST(2 AS Int64))"
+        let list = vec![
+            lit(ScalarValue::Int64(Some(1))),
+            expressions::cast(
+                lit(ScalarValue::Int32(Some(2))),
+                &schema,
+                DataType::Int64,
+            )?,
+        ];
+        let expr = InListExpr::new(col_a, list, false);
+        assert!(expr.list_values.get().is_none());
+
+        let evaluate = |expr: &InListExpr, values| -> Result<ArrayRef> {
+            let batch = batch(values)?;
+            Ok(expr.evaluate(&batch)?.into_array(batch.num_rows()))
+        };
+        let result = evaluate(&expr, vec![Some(1), Some(3)])?;
+        assert_eq!(
+            result.as_any().downcast_ref::<BooleanArray>().unwrap(),
+            &BooleanArray::from(vec![Some(true), Some(false)])
+        );
+        assert_eq!(
+            expr.list_values.get(),
+            Some(&Some(vec![
+                ScalarValue::Int64(Some(1)),
+                ScalarValue::Int64(Some(2))
+            ]))
+        );
+        let result = evaluate(&expr, vec![Some(2), None])?;
+        assert_eq!(
+            result.as_any().downcast_ref::<BooleanArray>().unwrap(),
+            &BooleanArray::from(vec![Some(true), None])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_try_simplify_to_binary() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(vec![Some(5), Some(6), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(a)])?;
+
+        // expression: "a in (5)" and "a not in (5)"
+        for negated in [false, true] {
+            let expr = InListExpr::new(
+                col_a.clone(),
+                vec![lit(ScalarValue::Int64(Some(5)))],
+                negated,
+            );
+            let binary = expr.try_simplify_to_binary(&schema).unwrap();
+            assert_eq!(
+                binary.to_string(),
+                format!("a@0 {} 5", if negated { "!=" } else { "=" })
+            );
+            assert_eq!(
+                &binary.evaluate(&batch)?.into_array(batch.num_rows()),
+                &expr.evaluate(&batch)?.into_array(batch.num_rows())
+            );
+        }
+
+        // expression: "a in (NULL)"
+        let expr =
+            InListExpr::new(col_a.clone(), vec![lit(ScalarValue::Int64(None))], false);
+        assert!(expr.try_simplify_to_binary(&schema).is_none());
+        let list = vec![
+            lit(ScalarValue::Int64(Some(5))),
+            lit(ScalarValue::Int64(Some(6))),
+        ];
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        assert!(expr.try_simplify_to_binary(&schema).is_none());
+        let expr =
+            InListExpr::new(col_a.clone(), vec![lit(ScalarValue::Int64(Some(5)))], false)
+                .with_null_handling(NullHandling::NullEqualsNull);
+        assert!(expr.try_simplify_to_binary(&schema).is_none());
+
+        // expression: "a in (5)" with an Int32 literal, which the IN list
+        // compares but a binary expression would not
+        let expr = InListExpr::new(col_a, vec![lit(ScalarValue::Int32(Some(5)))], false);
+        assert!(expr.try_simplify_to_binary(&schema).is_none());
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(
+            result,
+            &BooleanArray::from(vec![Some(true), Some(false), None])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_set_falls_back_to_list() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Null, true)]);
+        let a = NullArray::new(3);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        assert!(!set_supports(&DataType::Null));
+
+        // expression: "a in (NULL, NULL, ...)", long enough for a set
+        let list = vec![lit(ScalarValue::Null); OPTIMIZER_INSET_THRESHOLD + 1];
+        for expr in [
+            InListExpr::new(col_a.clone(), list.clone(), false),
+            InListExpr::new_lazy(col_a.clone(), list.clone(), false),
+        ] {
+            assert!(expr.set.is_some() || expr.lazy_set.is_some());
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            assert_eq!(result.data_type(), &DataType::Boolean);
+            assert_eq!(result.null_count(), 3);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_binary_byte_order() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Binary, true)]);
+        // 258 stored little-endian and big-endian
+        let le = 258u16.to_le_bytes();
+        let be = 258u16.to_be_bytes();
+        let a = BinaryArray::from(vec![Some(&le[..]), Some(&be[..])]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in (X'0201')"
+        let list = vec![lit(ScalarValue::Binary(Some(le.to_vec())))];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(false)],
+            col_a.clone()
+        );
+
+        // expression: "a in (X'0102')"
+        let list = vec![lit(ScalarValue::Binary(Some(be.to_vec())))];
+        in_list!(batch, list, &false, vec![Some(false), Some(true)], col_a);
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_dictionary_value_types() -> Result<()> {
+        // compare the result over a dictionary of `values` with the decoded one
+        fn check(values: ArrayRef, literal: fn(usize) -> ScalarValue) -> Result<()> {
+            let keys = (0..1000)
+                .map(|i| (i % 11 != 10).then(|| (i % 11) as i8))
+                .collect::<Int8Array>();
+            let a: ArrayRef =
+                Arc::new(DictionaryArray::<Int8Type>::try_new(&keys, &values)?);
+            let schema = Schema::new(vec![Field::new("a", a.data_type().clone(), true)]);
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![a.clone()])?;
+            let plain = kernels::cast::cast(&a, values.data_type())?;
+            let plain_schema =
+                Schema::new(vec![Field::new("a", values.data_type().clone(), true)]);
+            let plain_batch = RecordBatch::try_new(Arc::new(plain_schema), vec![plain])?;
+
+            // list path, then set path, with values missing from the dictionary
+            for n in [4, 40] {
+                let list = (0..n).map(|i| lit(literal(i * 3))).collect::<Vec<_>>();
+                for negated in [false, true] {
+                    let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+                    let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                    let expected = expr
+                        .evaluate(&plain_batch)?
+                        .into_array(plain_batch.num_rows());
+                    assert_eq!(&result, &expected);
+                    let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+                    assert_eq!(result.value(3), !negated);
+                }
+            }
+            Ok(())
+        }
+
+        check(
+            Arc::new(LargeStringArray::from_iter_values(
+                (0..10).map(|i| format!("value_{}", i)),
+            )),
+            |i| ScalarValue::LargeUtf8(Some(format!("value_{}", i))),
+        )?;
+        check(
+            Arc::new(Int64Array::from_iter_values((0..10).map(|i| i * 100))),
+            |i| ScalarValue::Int64(Some(i as i64 * 100)),
+        )
+    }
+
+    #[test]
+    fn in_list_float_nan() -> Result<()> {
+        for data_type in [DataType::Float64, DataType::Float32] {
+            let values = vec![Some(f64::NAN), Some(0.0), Some(-0.0), Some(1.0), None];
+            let a: ArrayRef = Arc::new(Float64Array::from(values));
+            let a = kernels::cast::cast(&a, &data_type)?;
+            let schema = Schema::new(vec![Field::new("a", data_type.clone(), true)]);
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![a])?;
+            let float = |v: Option<f64>| match data_type {
+                DataType::Float32 => lit(ScalarValue::Float32(v.map(|v| v as f32))),
+                _ => lit(ScalarValue::Float64(v)),
+            };
+            // the list, then padded so that it uses a set
+            let lists = |list: Vec<Arc<dyn PhysicalExpr>>| {
+                let padding =
+                    (0..OPTIMIZER_INSET_THRESHOLD).map(|i| float(Some(i as f64 + 2.0)));
+                let padded = list.iter().cloned().chain(padding).collect();
+                [list, padded]
+            };
+
+            // expression: "a in (NaN)"
+            for list in lists(vec![float(Some(f64::NAN))]) {
+                let expected =
+                    vec![Some(true), Some(false), Some(false), Some(false), None];
+                in_list!(batch, list.clone(), &false, expected, col_a.clone());
+                let expected =
+                    vec![Some(false), Some(true), Some(true), Some(true), None];
+                in_list!(batch, list, &true, expected, col_a.clone());
+            }
+            // expression: "a in (-0.0)", which equals 0.0
+            for list in lists(vec![float(Some(-0.0))]) {
+                let expected =
+                    vec![Some(false), Some(true), Some(true), Some(false), None];
+                in_list!(batch, list, &false, expected, col_a.clone());
+            }
+            // expression: "a not in (NaN, 1.0, NULL)"
+            let list = vec![float(Some(f64::NAN)), float(Some(1.0)), float(None)];
+            let expected = vec![Some(false), None, None, Some(false), None];
+            in_list!(batch, list, &true, expected, col_a);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_dictionary_output_is_flat() -> Result<()> {
+        let field_type =
+            DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8));
+        let schema = Schema::new(vec![Field::new("a", field_type, true)]);
+        let a = (0..4096)
+            .map(|i| ["red", "green", "blue"][i % 3])
+            .collect::<DictionaryArray<Int8Type>>();
+        let keys_size = a.keys().get_array_memory_size();
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in ('red', 'green')"
+        let list = vec![
+            lit(ScalarValue::Utf8(Some("red".to_string()))),
+            lit(ScalarValue::Utf8(Some("green".to_string()))),
+        ];
+        let result = in_list(col_a, list, &false)?
+            .evaluate(&batch)?
+            .into_array(batch.num_rows());
+        assert_eq!(result.data_type(), &DataType::Boolean);
+        // smaller than the keys alone of a dictionary encoded result
+        assert!(result.get_array_memory_size() < keys_size);
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_validate_bound_set() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::LargeUtf8, true),
+        ]);
+        let col_a = col("a", &schema)?;
+        let col_b = col("b", &schema)?;
+        let validity = Buffer::from([0b11]);
+
+        let ints: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), None]));
+        let strings: ArrayRef = Arc::new(StringArray::from(vec!["x", "y"]));
+        let expr = InListExpr::from_values_with_validity(
+            col_a.clone(),
+            &ints,
+            &validity,
+            false,
+        )?;
+        assert!(expr.validate_bound_set(&schema).is_ok());
+        // Utf8 values compare with a LargeUtf8 column
+        let expr =
+            InListExpr::from_values_with_validity(col_b, &strings, &validity, false)?;
+        assert!(expr.validate_bound_set(&schema).is_ok());
+        let expr = InListExpr::from_values_with_validity(
+            col_a.clone(),
+            &strings,
+            &validity,
+            false,
+        )?;
+        let err = expr.validate_bound_set(&schema).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("type Utf8 cannot be compared with a@0 of type Int64"));
+
+        let dynamic_set = DynamicInSet::default();
+        let expr = InListExpr::new_with_dynamic_set(col_a, dynamic_set.clone(), false);
+        assert!(expr.validate_bound_set(&schema).is_ok());
+        dynamic_set.update(HashSet::from([ScalarValue::Int32(Some(1))]));
+        assert!(expr.validate_bound_set(&schema).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_coerces_numeric_literals() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let a = Int32Array::from(vec![Some(1), Some(3), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        let int64 = |v: Option<i64>| lit(ScalarValue::Int64(v));
+
+        // expression: "a in (1, 5000000000)" with Int64 literals, out of range
+        // of Int32 for the second, then padded to use a set
+        let list = vec![int64(Some(1)), int64(Some(5_000_000_000))];
+        let padded = list
+            .iter()
+            .cloned()
+            .chain((0..OPTIMIZER_INSET_THRESHOLD as i64).map(|i| int64(Some(100 + i))))
+            .collect::<Vec<_>>();
+        for list in [list, padded] {
+            in_list!(
+                batch,
+                list.clone(),
+                &false,
+                vec![Some(true), Some(false), None],
+                col_a.clone()
+            );
+            in_list!(
+                batch,
+                list,
+                &true,
+                vec![Some(false), Some(true), None],
+                col_a.clone()
+            );
+        }
+
+        // the literals are cast once, by the first batch
+        let expr = InListExpr::new(
+            col_a.clone(),
+            vec![int64(Some(1)), int64(Some(5_000_000_000))],
+            false,
+        );
+        expr.evaluate(&batch)?;
+        let coerced = expr.coerced_list_values.get().unwrap();
+        assert_eq!(
+            coerced,
+            &(DataType::Int32, vec![ScalarValue::Int32(Some(1))])
+        );
+
+        // expression: "a not in (1, NULL)" with an Int64 NULL
+        let list = vec![int64(Some(1)), int64(None)];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), None, None],
+            col_a.clone()
+        );
+
+        // a literal that cannot be cast is an error rather than a panic
+        let list = vec![lit(ScalarValue::Binary(Some(vec![1])))];
+        let expr = InListExpr::new(col_a, list, false);
+        assert!(matches!(
+            expr.evaluate(&batch),
+            Err(DataFusionError::Plan(_))
+        ));
+
+        // expression: "b in (0.5, 0.1)" with Float64 literals for a Float32 column,
+        // where 0.1 cannot be represented
+        let schema = Schema::new(vec![Field::new("b", DataType::Float32, true)]);
+        let b = Float32Array::from(vec![Some(0.5), Some(0.1), None]);
+        let col_b = col("b", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(b)])?;
+        let list = vec![
+            lit(ScalarValue::Float64(Some(0.5))),
+            lit(ScalarValue::Float64(Some(0.1))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![Some(true), Some(false), None],
+            col_b
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_mismatched_literal_is_error() -> Result<()> {
+        fn check(array: ArrayRef, literal: ScalarValue) -> Result<()> {
+            let schema =
+                Schema::new(vec![Field::new("a", array.data_type().clone(), true)]);
+            let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![array])?;
+            let expr = InListExpr::new(col("a", &schema)?, vec![lit(literal)], false);
+            assert!(matches!(
+                expr.evaluate(&batch),
+                Err(DataFusionError::Internal(_))
+            ));
+            Ok(())
+        }
+
+        check(
+            Arc::new(BooleanArray::from(vec![Some(true), None])),
+            ScalarValue::Utf8(Some("a".to_string())),
+        )?;
+        check(
+            Arc::new(StringArray::from(vec![Some("a"), None])),
+            ScalarValue::Int64(Some(1)),
+        )
+    }
+
+    #[test]
+    fn in_list_sorted_float64() -> Result<()> {
+        let probes = vec![
+            Some(f64::NAN),
+            Some(-f64::NAN),
+            Some(0.0),
+            Some(-0.0),
+            Some(1.5),
+            Some(2.0),
+            Some(f64::INFINITY),
+            Some(f64::NEG_INFINITY),
+            Some(f64::MIN_POSITIVE / 2.0),
+            Some(-7.25),
+            None,
+        ];
+        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+        let a = Float64Array::from(probes.clone());
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let sorted = (0..20).map(|i| i as f64 * 0.5 - 4.0).collect::<Vec<_>>();
+        let mut unsorted = sorted.clone();
+        unsorted.reverse();
+        unsorted.extend([-0.0, f64::NAN, f64::INFINITY]);
+        for values in [sorted, unsorted] {
+            for with_null in [false, true] {
+                let mut list = values
+                    .iter()
+                    .map(|v| lit(ScalarValue::Float64(Some(*v))))
+                    .collect::<Vec<_>>();
+                if with_null {
+                    list.push(lit(ScalarValue::Float64(None)));
+                }
+                for negated in [false, true] {
+                    // `ScalarValue` equality, where NaN equals NaN
+                    let expected = probes
+                        .iter()
+                        .map(|p| {
+                            let p = ScalarValue::Float64(Some((*p)?));
+                            let found = values
+                                .iter()
+                                .any(|v| ScalarValue::Float64(Some(*v)) == p);
+                            match found {
+                                true => Some(!negated),
+                                false if with_null => None,
+                                false => Some(negated),
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    in_list!(batch, list.clone(), &negated, expected, col_a.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_int64_hash_set() -> Result<()> {
+        // spread over more than a bitset span, so hashed whatever the hasher
+        let values = (0..100i64)
+            .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15u64 as i64))
+            .collect::<Vec<_>>();
+        let mut probes = values
+            .iter()
+            .step_by(3)
+            .copied()
+            .map(Some)
+            .collect::<Vec<_>>();
+        probes.extend((0..50).map(|i| Some(i * 1_000_003 + 1)));
+        probes.extend([None, Some(i64::MIN), Some(i64::MAX)]);
+
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let a = Int64Array::from(probes.clone());
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        for with_null in [false, true] {
+            let mut list = values
+                .iter()
+                .map(|v| lit(ScalarValue::Int64(Some(*v))))
+                .collect::<Vec<_>>();
+            if with_null {
+                list.push(lit(ScalarValue::Int64(None)));
+            }
+            let expr = InListExpr::new(col_a.clone(), list.clone(), false);
+            assert!(matches!(
+                expr.set.as_ref().unwrap().native,
+                Some(NativeSet::Int64Hash(_))
+            ));
+            for negated in [false, true] {
+                let expected = probes
+                    .iter()
+                    .map(|p| match values.contains(&(*p)?) {
+                        true => Some(!negated),
+                        false if with_null => None,
+                        false => Some(negated),
+                    })
+                    .collect::<Vec<_>>();
+                in_list!(batch, list.clone(), &negated, expected, col_a.clone());
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_array_entries() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Int64, true),
+            Field::new("c", DataType::Int32, true),
+        ]);
+        let a = Int64Array::from(vec![Some(1), Some(2), Some(3), Some(4), None, Some(6)]);
+        let b = Int64Array::from(vec![Some(1), Some(0), None, Some(0), Some(5), Some(0)]);
+        let c = Int32Array::from(vec![Some(0), Some(2), Some(0), None, Some(5), Some(0)]);
+        let col_a = col("a", &schema)?;
+        let col_b = col("b", &schema)?;
+        let col_c = col("c", &schema)?;
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(a), Arc::new(b), Arc::new(c)],
+        )?;
+
+        // expression: "a in (b, c)"
+        let list = vec![col_b.clone(), col_c.clone()];
+        in_list!(
+            batch,
+            list.clone(),
+            &false,
+            vec![Some(true), Some(true), None, None, None, Some(false)],
+            col_a.clone()
+        );
+        // expression: "a not in (b, c)"
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), Some(false), None, None, None, Some(true)],
+            col_a.clone()
+        );
+
+        // expression: "a in (b, c, 3, 4)"
+        let list = vec![
+            col_b.clone(),
+            col_c.clone(),
+            lit(ScalarValue::Int64(Some(3))),
+            lit(ScalarValue::Int64(Some(4))),
+        ];
+        in_list!(
+            batch,
+            list,
+            &false,
+            vec![
+                Some(true),
+                Some(true),
+                Some(true),
+                Some(true),
+                None,
+                Some(false)
+            ],
+            col_a.clone()
+        );
+
+        // expression: "a not in (b, NULL)"
+        let list = vec![col_b, lit(ScalarValue::Int64(None))];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), None, None, None, None, None],
+            col_a.clone()
+        );
+
+        // an entry of the wrong length is an error
+        let expr = InListExpr::new(col_a, vec![col_c], false);
+        let short: ArrayRef = Arc::new(Int32Array::from(vec![1, 2]));
+        let result = expr
+            .evaluate_list(batch.column(0).clone(), vec![ColumnarValue::Array(short)]);
+        assert!(matches!(result, Err(DataFusionError::Internal(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_sliced_dictionary() -> Result<()> {
+        let values = StringArray::from(vec![Some("a"), None, Some("c"), Some("d")]);
+        // null keys hold values out of bounds, or negative
+        let keys = ArrayData::builder(DataType::Int8)
+            .len(8)
+            .add_buffer(Buffer::from_slice_ref(&[0i8, 100, 1, 2, -1, 3, 0, 2]))
+            .null_bit_buffer(Some(Buffer::from([0b11101101u8])))
+            .build()?;
+        let data_type =
+            DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8));
+        let dict = ArrayData::builder(data_type.clone())
+            .len(8)
+            .add_buffer(keys.buffers()[0].clone())
+            .null_bit_buffer(keys.null_buffer().cloned())
+            .add_child_data(values.data().clone())
+            .build()?;
+        let dict = make_array(dict).slice(1, 6);
+        let decoded = kernels::cast::cast(&dict, &DataType::Utf8)?;
+
+        let utf8 = |v: &str| lit(ScalarValue::Utf8(Some(v.to_string())));
+        let list = vec![utf8("a"), utf8("c")];
+        let padded = list
+            .iter()
+            .cloned()
+            .chain((0..OPTIMIZER_INSET_THRESHOLD).map(|i| utf8(&format!("z{}", i))))
+            .collect::<Vec<_>>();
+        let mut with_null = list.clone();
+        with_null.push(lit(ScalarValue::Utf8(None)));
+        for list in [list, padded, with_null] {
+            for negated in [false, true] {
+                let evaluate = |array: &ArrayRef| -> Result<ArrayRef> {
+                    let schema = Schema::new(vec![Field::new(
+                        "a",
+                        array.data_type().clone(),
+                        true,
+                    )]);
+                    let batch = RecordBatch::try_new(
+                        Arc::new(schema.clone()),
+                        vec![array.clone()],
+                    )?;
+                    let expr = InListExpr::new(col("a", &schema)?, list.clone(), negated);
+                    Ok(expr.evaluate(&batch)?.into_array(batch.num_rows()))
+                };
+                let result = evaluate(&dict)?;
+                assert_eq!(result.len(), 6);
+                assert_eq!(&result, &evaluate(&decoded)?);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_value_bounds() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let int64 = |v: Option<i64>| lit(ScalarValue::Int64(v));
+        let list = vec![
+            int64(Some(7)),
+            int64(None),
+            int64(Some(-3)),
+            int64(Some(12)),
+        ];
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        assert_eq!(
+            expr.value_bounds(),
+            Some((ScalarValue::Int64(Some(-3)), ScalarValue::Int64(Some(12))))
+        );
+        // the same on a second call, from the cache
+        assert_eq!(
+            expr.value_bounds(),
+            Some((ScalarValue::Int64(Some(-3)), ScalarValue::Int64(Some(12))))
+        );
+
+        let utf8 = |v: &str| lit(ScalarValue::Utf8(Some(v.to_string())));
+        let list = vec![utf8("pear"), utf8("apple"), utf8("zucchini"), utf8("fig")];
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        assert_eq!(
+            expr.value_bounds(),
+            Some((
+                ScalarValue::Utf8(Some("apple".to_string())),
+                ScalarValue::Utf8(Some("zucchini".to_string()))
+            ))
+        );
+
+        // empty, all NULL, not orderable or not constant
+        for list in [
+            vec![],
+            vec![int64(None)],
+            vec![int64(Some(1)), utf8("a")],
+            vec![lit(ScalarValue::Float64(Some(f64::NAN)))],
+            vec![int64(Some(1)), col_a.clone()],
+        ] {
+            let expr = InListExpr::new(col_a.clone(), list, false);
+            assert_eq!(expr.value_bounds(), None);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_set_accessor() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let values = (0..40)
+            .map(|i| ScalarValue::Int64(Some(i * 3)))
+            .chain([ScalarValue::Int64(None)])
+            .collect::<Vec<_>>();
+        let list = values.iter().cloned().map(lit).collect::<Vec<_>>();
+
+        let expr = InListExpr::new(col_a.clone(), list.clone(), true);
+        let set = expr.set().unwrap();
+        assert_eq!(
+            set.get_set(),
+            &values.iter().cloned().collect::<HashSet<_, RandomState>>()
+        );
+        assert!(expr.negated());
+
+        assert!(InListExpr::new(col_a.clone(), list[..3].to_vec(), false)
+            .set()
+            .is_none());
+        // a lazy set once built
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(Int64Array::from(vec![Some(3)]))],
+        )?;
+        let expr = InListExpr::new_lazy(col_a, list, false);
+        assert!(expr.set().is_none());
+        expr.evaluate(&batch)?;
+        assert_eq!(expr.set().unwrap().get_set().len(), 41);
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_bloom_filter() -> Result<()> {
+        let value = |i: usize| format!("customer_{:08}", i * 7);
+        let mut set = (0..20_000)
+            .map(|i| ScalarValue::Utf8(Some(value(i))))
+            .collect::<HashSet<_, RandomState>>();
+        let probes = (0..3000)
+            .map(|i| (i % 5 != 0).then(|| format!("customer_{:08}", i * 3)))
+            .collect::<Vec<_>>();
+        assert!(InSet::new(set.clone()).bloom.is_some());
+        assert!(InSet::new(set.clone())
+            .with_bloom_threshold(usize::MAX)
+            .bloom
+            .is_none());
+        // short strings are packed instead
+        let short = (0..20_000)
+            .map(|i| ScalarValue::Utf8(Some(i.to_string())))
+            .collect::<HashSet<_, RandomState>>();
+        assert!(InSet::new(short).bloom.is_none());
+
+        for with_null in [false, true] {
+            if with_null {
+                set.insert(ScalarValue::Utf8(None));
+            }
+            let arrays: Vec<ArrayRef> = vec![
+                Arc::new(probes.iter().collect::<StringArray>()),
+                Arc::new(probes.iter().collect::<LargeStringArray>()),
+            ];
+            for array in arrays {
+                let schema =
+                    Schema::new(vec![Field::new("a", array.data_type().clone(), true)]);
+                let col_a = col("a", &schema)?;
+                let batch = RecordBatch::try_new(Arc::new(schema), vec![array])?;
+                for negated in [false, true] {
+                    let evaluate = |in_set: InSet| -> Result<ArrayRef> {
+                        let expr =
+                            InListExpr::from_build_side(col_a.clone(), in_set, negated);
+                        Ok(expr.evaluate(&batch)?.into_array(batch.num_rows()))
+                    };
+                    let filtered = evaluate(InSet::new(set.clone()))?;
+                    let exact = evaluate(
+                        InSet::new(set.clone()).with_bloom_threshold(usize::MAX),
+                    )?;
+                    assert_eq!(&filtered, &exact);
+                    let filtered =
+                        filtered.as_any().downcast_ref::<BooleanArray>().unwrap();
+                    let hits = (0..filtered.len())
+                        .filter(|i| {
+                            filtered.is_valid(*i) && filtered.value(*i) != negated
+                        })
+                        .count();
+                    // every 7th probe is in the set, but not those that are NULL
+                    assert_eq!(
+                        hits,
+                        (0..3000).filter(|i| i % 7 == 0 && i % 5 != 0).count()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_denylist() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let a = StringArray::from(vec![
+            Some("ok"),
+            Some("spam"),
+            None,
+            Some("phish"),
+            Some("fine"),
+            Some("spam"),
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        let utf8 = |v: &str| lit(ScalarValue::Utf8(Some(v.to_string())));
+        let list = vec![utf8("malware"), utf8("spam"), utf8("phish")];
+
+        let expr = InListExpr::new(col_a.clone(), list.clone(), true);
+        let (allowed, index) = expr.evaluate_denylist(&batch)?;
+        assert_eq!(
+            allowed,
+            BooleanArray::from(vec![
+                Some(true),
+                Some(false),
+                None,
+                Some(false),
+                Some(true),
+                Some(false)
+            ])
+        );
+        assert_eq!(
+            index,
+            Int32Array::from(vec![None, Some(1), None, Some(2), None, Some(1)])
+        );
+
+        // only for NOT IN over strings
+        let expr = InListExpr::new(col_a, list, false);
+        assert!(matches!(
+            expr.evaluate_denylist(&batch),
+            Err(DataFusionError::Plan(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_dictionary_against_dictionary() -> Result<()> {
+        let dictionary = |values: Vec<Option<&str>>, keys: Vec<Option<i8>>| -> ArrayRef {
+            let values = StringArray::from(values);
+            Arc::new(
+                DictionaryArray::<Int8Type>::try_new(&Int8Array::from(keys), &values)
+                    .unwrap(),
+            )
+        };
+        // "x", "y", NULL, "z", "y", "w"
+        let a = dictionary(
+            vec![Some("x"), Some("y"), Some("z"), Some("w")],
+            vec![Some(0), Some(1), None, Some(2), Some(1), Some(3)],
+        );
+        // "z", "y", NULL, "v", "z", "y" from another dictionary, with a NULL
+        // value rather than key
+        let b = dictionary(
+            vec![Some("v"), None, Some("y"), Some("z")],
+            vec![Some(3), Some(2), Some(1), Some(0), Some(3), Some(2)],
+        );
+        let schema = Schema::new(vec![
+            Field::new("a", a.data_type().clone(), true),
+            Field::new("b", b.data_type().clone(), true),
+        ]);
+        let col_a = col("a", &schema)?;
+        let col_b = col("b", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![a, b.clone()])?;
+
+        // a set of the values of `b`, without the NULL
+        let mut builder = InSetBuilder::new();
+        builder.append(&b)?;
+        assert_eq!(builder.len(), 3);
+        let expr = InListExpr::from_build_side(col_a.clone(), builder.finish(), false);
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(
+            result,
+            &BooleanArray::from(vec![
+                Some(false),
+                Some(true),
+                None,
+                Some(true),
+                Some(true),
+                Some(false)
+            ])
+        );
+
+        // expression: "a in (b)", row by row
+        in_list!(
+            batch,
+            vec![col_b],
+            &false,
+            vec![
+                Some(false),
+                Some(true),
+                None,
+                Some(false),
+                Some(false),
+                Some(false)
+            ],
+            col_a
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_native_primitive_sets() -> Result<()> {
+        let arrays: Vec<(ArrayRef, Vec<ScalarValue>)> = vec![
+            (
+                Arc::new(Int8Array::from_iter((-60..60).map(Some).chain([None]))),
+                (-40..0).map(|v| ScalarValue::Int8(Some(v * 3))).collect(),
+            ),
+            (
+                Arc::new(UInt32Array::from_iter((0..120).map(Some).chain([None]))),
+                (0..40).map(|v| ScalarValue::UInt32(Some(v * 7))).collect(),
+            ),
+            (
+                Arc::new(Float32Array::from_iter(
+                    [f32::NAN, -0.0, 0.0, 1.5, 2.5, f32::INFINITY]
+                        .into_iter()
+                        .map(Some)
+                        .chain([None]),
+                )),
+                (0..40)
+                    .map(|v| ScalarValue::Float32(Some(v as f32 * 0.5)))
+                    .chain([ScalarValue::Float32(Some(f32::NAN))])
+                    .collect(),
+            ),
+            (
+                Arc::new(StringArray::from_iter(
+                    (0..120).map(|i| Some(format!("long customer name {}", i))),
+                )),
+                (0..40)
+                    .map(|i| {
+                        ScalarValue::Utf8(Some(format!("long customer name {}", i * 2)))
+                    })
+                    .collect(),
+            ),
+            (
+                Arc::new(BooleanArray::from(vec![Some(true), Some(false), None])),
+                vec![ScalarValue::Boolean(Some(false)); 40],
+            ),
+            // a set of both offset sizes probes either
+            (
+                Arc::new(LargeStringArray::from_iter(
+                    (0..120).map(|i| Some(format!("long customer name {}", i))),
+                )),
+                (0..40)
+                    .map(|i| {
+                        let v = Some(format!("long customer name {}", i * 2));
+                        if i % 2 == 0 {
+                            ScalarValue::LargeUtf8(v)
+                        } else {
+                            ScalarValue::Utf8(v)
+                        }
+                    })
+                    .collect(),
+            ),
+            (
+                Arc::new(BinaryArray::from_iter(
+                    (0..120u8).map(|i| Some(vec![i, i])).chain([None]),
+                )),
+                (0..40u8)
+                    .map(|i| ScalarValue::Binary(Some(vec![i * 2, i * 2])))
+                    .collect(),
+            ),
+        ];
+
+        for (array, values) in arrays {
+            let schema =
+                Schema::new(vec![Field::new("a", array.data_type().clone(), true)]);
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![array.clone()])?;
+            for with_null in [false, true] {
+                let mut list = values.iter().cloned().map(lit).collect::<Vec<_>>();
+                if with_null {
+                    let null = match &values[0] {
+                        ScalarValue::Binary(_) => ScalarValue::Binary(None),
+                        v => ScalarValue::try_from(&v.get_datatype())?,
+                    };
+                    list.push(lit(null));
+                }
+                for negated in [false, true] {
+                    let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+                    let in_set = expr.set.as_ref().unwrap();
+                    assert!(matches!(
+                        in_set.native,
+                        Some(
+                            NativeSet::Primitive(_)
+                                | NativeSet::Strings(_)
+                                | NativeSet::Binaries(_)
+                        )
+                    ));
+                    let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                    assert_eq!(&result, &evaluate_linear(&expr, &batch)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_range_folding() -> Result<()> {
+        let cases: Vec<(ArrayRef, Vec<ScalarValue>)> = vec![
+            (
+                Arc::new(Int64Array::from(vec![
+                    Some(i64::MIN),
+                    Some(-11),
+                    Some(-10),
+                    Some(0),
+                    Some(10),
+                    Some(11),
+                    Some(i64::MAX),
+                    None,
+                ])),
+                (-10..=10)
+                    .rev()
+                    .chain([0, 5])
+                    .map(|v| ScalarValue::Int64(Some(v)))
+                    .collect(),
+            ),
+            (
+                Arc::new(UInt8Array::from(vec![
+                    Some(0),
+                    Some(1),
+                    Some(254),
+                    Some(255),
+                ])),
+                (1..=255).map(|v| ScalarValue::UInt8(Some(v))).collect(),
+            ),
+            (
+                Arc::new(Int32Array::from(vec![Some(6), Some(7), Some(8), None])),
+                vec![ScalarValue::Int32(Some(7))],
+            ),
+        ];
+        for (array, values) in cases {
+            let schema =
+                Schema::new(vec![Field::new("a", array.data_type().clone(), true)]);
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![array])?;
+            for with_null in [false, true] {
+                let mut list = values.iter().cloned().map(lit).collect::<Vec<_>>();
+                if with_null {
+                    list.push(lit(ScalarValue::try_from(&values[0].get_datatype())?));
+                }
+                let expr = InListExpr::new(col_a.clone(), list.clone(), false)
+                    .with_range_folding();
+                assert!(expr.range.is_some());
+                let reference = InListExpr::new(col_a.clone(), list, false);
+                let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                let expected = reference.evaluate(&batch)?.into_array(batch.num_rows());
+                assert_eq!(&result, &expected);
+            }
+        }
+
+        // NOT IN, a run with a gap, and a non-integer list are not folded
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let list = |values: &[i64]| {
+            values
+                .iter()
+                .map(|v| lit(ScalarValue::Int64(Some(*v))))
+                .collect::<Vec<_>>()
+        };
+        let expr =
+            InListExpr::new(col_a.clone(), list(&[1, 2, 3]), true).with_range_folding();
+        assert!(expr.range.is_none());
+        let expr =
+            InListExpr::new(col_a.clone(), list(&[1, 2, 4]), false).with_range_folding();
+        assert!(expr.range.is_none());
+        let strings = vec![lit(ScalarValue::Utf8(Some("a".to_string())))];
+        let expr = InListExpr::new(col_a.clone(), strings, false).with_range_folding();
+        assert!(expr.range.is_none());
+
+        // the range follows a coerced list
+        let expr = InListExpr::new(
+            col_a,
+            vec![
+                lit(ScalarValue::Int32(Some(1))),
+                lit(ScalarValue::Int32(Some(2))),
+            ],
+            false,
+        )
+        .with_range_folding()
+        .coerce_literals(&schema)?;
+        assert!(matches!(
+            expr.range,
+            Some(IntegerRange {
+                low: ScalarValue::Int64(Some(1)),
+                high: ScalarValue::Int64(Some(2)),
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_null_literal_fraction() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let list = (0..4)
+            .map(|i| lit(ScalarValue::Int64(Some(i))))
+            .chain((0..4).map(|_| lit(ScalarValue::Int64(None))))
+            .collect::<Vec<_>>();
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        assert_eq!(expr.null_literal_fraction(), 0.5);
+
+        let list = vec![lit(ScalarValue::Int64(Some(1))), col_a.clone()];
+        let expr = InListExpr::new(col_a.clone(), list, true);
+        assert_eq!(expr.null_literal_fraction(), 0.0);
+        let expr = InListExpr::new(col_a, vec![], false);
+        assert_eq!(expr.null_literal_fraction(), 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_fixed_size_binary() -> Result<()> {
+        let uuid = |b: u8| vec![b; 16];
+        let a = FixedSizeBinaryArray::try_from_sparse_iter(
+            vec![Some(uuid(1)), Some(uuid(2)), None, Some(uuid(3))].into_iter(),
+        )?;
+        let schema =
+            Schema::new(vec![Field::new("a", DataType::FixedSizeBinary(16), true)]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a in (X'01..', X'03..')"
+        let list = vec![
+            lit(ScalarValue::Binary(Some(uuid(1)))),
+            lit(ScalarValue::Binary(Some(uuid(3)))),
+        ];
+        in_list!(
+            batch,
+            list.clone(),
+            &false,
+            vec![Some(true), Some(false), None, Some(true)],
+            col_a.clone()
+        );
+        in_list!(
+            batch,
+            list.clone(),
+            &true,
+            vec![Some(false), Some(true), None, Some(false)],
+            col_a.clone()
+        );
+
+        // a NULL entry makes misses NULL
+        let mut list = list;
+        list.push(lit(ScalarValue::Binary(None)));
+        in_list!(
+            batch,
+            list.clone(),
+            &false,
+            vec![Some(true), None, None, Some(true)],
+            col_a.clone()
+        );
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), None, None, Some(false)],
+            col_a.clone()
+        );
+
+        // a literal of another width is an error
+        let list = vec![lit(ScalarValue::Binary(Some(vec![1; 8])))];
+        let expr = in_list(col_a, list, &false)?;
+        assert!(matches!(
+            expr.evaluate(&batch),
+            Err(DataFusionError::Plan(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_time() -> Result<()> {
+        let time = |s: &str| lit(ScalarValue::Utf8(Some(s.to_string())));
+        let cases: Vec<(ArrayRef, Vec<Option<bool>>)> = vec![
+            (
+                Arc::new(Time32SecondArray::from(vec![
+                    Some(9 * 3600),
+                    Some(9 * 3600 + 1),
+                    Some(12 * 3600 + 30 * 60),
+                    None,
+                ])),
+                vec![Some(true), Some(false), Some(true), None],
+            ),
+            (
+                Arc::new(Time32MillisecondArray::from(vec![
+                    Some(9 * 3_600_000),
+                    Some(9 * 3_600_000 + 500),
+                    Some((12 * 3600 + 30 * 60) * 1_000),
+                    None,
+                ])),
+                vec![Some(true), Some(true), Some(true), None],
+            ),
+            (
+                Arc::new(Time64MicrosecondArray::from(vec![
+                    Some(9 * 3_600_000_000),
+                    Some(9 * 3_600_000_000 + 500_000),
+                    Some(9 * 3_600_000_000 + 1),
+                    None,
+                ])),
+                vec![Some(true), Some(true), Some(false), None],
+            ),
+            (
+                Arc::new(Time64NanosecondArray::from(vec![
+                    Some(9 * 3_600_000_000_000),
+                    Some(9 * 3_600_000_000_000 + 500_000_000),
+                    Some(9 * 3_600_000_000_000 + 1),
+                    None,
+                ])),
+                vec![Some(true), Some(true), Some(false), None],
+            ),
+        ];
+        for (array, expected) in cases {
+            let schema =
+                Schema::new(vec![Field::new("a", array.data_type().clone(), true)]);
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![array])?;
+
+            // expression: "a in ('09:00:00', '09:00:00.5', '12:30:00')", the
+            // fraction not matching whole seconds
+            let list = vec![time("09:00:00"), time("09:00:00.5"), time("12:30:00")];
+            in_list!(batch, list.clone(), &false, expected.clone(), col_a.clone());
+
+            // a NULL entry makes misses NULL
+            let mut list = list;
+            list.push(lit(ScalarValue::Utf8(None)));
+            let expected = expected
+                .iter()
+                .map(|r| r.filter(|r| *r))
+                .collect::<Vec<_>>();
+            in_list!(batch, list, &false, expected, col_a.clone());
+        }
+
+        // integers are taken in the unit of the column
+        let a = Time64MicrosecondArray::from(vec![Some(5), Some(6)]);
+        let schema = Schema::new(vec![Field::new(
+            "a",
+            DataType::Time64(TimeUnit::Microsecond),
+            true,
+        )]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        let list = vec![lit(ScalarValue::Int64(Some(5)))];
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), Some(true)],
+            col_a.clone()
+        );
+
+        let expr = in_list(col_a, vec![time("9 o'clock")], &false)?;
+        assert!(matches!(
+            expr.evaluate(&batch),
+            Err(DataFusionError::Plan(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_probe_cache() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let col_a = col("a", &schema)?;
+        let schema = Arc::new(schema);
+        let batch = |i: usize| -> Result<RecordBatch> {
+            let a = (0..100)
+                .map(|j| match (i + j) % 9 {
+                    0 => None,
+                    k => Some(format!("hot value {}", k * (1 + i % 2))),
+                })
+                .collect::<StringArray>();
+            Ok(RecordBatch::try_new(schema.clone(), vec![Arc::new(a)])?)
+        };
+        let list = |with_null: bool| {
+            (0..40)
+                .map(|i| lit(ScalarValue::Utf8(Some(format!("hot value {}", i * 3)))))
+                .chain(with_null.then(|| lit(ScalarValue::Utf8(None))))
+                .collect::<Vec<_>>()
+        };
+
+        // a capacity below the number of distinct values evicts entries
+        for capacity in [2, 64] {
+            for (with_null, negated) in [(false, false), (true, false), (true, true)] {
+                let expr = InListExpr::new(col_a.clone(), list(with_null), negated)
+                    .with_probe_cache(capacity);
+                let reference = InListExpr::new(col_a.clone(), list(with_null), negated);
+                for i in 0..4 {
+                    let batch = batch(i)?;
+                    let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                    let expected =
+                        reference.evaluate(&batch)?.into_array(batch.num_rows());
+                    assert_eq!(&result, &expected);
+                }
+            }
+        }
+
+        // partitions evaluating copies concurrently take caches of their own
+        let expr = InListExpr::new(col_a.clone(), list(true), false).with_probe_cache(4);
+        let reference = InListExpr::new(col_a.clone(), list(true), false);
+        let handles = (0..4)
+            .map(|partition| {
+                let (expr, reference, batch) =
+                    (expr.clone(), reference.clone(), batch(partition)?);
+                Ok(std::thread::spawn(move || {
+                    (0..50).all(|_| {
+                        let result = expr.evaluate(&batch).unwrap();
+                        let expected = reference.evaluate(&batch).unwrap();
+                        result.into_array(batch.num_rows())
+                            == expected.into_array(batch.num_rows())
+                    })
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+        let idle = expr.probe_cache.as_ref().unwrap().idle.lock().len();
+        assert!((1..=4).contains(&idle));
+
+        // large strings
+        let large = Schema::new(vec![Field::new("a", DataType::LargeUtf8, true)]);
+        let a = batch(1)?.column(0).clone();
+        let a = kernels::cast::cast(&a, &DataType::LargeUtf8)?;
+        let large_batch = RecordBatch::try_new(Arc::new(large.clone()), vec![a])?;
+        let col_large = col("a", &large)?;
+        let expr =
+            InListExpr::new(col_large.clone(), list(true), true).with_probe_cache(3);
+        let reference = InListExpr::new(col_large, list(true), true);
+        assert_eq!(
+            &expr
+                .evaluate(&large_batch)?
+                .into_array(large_batch.num_rows()),
+            &reference
+                .evaluate(&large_batch)?
+                .into_array(large_batch.num_rows())
+        );
+
+        // an update of a dynamic set drops the cached results
+        let dynamic_set = DynamicInSet::new(HashSet::from([ScalarValue::Utf8(Some(
+            "hot value 3".to_string(),
+        ))]));
+        let expr = InListExpr::new_with_dynamic_set(col_a, dynamic_set.clone(), false)
+            .with_probe_cache(8);
+        let batch = batch(0)?;
+        let hits = |expr: &InListExpr| -> Result<usize> {
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+            Ok(result.iter().filter(|r| *r == Some(true)).count())
+        };
+        assert_eq!(hits(&expr)?, 11);
+        dynamic_set.update(HashSet::from([
+            ScalarValue::Utf8(Some("hot value 1".to_string())),
+            ScalarValue::Utf8(Some("hot value 2".to_string())),
+        ]));
+        assert_eq!(hits(&expr)?, 22);
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_sorted_integers() -> Result<()> {
+        // unordered, with duplicates, spanning the whole domain
+        let mut values = vec![i32::MAX, 7, -3, 7, 0, i32::MIN, 12, -3, 40, 5, 7];
+        let array = (-50..50)
+            .map(Some)
+            .chain([None, Some(i32::MIN), Some(i32::MAX)])
+            .collect::<Int32Array>();
+        let scanned = [(false, false), (false, true), (true, false), (true, true)]
+            .iter()
+            .map(|(negated, contains_null)| {
+                contains_primitive_values(&array, &values, *contains_null, *negated)
+                    .map(|r| r.into_array(0))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        values.sort_unstable();
+        let searched = [(false, false), (false, true), (true, false), (true, true)]
+            .iter()
+            .map(|(negated, contains_null)| {
+                contains_sorted_values(&array, &values, *contains_null, *negated)
+                    .map(|r| r.into_array(0))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(scanned, searched);
+
+        // through the list path, which sorts the list itself
+        let schema = Schema::new(vec![Field::new("a", DataType::UInt8, true)]);
+        let a = UInt8Array::from(vec![Some(0), Some(3), Some(4), Some(255), None]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+        let list = [255u8, 9, 3, 3, 200, 1, 0, 17, 9]
+            .iter()
+            .map(|v| lit(ScalarValue::UInt8(Some(*v))))
+            .chain([lit(ScalarValue::UInt8(None))])
+            .collect::<Vec<_>>();
+        in_list!(
+            batch,
+            list.clone(),
+            &false,
+            vec![Some(true), Some(true), None, Some(true), None],
+            col_a.clone()
+        );
+        in_list!(
+            batch,
+            list,
+            &true,
+            vec![Some(false), Some(false), None, Some(false), None],
+            col_a
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "roaring_set")]
+    #[test]
+    fn in_list_roaring_code_set() -> Result<()> {
+        // clustered runs far apart, and sparse codes up to `u32::MAX`
+        let codes = (0..70_000i64)
+            .map(|i| (i / 1000) * 1_000_000 + i % 1000)
+            .chain([u32::MAX as i64, 4_000_000_000])
+            .collect::<Vec<_>>();
+        let probes = codes
+            .iter()
+            .step_by(97)
+            .flat_map(|c| [*c, c + 1000])
+            .map(Some)
+            .chain([Some(-1), Some(u32::MAX as i64 + 1), Some(i64::MAX), None])
+            .collect::<Vec<_>>();
+
+        let int32_probes = probes
+            .iter()
+            .map(|p| p.and_then(|p| i32::try_from(p).ok()))
+            .collect::<Int32Array>();
+        let cases: Vec<(ArrayRef, Vec<ScalarValue>)> = vec![
+            (
+                Arc::new(Int64Array::from(probes)),
+                codes.iter().map(|c| ScalarValue::Int64(Some(*c))).collect(),
+            ),
+            (
+                Arc::new(int32_probes),
+                codes
+                    .iter()
+                    .filter_map(|c| i32::try_from(*c).ok())
+                    .map(|c| ScalarValue::Int32(Some(c)))
+                    .collect(),
+            ),
+        ];
+        for (array, values) in cases {
+            let schema =
+                Schema::new(vec![Field::new("a", array.data_type().clone(), true)]);
+            let col_a = col("a", &schema)?;
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![array.clone()])?;
+            let list = values.into_iter().map(lit).collect::<Vec<_>>();
+            for negated in [false, true] {
+                let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+                let in_set = expr.set.as_ref().unwrap();
+                assert!(matches!(
+                    in_set.native,
+                    Some(NativeSet::Int64Roaring(_) | NativeSet::Int32Roaring(_))
+                ));
+
+                // the bitmap agrees with the hash set it replaces
+                let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                assert_eq!(&result, &evaluate_linear(&expr, &batch)?);
+            }
+        }
 
-    // applies the in_list expr to an input batch and list
-    macro_rules! in_list {
-        ($BATCH:expr, $LIST:expr, $NEGATED:expr, $EXPECTED:expr, $COL:expr) => {{
-            let expr = in_list($COL, $LIST, $NEGATED).unwrap();
-            let result = expr.evaluate(&$BATCH)?.into_array($BATCH.num_rows());
-            let result = result
-                .as_any()
-                .downcast_ref::<BooleanArray>()
-                .expect("failed to downcast to BooleanArray");
-            let expected = &BooleanArray::from($EXPECTED);
-            assert_eq!(expected, result);
-        }};
+        // negative values are not codes
+        assert!(RoaringCodeSet::try_new(&[-1i64, 5]).is_none());
+        Ok(())
     }
 
     #[test]
-    fn in_list_utf8() -> Result<()> {
+    fn in_list_empty() -> Result<()> {
         let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
-        let a = StringArray::from(vec![Some("a"), Some("d"), None]);
+        let a = StringArray::from(vec![Some("a"), None, Some("b"), None, Some("c")]);
         let col_a = col("a", &schema)?;
         let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
 
-        // expression: "a in ("a", "b")"
-        let list = vec![
-            lit(ScalarValue::Utf8(Some("a".to_string()))),
-            lit(ScalarValue::Utf8(Some("b".to_string()))),
-        ];
+        // expression: "a in ()"
         in_list!(
             batch,
-            list,
+            vec![],
             &false,
-            vec![Some(true), Some(false), None],
+            vec![Some(false), None, Some(false), None, Some(false)],
             col_a.clone()
         );
-
-        // expression: "a not in ("a", "b")"
-        let list = vec![
-            lit(ScalarValue::Utf8(Some("a".to_string()))),
-            lit(ScalarValue::Utf8(Some("b".to_string()))),
-        ];
+        // expression: "a not in ()"
         in_list!(
             batch,
-            list,
+            vec![],
             &true,
-            vec![Some(false), Some(true), None],
+            vec![Some(true), None, Some(true), None, Some(true)],
             col_a.clone()
         );
 
-        // expression: "a not in ("a", "b")"
-        let list = vec![
-            lit(ScalarValue::Utf8(Some("a".to_string()))),
-            lit(ScalarValue::Utf8(Some("b".to_string()))),
-            lit(ScalarValue::Utf8(None)),
+        // a sliced input keeps the nulls of its slice, and a dictionary the
+        // nulls of its values
+        let sliced = batch.slice(1, 3);
+        let expr = InListExpr::new(col_a.clone(), vec![], true);
+        let result = expr.evaluate(&sliced)?.into_array(sliced.num_rows());
+        let expected: ArrayRef =
+            Arc::new(BooleanArray::from(vec![None, Some(true), None]));
+        assert_eq!(&result, &expected);
+
+        let keys = Int8Array::from(vec![Some(0), Some(1), None]);
+        let values = StringArray::from(vec![Some("a"), None]);
+        let dict = DictionaryArray::<Int8Type>::try_new(&keys, &values)?;
+        let schema = Schema::new(vec![Field::new("d", dict.data_type().clone(), true)]);
+        let col_d = col("d", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(dict)])?;
+        in_list!(batch, vec![], &false, vec![Some(false), None, None], col_d);
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_multibyte_strings() -> Result<()> {
+        // multi-byte characters, split differently at the 8 byte boundary of
+        // short strings, and differing only past their first byte
+        let words = [
+            "é",
+            "ü",
+            "日本",
+            "日本語",
+            "über",
+            "ürgen",
+            "naïve",
+            "🦀",
+            "🦀🦀",
+            "Ωmega",
+            "résumé",
+            "résume",
+            "日本語テキスト",
         ];
-        in_list!(
-            batch,
-            list,
-            &false,
-            vec![Some(true), None, None],
-            col_a.clone()
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let a = words
+            .iter()
+            .map(|w| Some(w.to_string()))
+            .chain((0..20).map(|i| Some(format!("ключ {}", i))))
+            .chain([None])
+            .collect::<StringArray>();
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        let short = words
+            .iter()
+            .filter(|w| w.len() <= 8)
+            .step_by(2)
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>();
+        let long = (0..40)
+            .map(|i| format!("ключ {}", i * 2))
+            .chain(["日本語テキスト".to_string(), "résumé".to_string()])
+            .collect::<Vec<_>>();
+        let many = (0..300)
+            .map(|i| format!("ключ {}", i))
+            .chain(["naïve".to_string()])
+            .collect::<Vec<_>>();
+        for values in [short, long, many] {
+            let list = values
+                .iter()
+                .map(|v| lit(ScalarValue::Utf8(Some(v.clone()))))
+                .collect::<Vec<_>>();
+            for negated in [false, true] {
+                let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+                let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+                let expected = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap()
+                    .iter()
+                    .map(|v| v.map(|v| values.iter().any(|w| w == v) != negated))
+                    .collect::<BooleanArray>();
+                assert_eq!(
+                    result.as_any().downcast_ref::<BooleanArray>().unwrap(),
+                    &expected
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_zip_with() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let a = Int64Array::from(vec![Some(1), Some(1), Some(2), Some(2), Some(3)]);
+        let b = StringArray::from(vec![
+            Some("x"),
+            Some("y"),
+            Some("x"),
+            Some("y"),
+            Some("x"),
+        ]);
+        let (col_a, col_b) = (col("a", &schema)?, col("b", &schema)?);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(a), Arc::new(b)],
+        )?;
+
+        // the pairs (1, 'x') and (2, 'y')
+        let a_in = InListExpr::new(
+            col_a,
+            vec![
+                lit(ScalarValue::Int64(Some(1))),
+                lit(ScalarValue::Int64(Some(2))),
+            ],
+            false,
+        );
+        let b_in = InListExpr::new(
+            col_b,
+            vec![lit(ScalarValue::from("x")), lit(ScalarValue::from("y"))],
+            false,
         );
 
-        // expression: "a not in ("a", "b")"
-        let list = vec![
-            lit(ScalarValue::Utf8(Some("a".to_string()))),
-            lit(ScalarValue::Utf8(Some("b".to_string()))),
-            lit(ScalarValue::Utf8(None)),
-        ];
-        in_list!(
-            batch,
-            list,
-            &true,
-            vec![Some(false), None, None],
-            col_a.clone()
+        // "a in (1, 2) and b in ('x', 'y')" accepts (1, 'y') and (2, 'x')
+        let and = binary(
+            Arc::new(a_in.clone()),
+            Operator::And,
+            Arc::new(b_in.clone()),
+            &schema,
+        )?;
+        let result = and.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected = BooleanArray::from(vec![true, true, true, true, false]);
+        assert_eq!(
+            result.as_any().downcast_ref::<BooleanArray>().unwrap(),
+            &expected
+        );
+
+        let zipped = a_in.zip_with(&b_in)?;
+        assert_eq!(zipped.to_string(), "(a@0, b@1) IN ((1, x), (2, y))");
+        let result = zipped.evaluate(&batch)?.into_array(batch.num_rows());
+        let expected = BooleanArray::from(vec![true, false, false, true, false]);
+        assert_eq!(
+            result.as_any().downcast_ref::<BooleanArray>().unwrap(),
+            &expected
         );
 
+        let short =
+            InListExpr::new(col("b", &schema)?, vec![lit(ScalarValue::from("x"))], false);
+        assert!(a_in.zip_with(&short).is_err());
+        let negated = InListExpr::new(
+            col("b", &schema)?,
+            vec![lit(ScalarValue::from("x")), lit(ScalarValue::from("y"))],
+            true,
+        );
+        assert!(a_in.zip_with(&negated).is_err());
         Ok(())
     }
 
     #[test]
-    fn in_list_int64() -> Result<()> {
+    fn in_list_display() -> Result<()> {
         let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
-        let a = Int64Array::from(vec![Some(0), Some(2), None]);
         let col_a = col("a", &schema)?;
-        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
 
-        // expression: "a in (0, 1)"
+        let list = (5..8)
+            .map(|v| lit(ScalarValue::Int64(Some(v))))
+            .collect::<Vec<_>>();
+        let expr = InListExpr::new(col_a.clone(), list, false);
+        assert_eq!(expr.to_string(), "a@0 IN (5, 6, 7)");
+
         let list = vec![
-            lit(ScalarValue::Int64(Some(0))),
             lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::Int64(None)),
         ];
+        let expr = InListExpr::new(col_a.clone(), list, true);
+        assert_eq!(expr.to_string(), "a@0 NOT IN (1, NULL)");
+
+        let list = vec![lit(ScalarValue::Int64(Some(1)))];
+        let expr = InListExpr::new(col_a, list, false).with_set_threshold(0);
+        assert!(expr.set().is_some());
+        assert_eq!(expr.to_string(), "a@0 IN (SET) (1)");
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_set_display() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let list = (0..31)
+            .map(|v| lit(ScalarValue::Int64(Some(v))))
+            .collect::<Vec<_>>();
+        let values = (0..31)
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let expr = InListExpr::new(col("a", &schema)?, list.clone(), false);
+        assert!(expr.set().is_some());
+        assert_eq!(expr.to_string(), format!("a@0 IN (SET) ({})", values));
+
+        let expr = InListExpr::new(col("a", &schema)?, list, true);
+        assert_eq!(expr.to_string(), format!("a@0 NOT IN (SET) ({})", values));
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_interval_year_month_and_month_day_nano() -> Result<()> {
+        // months in the high 32 bits, days in the next 32 and nanoseconds in
+        // the low 64
+        let one_day = 1_i128 << 64;
+        let one_day_as_nanos = 86_400_000_000_000_i128;
+        let one_month = 1_i128 << 96;
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Interval(IntervalUnit::YearMonth), true),
+            Field::new("b", DataType::Interval(IntervalUnit::MonthDayNano), true),
+        ]);
+        let a = IntervalYearMonthArray::from(vec![Some(12), Some(1), None]);
+        let b = IntervalMonthDayNanoArray::from(vec![
+            Some(one_day),
+            Some(one_day_as_nanos),
+            None,
+        ]);
+        let (col_a, col_b) = (col("a", &schema)?, col("b", &schema)?);
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a), Arc::new(b)])?;
+
+        // expression: "a in (INTERVAL '1' YEAR)"
+        let list = vec![lit(ScalarValue::IntervalYearMonth(Some(12)))];
         in_list!(
             batch,
             list,
@@ -755,143 +10123,367 @@ mod tests {
             col_a.clone()
         );
 
-        // expression: "a not in (0, 1)"
+        // expression: "a not in (INTERVAL '1' YEAR, NULL)"
         let list = vec![
-            lit(ScalarValue::Int64(Some(0))),
-            lit(ScalarValue::Int64(Some(1))),
+            lit(ScalarValue::IntervalYearMonth(Some(12))),
+            lit(ScalarValue::IntervalYearMonth(None)),
         ];
         in_list!(
             batch,
             list,
             &true,
-            vec![Some(false), Some(true), None],
+            vec![Some(false), None, None],
             col_a.clone()
         );
 
-        // expression: "a in (0, 1, NULL)"
-        let list = vec![
-            lit(ScalarValue::Int64(Some(0))),
-            lit(ScalarValue::Int64(Some(1))),
-            lit(ScalarValue::Utf8(None)),
-        ];
+        // expression: "b in (INTERVAL '1' DAY)", which does not match 24 hours
+        let list = vec![lit(ScalarValue::IntervalMonthDayNano(Some(one_day)))];
         in_list!(
             batch,
             list,
             &false,
-            vec![Some(true), None, None],
-            col_a.clone()
-        );
-
-        // expression: "a not in (0, 1, NULL)"
-        let list = vec![
-            lit(ScalarValue::Int64(Some(0))),
-            lit(ScalarValue::Int64(Some(1))),
-            lit(ScalarValue::Utf8(None)),
-        ];
-        in_list!(
-            batch,
-            list,
-            &true,
-            vec![Some(false), None, None],
-            col_a.clone()
+            vec![Some(true), Some(false), None],
+            col_b.clone()
         );
 
-        Ok(())
-    }
-
-    #[test]
-    fn in_list_float64() -> Result<()> {
-        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
-        let a = Float64Array::from(vec![Some(0.0), Some(0.2), None]);
-        let col_a = col("a", &schema)?;
-        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
-
-        // expression: "a in (0.0, 0.2)"
-        let list = vec![
-            lit(ScalarValue::Float64(Some(0.0))),
-            lit(ScalarValue::Float64(Some(0.1))),
-        ];
+        // the same through a set
+        let list = (0..=OPTIMIZER_INSET_THRESHOLD as i128)
+            .map(|i| lit(ScalarValue::IntervalYearMonth(Some(12 * (i + 1) as i32))))
+            .collect();
         in_list!(
             batch,
             list,
             &false,
             vec![Some(true), Some(false), None],
-            col_a.clone()
+            col_a
         );
-
-        // expression: "a not in (0.0, 0.2)"
-        let list = vec![
-            lit(ScalarValue::Float64(Some(0.0))),
-            lit(ScalarValue::Float64(Some(0.1))),
-        ];
+        let list = (0..=OPTIMIZER_INSET_THRESHOLD as i128)
+            .map(|i| {
+                lit(ScalarValue::IntervalMonthDayNano(Some(
+                    one_day + i * one_month,
+                )))
+            })
+            .collect();
         in_list!(
             batch,
             list,
             &true,
             vec![Some(false), Some(true), None],
-            col_a.clone()
+            col_b
         );
+        Ok(())
+    }
 
-        // expression: "a in (0.0, 0.2, NULL)"
-        let list = vec![
-            lit(ScalarValue::Float64(Some(0.0))),
-            lit(ScalarValue::Float64(Some(0.1))),
-            lit(ScalarValue::Utf8(None)),
-        ];
-        in_list!(
-            batch,
-            list,
-            &false,
-            vec![Some(true), None, None],
-            col_a.clone()
+    #[test]
+    fn in_list_new_with_set() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let list = (0..40)
+            .map(|v| lit(ScalarValue::Int64(Some(v * 3))))
+            .collect::<Vec<_>>();
+        let set = InSet::new((0..40).map(|v| ScalarValue::Int64(Some(v * 3))).collect());
+
+        let batches = (0..3)
+            .map(|p| {
+                let a = (0..50).map(|i| (i % 7 != 0).then(|| p * 50 + i));
+                RecordBatch::try_new(
+                    Arc::new(schema.clone()),
+                    vec![Arc::new(a.collect::<Int64Array>())],
+                )
+            })
+            .collect::<arrow::error::Result<Vec<_>>>()?;
+        for negated in [false, true] {
+            // one expression per partition, sharing the set built above
+            for batch in &batches {
+                let shared = InListExpr::new_with_set(
+                    col_a.clone(),
+                    list.clone(),
+                    negated,
+                    set.clone(),
+                );
+                let built = InListExpr::new(col_a.clone(), list.clone(), negated);
+                assert!(shared.set().is_some() && built.set().is_some());
+                assert_eq!(shared.to_string(), built.to_string());
+                let shared = shared.evaluate(batch)?.into_array(batch.num_rows());
+                let built = built.evaluate(batch)?.into_array(batch.num_rows());
+                assert_eq!(&shared, &built);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_set_not_in_with_null() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let a = Int64Array::from(vec![Some(0), Some(1), Some(100), None]);
+        let b = StringArray::from(vec![Some("v0"), Some("v1"), Some("x"), None]);
+        let (col_a, col_b) = (col("a", &schema)?, col("b", &schema)?);
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a), Arc::new(b)])?;
+
+        // expression: "a not in (0, 2, .., 60, NULL)", probing a set
+        let list = (0..=OPTIMIZER_INSET_THRESHOLD as i64)
+            .map(|v| lit(ScalarValue::Int64(Some(v * 2))))
+            .chain([lit(ScalarValue::Int64(None))])
+            .collect::<Vec<_>>();
+        let expr = InListExpr::new(col_a.clone(), list.clone(), true);
+        let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(
+            result,
+            &BooleanArray::from(vec![Some(false), None, None, None])
+        );
+        // as the list path does
+        let expr = InListExpr::new_with_threshold(col_a, list, true, usize::MAX);
+        let linear = expr.evaluate(&batch)?.into_array(batch.num_rows());
+        assert_eq!(
+            linear.as_any().downcast_ref::<BooleanArray>().unwrap(),
+            result
         );
 
-        // expression: "a not in (0.0, 0.2, NULL)"
-        let list = vec![
-            lit(ScalarValue::Float64(Some(0.0))),
-            lit(ScalarValue::Float64(Some(0.1))),
-            lit(ScalarValue::Utf8(None)),
-        ];
+        // expression: "b not in ('v0', 'v2', .., NULL)"
+        let list = (0..=OPTIMIZER_INSET_THRESHOLD)
+            .map(|v| lit(ScalarValue::Utf8(Some(format!("v{}", v * 2)))))
+            .chain([lit(ScalarValue::Utf8(None))])
+            .collect::<Vec<_>>();
         in_list!(
             batch,
             list,
             &true,
-            vec![Some(false), None, None],
-            col_a.clone()
+            vec![Some(false), None, None, None],
+            col_b
         );
-
         Ok(())
     }
 
     #[test]
-    fn in_list_bool() -> Result<()> {
-        let schema = Schema::new(vec![Field::new("a", DataType::Boolean, true)]);
-        let a = BooleanArray::from(vec![Some(true), None]);
+    fn in_list_null_literal_keeps_set() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
         let col_a = col("a", &schema)?;
+        let a = Int64Array::from(vec![Some(0), Some(1), Some(38), Some(100), None]);
         let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
 
-        // expression: "a in (true)"
-        let list = vec![lit(ScalarValue::Boolean(Some(true)))];
-        in_list!(batch, list, &false, vec![Some(true), None], col_a.clone());
-
-        // expression: "a not in (true)"
-        let list = vec![lit(ScalarValue::Boolean(Some(true)))];
-        in_list!(batch, list, &true, vec![Some(false), None], col_a.clone());
+        // 40 literals, one of them NULL
+        let list = (0..39)
+            .map(|v| lit(ScalarValue::Int64(Some(v * 2))))
+            .chain([lit(ScalarValue::Int64(None))])
+            .collect::<Vec<_>>();
+        for negated in [false, true] {
+            let expr = InListExpr::new(col_a.clone(), list.clone(), negated);
+            assert!(expr.set.as_ref().unwrap().contains_null);
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            let linear =
+                InListExpr::new_without_set(col_a.clone(), list.clone(), negated)
+                    .evaluate(&batch)?
+                    .into_array(batch.num_rows());
+            assert_eq!(&result, &linear);
+            let expected = if negated {
+                vec![Some(false), None, Some(false), None, None]
+            } else {
+                vec![Some(true), None, Some(true), None, None]
+            };
+            assert_eq!(
+                result.as_any().downcast_ref::<BooleanArray>().unwrap(),
+                &BooleanArray::from(expected)
+            );
+        }
+        Ok(())
+    }
 
-        // expression: "a in (true, NULL)"
-        let list = vec![
-            lit(ScalarValue::Boolean(Some(true))),
-            lit(ScalarValue::Utf8(None)),
+    #[test]
+    fn in_list_list_column() -> Result<()> {
+        let field = Field::new("item", DataType::Int32, true);
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::List(Box::new(field.clone())), true),
+            Field::new("b", DataType::LargeList(Box::new(field)), true),
+        ]);
+        let rows = vec![
+            Some(vec![Some(1), Some(2)]),
+            Some(vec![Some(1), Some(3)]),
+            Some(vec![Some(1)]),
+            None,
+            Some(vec![Some(1), None]),
+            Some(vec![Some(4), None]),
+            Some(vec![]),
         ];
-        in_list!(batch, list, &false, vec![Some(true), None], col_a.clone());
+        let a = ListArray::from_iter_primitive::<Int32Type, _, _>(rows.clone());
+        let b = LargeListArray::from_iter_primitive::<Int32Type, _, _>(rows);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(a), Arc::new(b)],
+        )?;
+        let list_lit = |values: Vec<Option<i32>>| {
+            lit(ScalarValue::List(
+                Some(values.into_iter().map(ScalarValue::Int32).collect()),
+                Box::new(DataType::Int32),
+            ))
+        };
 
-        // expression: "a not in (true, NULL)"
-        let list = vec![
-            lit(ScalarValue::Boolean(Some(true))),
-            lit(ScalarValue::Utf8(None)),
+        for col_name in ["a", "b"] {
+            // expression: "a in ([1, 2], [])", where [1, 3] and [1] differ
+            let list = vec![list_lit(vec![Some(1), Some(2)]), list_lit(vec![])];
+            let expected = vec![
+                Some(true),
+                Some(false),
+                Some(false),
+                None,
+                None,
+                Some(false),
+                Some(true),
+            ];
+            in_list!(
+                batch,
+                list.clone(),
+                &false,
+                expected,
+                col(col_name, &schema)?
+            );
+            let expected = vec![
+                Some(false),
+                Some(true),
+                Some(true),
+                None,
+                None,
+                Some(true),
+                Some(false),
+            ];
+            in_list!(batch, list, &true, expected, col(col_name, &schema)?);
+
+            // expression: "a in ([1, NULL], NULL)"
+            let list = vec![
+                list_lit(vec![Some(1), None]),
+                lit(ScalarValue::List(None, Box::new(DataType::Int32))),
+            ];
+            in_list!(batch, list, &false, vec![None; 7], col(col_name, &schema)?);
+
+            // the same through a set
+            let list = (0..=OPTIMIZER_INSET_THRESHOLD as i32)
+                .map(|v| list_lit(vec![Some(1), Some(v + 2)]))
+                .collect::<Vec<_>>();
+            let expected = vec![
+                Some(true),
+                Some(true),
+                Some(false),
+                None,
+                None,
+                Some(false),
+                Some(false),
+            ];
+            in_list!(batch, list, &false, expected, col(col_name, &schema)?);
+        }
+
+        // literals must be lists of the element type
+        let list = vec![lit(ScalarValue::List(
+            Some(vec![ScalarValue::Int64(Some(1))]),
+            Box::new(DataType::Int64),
+        ))];
+        let expr = in_list(col("a", &schema)?, list, &false)?;
+        assert!(expr.evaluate(&batch).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_with_new_children_keeps_comparison() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Float64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let a = Float64Array::from(vec![Some(1.0000001), Some(1.5), None]);
+        let b = StringArray::from(vec![Some("v1  "), Some("x"), None]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(a), Arc::new(b)],
+        )?;
+        let expected: ArrayRef =
+            Arc::new(BooleanArray::from(vec![Some(true), Some(false), None]));
+
+        // lists long enough for a set, which these comparisons disable
+        let floats = (0..=OPTIMIZER_INSET_THRESHOLD)
+            .map(|v| lit(ScalarValue::Float64(Some(v as f64))))
+            .collect();
+        let strings = (0..=OPTIMIZER_INSET_THRESHOLD)
+            .map(|v| lit(ScalarValue::Utf8(Some(format!("v{}", v)))))
+            .collect();
+        let exprs: Vec<Arc<dyn PhysicalExpr>> = vec![
+            Arc::new(
+                InListExpr::new(col("a", &schema)?, floats, false)
+                    .with_float_eq(FloatEq::new(|l, r| (l - r).abs() < 1e-3)),
+            ),
+            Arc::new(
+                InListExpr::new(col("b", &schema)?, strings, false)
+                    .with_trim_trailing_whitespace(),
+            ),
         ];
-        in_list!(batch, list, &true, vec![Some(false), None], col_a.clone());
+        for expr in exprs {
+            let rebuilt = expr.clone().with_new_children(expr.children())?;
+            let in_list = rebuilt.as_any().downcast_ref::<InListExpr>().unwrap();
+            assert!(in_list.set.is_none());
+            let result = rebuilt.evaluate(&batch)?.into_array(batch.num_rows());
+            assert_eq!(&result, &expected, "{}", rebuilt);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn in_list_is_unsatisfiable_bound_set() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let col_a = col("a", &schema)?;
+        let bound = |values: Vec<Option<i64>>| -> Result<InListExpr> {
+            let values: ArrayRef = Arc::new(Int64Array::from(values));
+            let validity = Buffer::from(vec![0xff_u8; 1]);
+            InListExpr::from_values_with_validity(
+                col_a.clone(),
+                &values,
+                &validity,
+                false,
+            )
+        };
+
+        // "a in (NULL, NULL)" is never true
+        assert!(bound(vec![None, None])?.is_unsatisfiable(false));
+        // "a in (1, NULL)" is true for 1
+        assert!(!bound(vec![Some(1), None])?.is_unsatisfiable(false));
+        Ok(())
+    }
+
+    #[cfg(feature = "regex_expressions")]
+    #[test]
+    fn in_list_regex_alternation_new_children() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Utf8, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let a = StringArray::from(vec![Some("x"), Some("y"), None]);
+        let b = StringArray::from(vec![Some("z"), Some("y"), Some("x")]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(a), Arc::new(b)],
+        )?;
 
+        // expression: "a in ('x')", compiled to a regex
+        let list = vec![lit(ScalarValue::Utf8(Some("x".to_string())))];
+        let expr: Arc<dyn PhysicalExpr> = Arc::new(
+            InListExpr::new(col("a", &schema)?, list, false).with_regex_alternation()?,
+        );
+
+        // expression: "a in (b)", which cannot be compiled
+        let children = vec![col("a", &schema)?, col("b", &schema)?];
+        let rebuilt = expr.with_new_children(children)?;
+        assert!(rebuilt
+            .as_any()
+            .downcast_ref::<InListExpr>()
+            .unwrap()
+            .regex
+            .is_none());
+        let result = rebuilt.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(
+            result,
+            &BooleanArray::from(vec![Some(false), Some(true), None])
+        );
         Ok(())
     }
 }
@@ -17,7 +17,7 @@
 
 //! IS NOT NULL expression
 
-use std::{any::Any, sync::Arc};
+use std::{any::Any, hash::Hasher, sync::Arc};
 
 use crate::PhysicalExpr;
 use arrow::compute;
@@ -79,6 +79,16 @@ impl PhysicalExpr for IsNotNullExpr {
             )),
         }
     }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.arg.clone()]
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        other.is::<Self>()
+    }
+
+    fn node_hash(&self, _state: &mut dyn Hasher) {}
 }
 
 /// Create an IS NOT NULL expression
@@ -17,7 +17,7 @@
 
 //! IS NULL expression
 
-use std::{any::Any, sync::Arc};
+use std::{any::Any, hash::Hasher, sync::Arc};
 
 use arrow::compute;
 use arrow::{
@@ -80,6 +80,16 @@ impl PhysicalExpr for IsNullExpr {
             )),
         }
     }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.arg.clone()]
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        other.is::<Self>()
+    }
+
+    fn node_hash(&self, _state: &mut dyn Hasher) {}
 }
 
 /// Create an IS NULL expression
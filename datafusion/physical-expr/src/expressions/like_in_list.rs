@@ -0,0 +1,560 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! LIKE ANY / LIKE NONE expression over a list of patterns
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow::array::{BooleanArray, GenericStringArray, OffsetSizeTrait};
+use arrow::{
+    datatypes::{DataType, Schema},
+    record_batch::RecordBatch,
+};
+
+use crate::{expressions, PhysicalExpr};
+use datafusion_common::ScalarValue;
+use datafusion_common::{DataFusionError, Result};
+use datafusion_expr::ColumnarValue;
+
+/// A LIKE pattern compiled once at construction. The cases mirror arrow's
+/// `like_utf8_scalar`: `%` matches any sequence of characters, `_` matches
+/// any single character and there is no escape character.
+#[derive(Debug)]
+enum LikePattern {
+    /// Pattern without wildcards
+    Exact(String),
+    /// Pattern of the form `abc%`
+    Prefix(String),
+    /// Pattern of the form `%abc`
+    Suffix(String),
+    /// Any other pattern
+    Wildcard(Vec<LikeToken>),
+}
+
+#[derive(Debug)]
+enum LikeToken {
+    /// `%`
+    AnySequence,
+    /// `_`
+    AnyChar,
+    Char(char),
+}
+
+fn is_like_pattern(c: char) -> bool {
+    c == '%' || c == '_'
+}
+
+impl LikePattern {
+    fn compile(pattern: &str) -> Self {
+        if !pattern.contains(is_like_pattern) {
+            Self::Exact(pattern.to_string())
+        } else if pattern.ends_with('%')
+            && !pattern[..pattern.len() - 1].contains(is_like_pattern)
+        {
+            Self::Prefix(pattern[..pattern.len() - 1].to_string())
+        } else if pattern.starts_with('%') && !pattern[1..].contains(is_like_pattern) {
+            Self::Suffix(pattern[1..].to_string())
+        } else {
+            Self::Wildcard(
+                pattern
+                    .chars()
+                    .map(|c| match c {
+                        '%' => LikeToken::AnySequence,
+                        '_' => LikeToken::AnyChar,
+                        c => LikeToken::Char(c),
+                    })
+                    .collect(),
+            )
+        }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            Self::Exact(s) => value == s,
+            Self::Prefix(s) => value.starts_with(s.as_str()),
+            Self::Suffix(s) => value.ends_with(s.as_str()),
+            Self::Wildcard(tokens) => wildcard_match(tokens, value),
+        }
+    }
+}
+
+// iterative wildcard matching that backtracks to the most recent `%`
+fn wildcard_match(tokens: &[LikeToken], value: &str) -> bool {
+    // position in `tokens` and byte position in `value`
+    let (mut t, mut v) = (0, 0);
+    // token after the last `%` seen and the value position it resumes from
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    loop {
+        if t < tokens.len() {
+            let next = value[v..].chars().next();
+            match (&tokens[t], next) {
+                (LikeToken::AnySequence, _) => {
+                    t += 1;
+                    backtrack = Some((t, v));
+                    continue;
+                }
+                (LikeToken::AnyChar, Some(c)) => {
+                    t += 1;
+                    v += c.len_utf8();
+                    continue;
+                }
+                (LikeToken::Char(expected), Some(c)) if *expected == c => {
+                    t += 1;
+                    v += c.len_utf8();
+                    continue;
+                }
+                _ => {}
+            }
+        } else if v == value.len() {
+            return true;
+        }
+
+        // mismatch: let the last `%` consume one more character
+        match backtrack {
+            Some((bt, bv)) => match value[bv..].chars().next() {
+                Some(c) => {
+                    let bv = bv + c.len_utf8();
+                    backtrack = Some((bt, bv));
+                    t = bt;
+                    v = bv;
+                }
+                None => return false,
+            },
+            None => return false,
+        }
+    }
+}
+
+/// Pattern count from which all patterns are matched at once with a regex set
+#[cfg(feature = "like_regex_set")]
+const LIKE_REGEX_SET_THRESHOLD: usize = 16;
+
+// translate a LIKE pattern to an anchored regex. `(?s)` lets wildcards match
+// newlines, as they do when matching pattern by pattern.
+#[cfg(feature = "like_regex_set")]
+fn like_to_regex(pattern: &str) -> String {
+    let re_pattern = regex::escape(pattern).replace('%', ".*").replace('_', ".");
+    format!("(?s)^{}$", re_pattern)
+}
+
+/// LIKE ANY expression, e.g. `x LIKE ANY ('a%', '%b')`, testing whether the
+/// value matches any of a list of literal patterns. The negated form is
+/// `x LIKE NONE (...)`.
+///
+/// NULL patterns follow three-valued logic: a value matching no pattern
+/// evaluates to NULL rather than `false` if any pattern is NULL.
+#[derive(Debug)]
+pub struct LikeInListExpr {
+    expr: Arc<dyn PhysicalExpr>,
+    patterns: Vec<Option<String>>,
+    negated: bool,
+    compiled: Vec<LikePattern>,
+    contains_null: bool,
+    /// All non-null patterns compiled to a single regex set, used for large
+    /// pattern lists when the `like_regex_set` feature is enabled
+    #[cfg(feature = "like_regex_set")]
+    regex_set: Option<regex::RegexSet>,
+}
+
+impl LikeInListExpr {
+    /// Create a new LIKE ANY expression. Every pattern must be a string
+    /// literal.
+    pub fn try_new(
+        expr: Arc<dyn PhysicalExpr>,
+        patterns: Vec<Arc<dyn PhysicalExpr>>,
+        negated: bool,
+    ) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                match pattern
+                    .as_any()
+                    .downcast_ref::<expressions::Literal>()
+                    .map(|l| l.value())
+                {
+                    Some(ScalarValue::Utf8(v)) | Some(ScalarValue::LargeUtf8(v)) => {
+                        Ok(v.clone())
+                    }
+                    _ => Err(DataFusionError::Plan(format!(
+                        "LIKE ANY patterns must be string literals, got {}",
+                        pattern
+                    ))),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let compiled: Vec<LikePattern> = patterns
+            .iter()
+            .flatten()
+            .map(|p| LikePattern::compile(p))
+            .collect();
+        let contains_null = patterns.iter().any(|p| p.is_none());
+
+        // fall back to matching pattern by pattern if the set can't be built,
+        // e.g. because it exceeds the regex size limit
+        #[cfg(feature = "like_regex_set")]
+        let regex_set = if compiled.len() >= LIKE_REGEX_SET_THRESHOLD {
+            regex::RegexSet::new(patterns.iter().flatten().map(|p| like_to_regex(p))).ok()
+        } else {
+            None
+        };
+
+        Ok(Self {
+            expr,
+            patterns,
+            negated,
+            compiled,
+            contains_null,
+            #[cfg(feature = "like_regex_set")]
+            regex_set,
+        })
+    }
+
+    /// Input expression
+    pub fn expr(&self) -> &Arc<dyn PhysicalExpr> {
+        &self.expr
+    }
+
+    /// Patterns to match against, `None` for a NULL pattern
+    pub fn patterns(&self) -> &[Option<String>] {
+        &self.patterns
+    }
+
+    /// Is this negated e.g. LIKE NONE
+    pub fn negated(&self) -> bool {
+        self.negated
+    }
+
+    #[cfg(feature = "like_regex_set")]
+    fn is_match(&self, value: &str) -> bool {
+        match &self.regex_set {
+            Some(regex_set) => regex_set.is_match(value),
+            None => self.compiled.iter().any(|p| p.matches(value)),
+        }
+    }
+
+    #[cfg(not(feature = "like_regex_set"))]
+    fn is_match(&self, value: &str) -> bool {
+        self.compiled.iter().any(|p| p.matches(value))
+    }
+
+    fn matches_any<T: OffsetSizeTrait>(
+        &self,
+        array: &GenericStringArray<T>,
+    ) -> BooleanArray {
+        array
+            .iter()
+            .map(|x| {
+                x.and_then(|v| {
+                    if self.is_match(v) {
+                        Some(!self.negated)
+                    } else if self.contains_null {
+                        None
+                    } else {
+                        Some(self.negated)
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for LikeInListExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|p| match p {
+                Some(p) => format!("'{}'", p),
+                None => "NULL".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.negated {
+            write!(f, "{} LIKE NONE ({})", self.expr, patterns)
+        } else {
+            write!(f, "{} LIKE ANY ({})", self.expr, patterns)
+        }
+    }
+}
+
+impl PhysicalExpr for LikeInListExpr {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, _input_schema: &Schema) -> Result<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
+        Ok(self.contains_null || self.expr.nullable(input_schema)?)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        let array = self.expr.evaluate(batch)?.into_array(batch.num_rows());
+        let result = match array.data_type() {
+            DataType::Utf8 => self.matches_any(
+                array
+                    .as_any()
+                    .downcast_ref::<GenericStringArray<i32>>()
+                    .unwrap(),
+            ),
+            DataType::LargeUtf8 => self.matches_any(
+                array
+                    .as_any()
+                    .downcast_ref::<GenericStringArray<i64>>()
+                    .unwrap(),
+            ),
+            datatype => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "LIKE ANY does not support datatype {:?}.",
+                    datatype
+                )))
+            }
+        };
+        Ok(ColumnarValue::Array(Arc::new(result)))
+    }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn PhysicalExpr>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Internal(format!(
+                "LikeInListExpr expects 1 child, got {}",
+                children.len()
+            )));
+        }
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|p| expressions::lit(ScalarValue::Utf8(p.clone())))
+            .collect();
+        Ok(Arc::new(Self::try_new(
+            children[0].clone(),
+            patterns,
+            self.negated,
+        )?))
+    }
+}
+
+/// Creates a LIKE ANY expression
+pub fn like_in_list(
+    expr: Arc<dyn PhysicalExpr>,
+    patterns: Vec<Arc<dyn PhysicalExpr>>,
+    negated: &bool,
+) -> Result<Arc<dyn PhysicalExpr>> {
+    Ok(Arc::new(LikeInListExpr::try_new(expr, patterns, *negated)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{array::StringArray, datatypes::Field};
+
+    use super::*;
+    use crate::expressions::{col, lit};
+
+    // applies the like_in_list expr to an input batch and list of patterns
+    macro_rules! like_in_list {
+        ($BATCH:expr, $PATTERNS:expr, $NEGATED:expr, $EXPECTED:expr, $COL:expr) => {{
+            let patterns = $PATTERNS
+                .into_iter()
+                .map(|p: Option<&str>| lit(ScalarValue::Utf8(p.map(|p| p.to_string()))))
+                .collect();
+            let expr = like_in_list($COL, patterns, $NEGATED).unwrap();
+            let result = expr.evaluate(&$BATCH)?.into_array($BATCH.num_rows());
+            let result = result
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .expect("failed to downcast to BooleanArray");
+            let expected = &BooleanArray::from($EXPECTED);
+            assert_eq!(expected, result);
+        }};
+    }
+
+    #[test]
+    fn like_in_list_utf8() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let a = StringArray::from(vec![
+            Some("apple"),
+            Some("crab"),
+            Some("abc"),
+            Some("xyz"),
+            None,
+        ]);
+        let col_a = col("a", &schema)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a)])?;
+
+        // expression: "a like any ('ap%', '%b')"
+        like_in_list!(
+            batch,
+            vec![Some("ap%"), Some("%b")],
+            &false,
+            vec![Some(true), Some(true), Some(false), Some(false), None],
+            col_a.clone()
+        );
+
+        // expression: "a like any ('a_c', '%y%')"
+        like_in_list!(
+            batch,
+            vec![Some("a_c"), Some("%y%")],
+            &false,
+            vec![Some(false), Some(false), Some(true), Some(true), None],
+            col_a.clone()
+        );
+
+        // expression: "a like none ('ap%', '%b')"
+        like_in_list!(
+            batch,
+            vec![Some("ap%"), Some("%b")],
+            &true,
+            vec![Some(false), Some(false), Some(true), Some(true), None],
+            col_a.clone()
+        );
+
+        // expression: "a like any ('%q%', 'z')"
+        like_in_list!(
+            batch,
+            vec![Some("%q%"), Some("z")],
+            &false,
+            vec![Some(false), Some(false), Some(false), Some(false), None],
+            col_a.clone()
+        );
+
+        // expression: "a like any ('ap%', NULL)"
+        like_in_list!(
+            batch,
+            vec![Some("ap%"), None],
+            &false,
+            vec![Some(true), None, None, None, None],
+            col_a.clone()
+        );
+
+        // expression: "a like none ('ap%', NULL)"
+        like_in_list!(
+            batch,
+            vec![Some("ap%"), None],
+            &true,
+            vec![Some(false), None, None, None, None],
+            col_a
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn like_pattern_wildcards() {
+        let pattern = LikePattern::compile("a%b%c");
+        assert!(pattern.matches("abc"));
+        assert!(pattern.matches("aXXbYYc"));
+        assert!(pattern.matches("abbbc"));
+        assert!(!pattern.matches("abcd"));
+        assert!(!pattern.matches("acb"));
+
+        let pattern = LikePattern::compile("_é_");
+        assert!(pattern.matches("aéb"));
+        assert!(!pattern.matches("aé"));
+
+        let pattern = LikePattern::compile("%");
+        assert!(pattern.matches(""));
+        assert!(pattern.matches("anything"));
+    }
+
+    #[cfg(feature = "like_regex_set")]
+    #[test]
+    fn like_in_list_regex_set_parity() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let mut patterns = (0..LIKE_REGEX_SET_THRESHOLD)
+            .map(|i| format!("%key_{}", i))
+            .collect::<Vec<_>>();
+        patterns.extend(
+            ["a%b%c", "x_z", "exact", "pre%", "%.*%", "(%)"]
+                .iter()
+                .map(|p| p.to_string()),
+        );
+        let patterns = patterns
+            .into_iter()
+            .map(|p| lit(ScalarValue::Utf8(Some(p))))
+            .collect();
+        let expr = LikeInListExpr::try_new(col("a", &schema)?, patterns, false)?;
+        assert!(expr.regex_set.is_some());
+
+        for value in [
+            "",
+            "a\nb\nc",
+            "abc",
+            "axbyc",
+            "xyz",
+            "x\nz",
+            "exact",
+            "exactly",
+            "prefix",
+            "my key_3",
+            "key_33x",
+            "a.*b",
+            "(anything)",
+            "(",
+            "é",
+        ] {
+            let per_pattern = expr.compiled.iter().any(|p| p.matches(value));
+            assert_eq!(expr.is_match(value), per_pattern, "value {:?}", value);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn like_in_list_with_new_children() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Utf8, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let a = StringArray::from(vec![Some("apple"), Some("crab")]);
+        let b = StringArray::from(vec![Some("crab"), Some("apple")]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(a), Arc::new(b)],
+        )?;
+
+        // expression: "a like any ('ap%', NULL)"
+        let patterns = vec![
+            lit(ScalarValue::Utf8(Some("ap%".to_string()))),
+            lit(ScalarValue::Utf8(None)),
+        ];
+        let expr = like_in_list(col("a", &schema)?, patterns, &false)?;
+        assert_eq!(expr.children().len(), 1);
+        assert_eq!(expr.children()[0].to_string(), "a@0");
+
+        // expression: "b like any ('ap%', NULL)"
+        let rebuilt = expr.clone().with_new_children(vec![col("b", &schema)?])?;
+        assert_eq!(rebuilt.to_string(), "b@1 LIKE ANY ('ap%', NULL)");
+        let result = rebuilt.evaluate(&batch)?.into_array(batch.num_rows());
+        let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+        assert_eq!(result, &BooleanArray::from(vec![None, Some(true)]));
+
+        assert!(expr.with_new_children(vec![]).is_err());
+        Ok(())
+    }
+}
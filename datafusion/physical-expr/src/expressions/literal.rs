@@ -18,6 +18,7 @@
 //! Literal expression
 
 use std::any::Any;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use arrow::{
@@ -71,6 +72,20 @@ impl PhysicalExpr for Literal {
     fn evaluate(&self, _batch: &RecordBatch) -> Result<ColumnarValue> {
         Ok(ColumnarValue::Scalar(self.value.clone()))
     }
+
+    fn is_constant(&self) -> bool {
+        true
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        other
+            .downcast_ref::<Self>()
+            .map_or(false, |o| self.value == o.value)
+    }
+
+    fn node_hash(&self, mut state: &mut dyn Hasher) {
+        self.value.hash(&mut state)
+    }
 }
 
 /// Create a literal expression
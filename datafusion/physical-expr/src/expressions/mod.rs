@@ -27,10 +27,12 @@ mod get_indexed_field;
 mod in_list;
 mod is_not_null;
 mod is_null;
+mod like_in_list;
 mod literal;
 mod negative;
 mod not;
 mod nullif;
+mod struct_in_list;
 mod try_cast;
 
 /// Module with some convenient methods used in expression building
@@ -73,13 +75,18 @@ pub use cast::{
 pub use column::{col, Column};
 pub use datetime::DateIntervalExpr;
 pub use get_indexed_field::GetIndexedFieldExpr;
-pub use in_list::{in_list, InListExpr};
+pub use in_list::{
+    in_list, DynamicInSet, FloatEq, InListExpr, InSet, InSetBuilder, NullHandling,
+    ParquetInFilter, ScalarPredicate, SortedMergeInSet,
+};
 pub use is_not_null::{is_not_null, IsNotNullExpr};
 pub use is_null::{is_null, IsNullExpr};
+pub use like_in_list::{like_in_list, LikeInListExpr};
 pub use literal::{lit, Literal};
 pub use negative::{negative, NegativeExpr};
 pub use not::{not, NotExpr};
 pub use nullif::nullif_func;
+pub use struct_in_list::{struct_in_list, StructInListExpr};
 pub use try_cast::{try_cast, TryCastExpr};
 
 /// returns the name of the state
@@ -18,6 +18,7 @@
 //! Negation (-) expression
 
 use std::any::Any;
+use std::hash::Hasher;
 use std::sync::Arc;
 
 use arrow::array::ArrayRef;
@@ -107,6 +108,16 @@ impl PhysicalExpr for NegativeExpr {
             }
         }
     }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.arg.clone()]
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        other.is::<Self>()
+    }
+
+    fn node_hash(&self, _state: &mut dyn Hasher) {}
 }
 
 /// Creates a unary expression NEGATIVE
@@ -19,6 +19,7 @@
 
 use std::any::Any;
 use std::fmt;
+use std::hash::Hasher;
 use std::sync::Arc;
 
 use crate::PhysicalExpr;
@@ -103,6 +104,16 @@ impl PhysicalExpr for NotExpr {
             }
         }
     }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.arg.clone()]
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        other.is::<Self>()
+    }
+
+    fn node_hash(&self, _state: &mut dyn Hasher) {}
 }
 
 /// Creates a unary expression NOT
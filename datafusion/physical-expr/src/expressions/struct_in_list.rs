@@ -0,0 +1,288 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Row-value IN expression over a list of literal tuples
+
+use std::any::Any;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray};
+use arrow::{
+    datatypes::{DataType, Schema},
+    record_batch::RecordBatch,
+};
+
+use crate::PhysicalExpr;
+use datafusion_common::ScalarValue;
+use datafusion_common::{DataFusionError, Result};
+use datafusion_expr::ColumnarValue;
+
+/// Row-value IN expression, e.g. `(a, b) IN ((1, 'x'), (2, 'y'))`, testing
+/// whether the values of several expressions match one of a list of tuples
+/// at once. Unlike `a IN (1, 2) AND b IN ('x', 'y')` it does not accept
+/// combinations such as `(1, 'y')` that appear in no tuple.
+///
+/// Tuples are compared as in SQL: a NULL component compares as unknown, so a
+/// row matching no tuple evaluates to NULL rather than `false` if it could
+/// match a tuple but for a NULL.
+#[derive(Debug)]
+pub struct StructInListExpr {
+    exprs: Vec<Arc<dyn PhysicalExpr>>,
+    tuples: Vec<Vec<ScalarValue>>,
+    negated: bool,
+    /// Tuples without NULL components
+    set: HashSet<Vec<ScalarValue>>,
+    /// Tuples with a NULL component, which never match but may be unknown
+    null_tuples: Vec<Vec<ScalarValue>>,
+}
+
+impl StructInListExpr {
+    /// Create a new row-value IN expression. Every tuple must have one value
+    /// per expression, of the type that expression evaluates to.
+    pub fn try_new(
+        exprs: Vec<Arc<dyn PhysicalExpr>>,
+        tuples: Vec<Vec<ScalarValue>>,
+        negated: bool,
+    ) -> Result<Self> {
+        if let Some(tuple) = tuples.iter().find(|t| t.len() != exprs.len()) {
+            return Err(DataFusionError::Plan(format!(
+                "Row-value IN of {} expressions got a tuple of {} values",
+                exprs.len(),
+                tuple.len()
+            )));
+        }
+        let (null_tuples, set): (Vec<_>, Vec<_>) = tuples
+            .iter()
+            .cloned()
+            .partition(|t| t.iter().any(|v| v.is_null()));
+        Ok(Self {
+            exprs,
+            tuples,
+            negated,
+            set: set.into_iter().collect(),
+            null_tuples,
+        })
+    }
+
+    /// Input expressions
+    pub fn exprs(&self) -> &[Arc<dyn PhysicalExpr>] {
+        &self.exprs
+    }
+
+    /// Tuples to search in, one value per input expression
+    pub fn tuples(&self) -> &[Vec<ScalarValue>] {
+        &self.tuples
+    }
+
+    /// Is this negated e.g. NOT IN
+    pub fn negated(&self) -> bool {
+        self.negated
+    }
+
+    fn contains(&self, row: &[ScalarValue]) -> Option<bool> {
+        if !row.iter().any(|v| v.is_null()) && self.set.contains(row) {
+            return Some(true);
+        }
+        let mut result = Some(false);
+        let candidates: Box<dyn Iterator<Item = &Vec<ScalarValue>>> =
+            if row.iter().any(|v| v.is_null()) {
+                Box::new(self.set.iter().chain(&self.null_tuples))
+            } else {
+                Box::new(self.null_tuples.iter())
+            };
+        for tuple in candidates {
+            match compare_row_values(row, tuple) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => result = None,
+            }
+        }
+        result
+    }
+}
+
+/// Compare two sequences of values as SQL compares row values: `Some(true)`
+/// if they are equal, `Some(false)` if their lengths or any pair of non-null
+/// values differ, and `None` if they are equal but for a NULL
+pub(crate) fn compare_row_values(
+    left: &[ScalarValue],
+    right: &[ScalarValue],
+) -> Option<bool> {
+    if left.len() != right.len() {
+        return Some(false);
+    }
+    let mut result = Some(true);
+    for (l, r) in left.iter().zip(right) {
+        if l.is_null() || r.is_null() {
+            result = None;
+        } else if l != r {
+            return Some(false);
+        }
+    }
+    result
+}
+
+impl std::fmt::Display for StructInListExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let exprs = self
+            .exprs
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let tuples = self
+            .tuples
+            .iter()
+            .map(|t| {
+                let values = t.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                format!("({})", values.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.negated {
+            write!(f, "({}) NOT IN ({})", exprs, tuples)
+        } else {
+            write!(f, "({}) IN ({})", exprs, tuples)
+        }
+    }
+}
+
+impl PhysicalExpr for StructInListExpr {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, _input_schema: &Schema) -> Result<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
+        if !self.null_tuples.is_empty() {
+            return Ok(true);
+        }
+        for expr in &self.exprs {
+            if expr.nullable(input_schema)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        let arrays = self
+            .exprs
+            .iter()
+            .map(|e| Ok(e.evaluate(batch)?.into_array(batch.num_rows())))
+            .collect::<Result<Vec<ArrayRef>>>()?;
+        let result = (0..batch.num_rows())
+            .map(|i| {
+                let row = arrays
+                    .iter()
+                    .map(|a| ScalarValue::try_from_array(a, i))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(self.contains(&row).map(|found| found != self.negated))
+            })
+            .collect::<Result<BooleanArray>>()?;
+        Ok(ColumnarValue::Array(Arc::new(result)))
+    }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        self.exprs.clone()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn PhysicalExpr>> {
+        Ok(Arc::new(Self::try_new(
+            children,
+            self.tuples.clone(),
+            self.negated,
+        )?))
+    }
+}
+
+/// Creates a row-value IN expression
+pub fn struct_in_list(
+    exprs: Vec<Arc<dyn PhysicalExpr>>,
+    tuples: Vec<Vec<ScalarValue>>,
+    negated: &bool,
+) -> Result<Arc<dyn PhysicalExpr>> {
+    Ok(Arc::new(StructInListExpr::try_new(
+        exprs, tuples, *negated,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{Int64Array, StringArray},
+        datatypes::Field,
+    };
+
+    use super::*;
+    use crate::expressions::col;
+
+    #[test]
+    fn struct_in_list_nulls() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let a = Int64Array::from(vec![Some(1), Some(1), None, Some(4), Some(3)]);
+        let b = StringArray::from(vec![Some("x"), Some("y"), Some("x"), None, None]);
+        let exprs = vec![col("a", &schema)?, col("b", &schema)?];
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(a), Arc::new(b)])?;
+
+        // expression: "(a, b) in ((1, 'x'), (2, 'y'), (3, NULL))"
+        let tuples = vec![
+            vec![
+                ScalarValue::Int64(Some(1)),
+                ScalarValue::Utf8(Some("x".into())),
+            ],
+            vec![
+                ScalarValue::Int64(Some(2)),
+                ScalarValue::Utf8(Some("y".into())),
+            ],
+            vec![ScalarValue::Int64(Some(3)), ScalarValue::Utf8(None)],
+        ];
+        for (negated, expected) in [
+            (
+                false,
+                vec![Some(true), Some(false), None, Some(false), None],
+            ),
+            (true, vec![Some(false), Some(true), None, Some(true), None]),
+        ] {
+            let expr = struct_in_list(exprs.clone(), tuples.clone(), &negated)?;
+            let result = expr.evaluate(&batch)?.into_array(batch.num_rows());
+            let result = result.as_any().downcast_ref::<BooleanArray>().unwrap();
+            assert_eq!(result, &BooleanArray::from(expected));
+        }
+
+        let err = StructInListExpr::try_new(
+            exprs,
+            vec![vec![ScalarValue::Int64(Some(1))]],
+            false,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("tuple of 1 values"));
+        Ok(())
+    }
+}
@@ -17,6 +17,7 @@
 
 use std::any::Any;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use crate::PhysicalExpr;
@@ -90,6 +91,24 @@ impl PhysicalExpr for TryCastExpr {
             }
         }
     }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+
+    fn node_eq(&self, other: &dyn Any) -> bool {
+        other
+            .downcast_ref::<Self>()
+            .map_or(false, |o| self.cast_type == o.cast_type)
+    }
+
+    fn node_hash(&self, mut state: &mut dyn Hasher) {
+        self.cast_type.hash(&mut state)
+    }
 }
 
 /// Return a PhysicalExpression representing `expr` casted to
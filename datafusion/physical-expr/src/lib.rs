@@ -42,7 +42,7 @@ pub mod var_provider;
 pub mod window;
 
 pub use aggregate::AggregateExpr;
-pub use physical_expr::PhysicalExpr;
+pub use physical_expr::{physical_expr_eq, physical_expr_hash, PhysicalExpr};
 pub use planner::create_physical_expr;
 pub use scalar_function::ScalarFunctionExpr;
 pub use sort_expr::PhysicalSortExpr;
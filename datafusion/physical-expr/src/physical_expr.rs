@@ -19,7 +19,7 @@ use arrow::datatypes::{DataType, Schema};
 
 use arrow::record_batch::RecordBatch;
 
-use datafusion_common::Result;
+use datafusion_common::{DataFusionError, Result};
 
 use datafusion_expr::ColumnarValue;
 use std::fmt::{Debug, Display};
@@ -27,6 +27,8 @@ use std::fmt::{Debug, Display};
 use arrow::array::{make_array, Array, ArrayRef, BooleanArray, MutableArrayData};
 use arrow::compute::{and_kleene, filter_record_batch, is_not_null, SlicesIterator};
 use std::any::Any;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 /// Expression that can be evaluated against a RecordBatch
 /// A Physical expression knows its type, nullability and how to evaluate itself.
@@ -40,6 +42,37 @@ pub trait PhysicalExpr: Send + Sync + Display + Debug {
     fn nullable(&self, input_schema: &Schema) -> Result<bool>;
     /// Evaluate an expression against a RecordBatch
     fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue>;
+    /// Returns true if this expression evaluates to the same value for every
+    /// row of any input, so it can be folded to a constant before execution
+    fn is_constant(&self) -> bool {
+        false
+    }
+    /// Get the child expressions of this expression, none by default
+    fn children(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![]
+    }
+    /// Returns a new expression with its children replaced by `children`, in
+    /// the order returned by [`PhysicalExpr::children`]
+    fn with_new_children(
+        self: Arc<Self>,
+        _children: Vec<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn PhysicalExpr>> {
+        Err(DataFusionError::NotImplemented(format!(
+            "{} does not support with_new_children",
+            self
+        )))
+    }
+    /// Returns true if `other` is the same kind of expression as this one with
+    /// the same fields apart from its children, which [`physical_expr_eq`]
+    /// compares. Expressions that don't override this never compare equal,
+    /// unless they are the same instance
+    fn node_eq(&self, _other: &dyn Any) -> bool {
+        false
+    }
+    /// Feed the fields compared by [`PhysicalExpr::node_eq`] into `state`
+    fn node_hash(&self, mut state: &mut dyn Hasher) {
+        self.to_string().hash(&mut state)
+    }
     /// Evaluate an expression against a RecordBatch after first applying a
     /// validity array
     fn evaluate_selection(
@@ -63,6 +96,32 @@ pub trait PhysicalExpr: Send + Sync + Display + Debug {
     }
 }
 
+/// Returns true if both expressions are the same tree, comparing each node
+/// with [`PhysicalExpr::node_eq`] and then its children
+pub fn physical_expr_eq(
+    left: &Arc<dyn PhysicalExpr>,
+    right: &Arc<dyn PhysicalExpr>,
+) -> bool {
+    if Arc::as_ptr(left) as *const () == Arc::as_ptr(right) as *const () {
+        return true;
+    }
+    let (left_children, right_children) = (left.children(), right.children());
+    left.node_eq(right.as_any())
+        && left_children.len() == right_children.len()
+        && left_children
+            .iter()
+            .zip(&right_children)
+            .all(|(l, r)| physical_expr_eq(l, r))
+}
+
+/// Feed a hash of `expr` consistent with [`physical_expr_eq`] into `state`
+pub fn physical_expr_hash(expr: &Arc<dyn PhysicalExpr>, state: &mut impl Hasher) {
+    expr.node_hash(state);
+    for child in expr.children() {
+        physical_expr_hash(&child, state);
+    }
+}
+
 /// Scatter `truthy` array by boolean mask. When the mask evaluates `true`, next values of `truthy`
 /// are taken, when the mask evaluates `false` values null values are filled.
 ///